@@ -1,31 +1,159 @@
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeAmount;
+use anchor_spl::token_2022::spl_token_2022::state::{Account as TokenAccountState, Mint as MintState};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 declare_id!("Aw5FwXAnbzB6e7A5zrw8G244VnwW3vV3Uz5rrDFt6ipj");
 
 /// Minimum time buffer before unlock (5 minutes)
 pub const MIN_UNLOCK_BUFFER: i64 = 300;
 
+/// Pause flags (bitmask stored in `Config.pause_flags`)
+pub const PAUSE_CREATE: u8 = 1 << 0;
+pub const PAUSE_DEPOSIT: u8 = 1 << 1;
+pub const PAUSE_CANCEL: u8 = 1 << 2;
+pub const PAUSE_RELEASE: u8 = 1 << 3;
+pub const PAUSE_CHECK_IN: u8 = 1 << 4;
+pub const PAUSE_WITHDRAW: u8 = 1 << 5;
+/// Deposit freeze: no new money in, check-ins and releases keep working
+pub const PAUSE_DEPOSIT_FREEZE: u8 = PAUSE_CREATE | PAUSE_DEPOSIT;
+pub const PAUSE_ALL: u8 =
+    PAUSE_CREATE | PAUSE_DEPOSIT | PAUSE_CANCEL | PAUSE_RELEASE | PAUSE_CHECK_IN | PAUSE_WITHDRAW;
+
+/// Wallet capability flags (bitmask stored in `WalletFlags.flags`)
+pub const WALLET_FLAG_SELF_BENEFICIARY: u8 = 1 << 0;  // May name itself beneficiary (QA vaults)
+pub const WALLET_FLAG_FEE_EXEMPT: u8 = 1 << 1;        // No creation or closing fee
+pub const WALLET_FLAG_CAP_EXEMPT: u8 = 1 << 2;        // Deposits ignore max_lock_per_vault
+pub const WALLET_FLAGS_ALL: u8 =
+    WALLET_FLAG_SELF_BENEFICIARY | WALLET_FLAG_FEE_EXEMPT | WALLET_FLAG_CAP_EXEMPT;
+
+/// Admin council limits
+pub const MAX_COUNCIL_MEMBERS: usize = 10;
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 12;
+pub const MAX_PROPOSAL_DATA_LEN: usize = 512;
+
+/// Upper bound on the config change timelock (30 days)
+pub const MAX_CONFIG_TIMELOCK_SECONDS: u32 = 2_592_000;
+
+/// Current `Config` schema version (0 = legacy layout without a version byte)
+pub const CONFIG_VERSION: u8 = 1;
+
+/// Current `Vault` schema version (1 = legacy layout without `schema_version`)
+pub const VAULT_SCHEMA_VERSION: u8 = 2;
+
+/// Hard cap on the release fee (5%), whatever the fee schedule says
+pub const MAX_RELEASE_FEE_BPS: u16 = 500;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Maximum number of payees a vault's release can be split between
+pub const MAX_BENEFICIARIES: usize = 5;
+
+/// Delay before `update_beneficiary` takes effect (default 7 days, max 30 days)
+pub const DEFAULT_BENEFICIARY_COOLDOWN_SECONDS: u32 = 604_800;
+pub const MAX_BENEFICIARY_COOLDOWN_SECONDS: u32 = 2_592_000;
+
+/// Longest claim window before a backup beneficiary becomes eligible (1 year)
+pub const MAX_CLAIM_WINDOW_SECONDS: u32 = 31_536_000;
+
+/// Most tranches a release schedule can have (10 years of monthly payments)
+pub const MAX_RELEASE_TRANCHES: u16 = 120;
+
+/// Most extra assets (SPL tokens or NFTs) a vault can register with `deposit_token`
+pub const MAX_VAULT_ASSETS: usize = 8;
+
+/// Most keys besides the creator that may check in for a vault
+pub const MAX_CHECKIN_DELEGATES: usize = 5;
+
+/// Prefix of the heartbeat message signed off-chain for `relay_check_in`
+pub const RELAY_CHECK_IN_DOMAIN: &[u8] = b"keepr-vault:check_in:v1";
+
+/// Longest a signed heartbeat may stay valid for `relay_check_in` (15 minutes)
+pub const MAX_RELAY_EXPIRY_SECONDS: i64 = 900;
+
 #[program]
 pub mod keepr_vault {
     use super::*;
 
-    /// Initialize the global config (one-time, admin only)
+    /// Initialize the global config (one-time, admin only). The config PDA can
+    /// never be closed, so this fails once a config exists and later changes go
+    /// through `queue_config_change` / `execute_config_change`.
     pub fn init_config(
         ctx: Context<InitConfig>,
         usdc_mint: Pubkey,
         max_lock_per_vault: u64,
-        paused: bool,
+        pause_flags: u8,
         treasury: Pubkey,
+        config_timelock_seconds: u32,
     ) -> Result<()> {
+        validate_pause_flags(pause_flags)?;
+        require!(
+            config_timelock_seconds <= MAX_CONFIG_TIMELOCK_SECONDS,
+            KeeprError::InvalidTimelock
+        );
+
         let config = &mut ctx.accounts.config;
         config.admin = ctx.accounts.admin.key();
+        config.version = CONFIG_VERSION;
         config.usdc_mint = usdc_mint;
         config.max_lock_per_vault = max_lock_per_vault;
-        config.paused = paused;
-        config.admin_test_wallets = Vec::new();
+        config.pause_flags = pause_flags;
         config.treasury = treasury;
+        config.pending_admin = None;
+        config.config_timelock_seconds = config_timelock_seconds;
+        config.fee_schedule = FeeSchedule::DEFAULT;
+        config.beneficiary_cooldown_seconds = DEFAULT_BENEFICIARY_COOLDOWN_SECONDS;
+        config.min_vault_balance = 0;
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+        });
+
+        if pause_flags != 0 {
+            emit!(ProtocolPaused {
+                admin: config.admin,
+                previous_flags: 0,
+                pause_flags,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Update config parameters that must apply immediately (admin or pauser).
+    /// A pauser can only set additional pause flags; clearing them needs the admin.
+    /// Mint, cap and timelock changes go through `queue_config_change`.
+    pub fn update_config(ctx: Context<RoleGatedConfig>, pause_flags: Option<u8>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let is_admin = ctx.accounts.require_role(Role::Pauser)?;
+        let config = &mut ctx.accounts.config;
+
+        if let Some(flags) = pause_flags {
+            validate_pause_flags(flags)?;
+
+            let previous_flags = config.pause_flags;
+            if !is_admin {
+                require!(
+                    flags & previous_flags == previous_flags,
+                    KeeprError::PauserCannotUnpause
+                );
+            }
+            config.pause_flags = flags;
+
+            if previous_flags != flags {
+                emit!(ProtocolPaused {
+                    admin: authority,
+                    previous_flags,
+                    pause_flags: flags,
+                });
+            }
+        }
 
         emit!(ConfigUpdated {
             admin: config.admin,
@@ -34,79 +162,571 @@ pub mod keepr_vault {
         Ok(())
     }
 
-    /// Update config parameters (admin only)
-    pub fn update_config(
-        ctx: Context<UpdateConfig>,
+    /// Queue a config change that can execute after `config_timelock_seconds` (admin only).
+    /// Only one change can be queued at a time. Rotating the treasury goes through here too.
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
         usdc_mint: Option<Pubkey>,
         max_lock_per_vault: Option<u64>,
-        paused: Option<bool>,
+        config_timelock_seconds: Option<u32>,
+        treasury: Option<Pubkey>,
+        beneficiary_cooldown_seconds: Option<u32>,
+        min_vault_balance: Option<u64>,
     ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let change = &mut ctx.accounts.config_change;
+        let clock = Clock::get()?;
+
+        require!(
+            usdc_mint.is_some()
+                || max_lock_per_vault.is_some()
+                || config_timelock_seconds.is_some()
+                || treasury.is_some()
+                || beneficiary_cooldown_seconds.is_some()
+                || min_vault_balance.is_some(),
+            KeeprError::EmptyConfigChange
+        );
+        if let Some(delay) = config_timelock_seconds {
+            require!(delay <= MAX_CONFIG_TIMELOCK_SECONDS, KeeprError::InvalidTimelock);
+        }
+        if let Some(cooldown) = beneficiary_cooldown_seconds {
+            require!(
+                cooldown <= MAX_BENEFICIARY_COOLDOWN_SECONDS,
+                KeeprError::InvalidBeneficiaryCooldown
+            );
+        }
+
+        change.usdc_mint = usdc_mint;
+        change.max_lock_per_vault = max_lock_per_vault;
+        change.config_timelock_seconds = config_timelock_seconds;
+        change.treasury = treasury;
+        change.beneficiary_cooldown_seconds = beneficiary_cooldown_seconds;
+        change.min_vault_balance = min_vault_balance;
+        change.queued_at = clock.unix_timestamp;
+        change.eta = clock
+            .unix_timestamp
+            .checked_add(config.config_timelock_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+        change.payer = ctx.accounts.payer.key();
+        change.bump = ctx.bumps.config_change;
+
+        emit!(ConfigChangeQueued {
+            usdc_mint,
+            max_lock_per_vault,
+            config_timelock_seconds,
+            treasury,
+            beneficiary_cooldown_seconds,
+            min_vault_balance,
+            eta: change.eta,
+        });
+
+        Ok(())
+    }
+
+    /// Apply the queued config change once its timelock has elapsed (anyone can call).
+    /// A treasury rotation must pass the new treasury's USDC ATA so fees can't be stranded.
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
         let config = &mut ctx.accounts.config;
+        let change = &ctx.accounts.config_change;
+        let clock = Clock::get()?;
+
+        require!(
+            clock.unix_timestamp >= change.eta,
+            KeeprError::ConfigChangeNotReady
+        );
 
-        if let Some(mint) = usdc_mint {
+        if let Some(mint) = change.usdc_mint {
             config.usdc_mint = mint;
         }
-        if let Some(cap) = max_lock_per_vault {
+        if let Some(cap) = change.max_lock_per_vault {
             config.max_lock_per_vault = cap;
         }
-        if let Some(p) = paused {
-            config.paused = p;
+        if let Some(delay) = change.config_timelock_seconds {
+            config.config_timelock_seconds = delay;
+        }
+        if let Some(cooldown) = change.beneficiary_cooldown_seconds {
+            config.beneficiary_cooldown_seconds = cooldown;
+        }
+        if let Some(min_balance) = change.min_vault_balance {
+            config.min_vault_balance = min_balance;
+        }
+        if let Some(new_treasury) = change.treasury {
+            // Validate against the mint in effect after this change
+            let treasury_ata = ctx
+                .accounts
+                .new_treasury_usdc_ata
+                .as_ref()
+                .ok_or(KeeprError::MissingTreasuryAta)?;
+            require_keys_eq!(
+                treasury_ata.key(),
+                get_associated_token_address_with_program_id(
+                    &new_treasury,
+                    &config.usdc_mint,
+                    treasury_ata.to_account_info().owner,
+                ),
+                KeeprError::MissingTreasuryAta
+            );
+
+            let old_treasury = config.treasury;
+            config.treasury = new_treasury;
+
+            emit!(TreasuryUpdated {
+                old: old_treasury,
+                new: new_treasury,
+            });
         }
 
+        emit!(ConfigChangeExecuted {
+            usdc_mint: change.usdc_mint,
+            max_lock_per_vault: change.max_lock_per_vault,
+            config_timelock_seconds: change.config_timelock_seconds,
+            treasury: change.treasury,
+            beneficiary_cooldown_seconds: change.beneficiary_cooldown_seconds,
+            min_vault_balance: change.min_vault_balance,
+        });
+
         emit!(ConfigUpdated {
             admin: config.admin,
         });
 
+        // Queued change account closed automatically via close constraint
+        Ok(())
+    }
+
+    /// Drop the queued config change (admin only)
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        emit!(ConfigChangeCancelled {
+            admin: ctx.accounts.admin.key(),
+            eta: ctx.accounts.config_change.eta,
+        });
+
+        // Queued change account closed automatically via close constraint
         Ok(())
     }
 
-    /// Update admin test wallets list (admin only)
-    pub fn update_admin_test_wallets(
-        ctx: Context<UpdateConfig>,
-        wallets: Vec<Pubkey>,
+    /// Replace the per-tier fee schedule (admin or fee manager)
+    pub fn update_fee_schedule(
+        ctx: Context<RoleGatedConfig>,
+        fee_schedule: FeeSchedule,
     ) -> Result<()> {
+        ctx.accounts.require_role(Role::FeeManager)?;
+        fee_schedule.validate()?;
+
         let config = &mut ctx.accounts.config;
+        config.fee_schedule = fee_schedule;
+
+        emit!(FeeScheduleUpdated {
+            admin: ctx.accounts.authority.key(),
+            fee_schedule,
+        });
 
+        Ok(())
+    }
+
+    /// Create or update a wallet's capability flags (admin or test-wallet manager)
+    pub fn set_wallet_flags(ctx: Context<SetWalletFlags>, wallet: Pubkey, flags: u8) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.role_assignment,
+            Role::TestWalletManager,
+        )?;
         require!(
-            wallets.len() <= 10,
-            KeeprError::AdminTestWalletsLimitExceeded
+            flags != 0 && flags & !WALLET_FLAGS_ALL == 0,
+            KeeprError::InvalidWalletFlags
         );
 
-        config.admin_test_wallets = wallets;
+        let wallet_flags = &mut ctx.accounts.wallet_flags;
+        let previous_flags = wallet_flags.flags;
+        if wallet_flags.wallet == Pubkey::default() {
+            wallet_flags.wallet = wallet;
+            wallet_flags.payer = ctx.accounts.payer.key();
+            wallet_flags.bump = ctx.bumps.wallet_flags;
+        }
+        wallet_flags.flags = flags;
+        wallet_flags.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(WalletFlagsSet {
+            authority: ctx.accounts.authority.key(),
+            wallet,
+            previous_flags,
+            flags,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a wallet's capability flags (admin or test-wallet manager)
+    pub fn remove_wallet_flags(ctx: Context<RemoveWalletFlags>) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.role_assignment,
+            Role::TestWalletManager,
+        )?;
+
+        emit!(WalletFlagsRemoved {
+            authority: ctx.accounts.authority.key(),
+            wallet: ctx.accounts.wallet_flags.wallet,
+            flags: ctx.accounts.wallet_flags.flags,
+        });
 
-        emit!(ConfigUpdated {
+        Ok(())
+    }
+
+    /// Propose a new admin (admin only); takes effect once the new admin accepts
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(
+            new_admin != Pubkey::default() && new_admin != config.admin,
+            KeeprError::InvalidAdmin
+        );
+
+        config.pending_admin = Some(new_admin);
+
+        emit!(AdminTransferProposed {
+            admin: config.admin,
+            pending_admin: new_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a pending admin proposal (pending admin only)
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        let previous_admin = config.admin;
+        config.admin = ctx.accounts.new_admin.key();
+        config.pending_admin = None;
+
+        emit!(AdminTransferred {
+            previous_admin,
+            new_admin: config.admin,
+        });
+
+        Ok(())
+    }
+
+    /// Drop a pending admin proposal (admin only)
+    pub fn cancel_admin_proposal(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        let pending_admin = config.pending_admin.take().ok_or(KeeprError::NoPendingAdmin)?;
+
+        emit!(AdminTransferCancelled {
             admin: config.admin,
+            pending_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Grant `role` to `holder` (admin only)
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+        let assignment = &mut ctx.accounts.role_assignment;
+        assignment.role = role;
+        assignment.holder = holder;
+        assignment.granted_at = Clock::get()?.unix_timestamp;
+        assignment.payer = ctx.accounts.payer.key();
+        assignment.bump = ctx.bumps.role_assignment;
+
+        emit!(RoleGranted {
+            admin: ctx.accounts.admin.key(),
+            role,
+            holder,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a previously granted role (admin only)
+    pub fn revoke_role(ctx: Context<RevokeRole>) -> Result<()> {
+        emit!(RoleRevoked {
+            admin: ctx.accounts.admin.key(),
+            role: ctx.accounts.role_assignment.role,
+            holder: ctx.accounts.role_assignment.holder,
+        });
+
+        Ok(())
+    }
+
+    /// List a mint other than the config mint, or update its cap, fees or
+    /// status (admin only). Amounts are in the mint's base units. Disabling
+    /// stops new vaults and deposits; existing vaults still pay out.
+    /// Call again after rotating the treasury to create its token account.
+    pub fn set_supported_mint(
+        ctx: Context<SetSupportedMint>,
+        max_lock_per_vault: u64,
+        fee_schedule: FeeSchedule,
+        enabled: bool,
+    ) -> Result<()> {
+        let mint = ctx.accounts.mint.key();
+        require!(mint != ctx.accounts.config.usdc_mint, KeeprError::ConfigMintListed);
+        require_custody_safe_mint(&ctx.accounts.mint)?;
+        fee_schedule.validate()?;
+
+        let supported_mint = &mut ctx.accounts.supported_mint;
+        supported_mint.mint = mint;
+        supported_mint.max_lock_per_vault = max_lock_per_vault;
+        supported_mint.fee_schedule = fee_schedule;
+        supported_mint.enabled = enabled;
+        supported_mint.updated_at = Clock::get()?.unix_timestamp;
+        supported_mint.bump = ctx.bumps.supported_mint;
+
+        emit!(SupportedMintSet {
+            admin: ctx.accounts.admin.key(),
+            mint,
+            max_lock_per_vault,
+            fee_schedule,
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade the legacy (v0) config account to the current schema in place (admin only).
+    /// Resizes to `Config::INIT_SPACE`, keeps admin/mint/treasury/limits and fills fields
+    /// the old layout didn't have with defaults. The inline test wallets don't carry over:
+    /// they are listed in `ConfigMigrated` so they can be re-added with `set_wallet_flags`.
+    /// The current layout is smaller, so the rent it no longer needs goes to the admin.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+
+        let legacy = {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() > 40 && data[..8] == *Config::DISCRIMINATOR,
+                KeeprError::UnsupportedConfigVersion
+            );
+
+            // The legacy layout predates the version byte and is identified by size
+            if data.len() != 8 + ConfigV0::INIT_SPACE {
+                return match data[40] {
+                    CONFIG_VERSION => err!(KeeprError::ConfigUpToDate),
+                    _ => err!(KeeprError::UnsupportedConfigVersion),
+                };
+            }
+            ConfigV0::deserialize(&mut &data[8..])?
+        };
+
+        require_keys_eq!(
+            legacy.admin,
+            ctx.accounts.admin.key(),
+            KeeprError::NotConfigAdmin
+        );
+
+        let dropped_test_wallets = legacy.admin_test_wallets.clone();
+        let migrated = legacy.migrate();
+
+        resize_account(
+            &config_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + Config::INIT_SPACE,
+        )?;
+        let excess_rent = config_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(8 + Config::INIT_SPACE));
+        move_lamports(&config_info, &ctx.accounts.admin.to_account_info(), excess_rent)?;
+
+        let mut data = config_info.try_borrow_mut_data()?;
+        migrated.try_serialize(&mut &mut data[..])?;
+
+        emit!(ConfigMigrated {
+            admin: migrated.admin,
+            from_version: 0,
+            to_version: CONFIG_VERSION,
+            dropped_test_wallets,
+            excess_rent,
+        });
+
+        Ok(())
+    }
+
+    /// Create the admin council (admin only). Hand the config over to it with
+    /// `propose_admin(council)` followed by a council-executed `accept_admin`.
+    pub fn init_council(
+        ctx: Context<InitCouncil>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_council(&members, threshold)?;
+
+        let council = &mut ctx.accounts.council;
+        council.members = members;
+        council.threshold = threshold;
+        council.proposal_count = 0;
+        council.members_epoch = 0;
+        council.bump = ctx.bumps.council;
+
+        emit!(CouncilUpdated {
+            council: council.key(),
+            members: council.members.clone(),
+            threshold,
+            members_epoch: council.members_epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Replace council members and threshold (council PDA only, via an executed proposal).
+    /// Bumps the members epoch so proposals approved by the old membership can't execute.
+    pub fn update_council(
+        ctx: Context<UpdateCouncil>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_council(&members, threshold)?;
+
+        let council = &mut ctx.accounts.council;
+        council.members = members;
+        council.threshold = threshold;
+        council.members_epoch = council
+            .members_epoch
+            .checked_add(1)
+            .ok_or(KeeprError::Overflow)?;
+
+        emit!(CouncilUpdated {
+            council: council.key(),
+            members: council.members.clone(),
+            threshold,
+            members_epoch: council.members_epoch,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a privileged instruction for the council to execute (member only).
+    /// `data` and `accounts` describe a keepr_vault instruction signed by the council PDA.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        data: Vec<u8>,
+        accounts: Vec<ProposalAccountMeta>,
+    ) -> Result<()> {
+        let council = &mut ctx.accounts.council;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(
+            data.len() <= MAX_PROPOSAL_DATA_LEN && accounts.len() <= MAX_PROPOSAL_ACCOUNTS,
+            KeeprError::ProposalTooLarge
+        );
+
+        let member_index = council.member_index(&ctx.accounts.proposer.key())?;
+
+        proposal.proposal_id = council.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.members_epoch = council.members_epoch;
+        proposal.approvals = 1 << member_index; // Proposer approves implicitly
+        proposal.executed = false;
+        proposal.created_at = Clock::get()?.unix_timestamp;
+        proposal.accounts = accounts;
+        proposal.data = data;
+        proposal.bump = ctx.bumps.proposal;
+
+        council.proposal_count = council
+            .proposal_count
+            .checked_add(1)
+            .ok_or(KeeprError::Overflow)?;
+
+        emit!(ProposalCreated {
+            proposal: proposal.key(),
+            proposal_id: proposal.proposal_id,
+            proposer: proposal.proposer,
         });
 
         Ok(())
     }
 
-    /// Close config account (admin only, for devnet schema migrations)
-    pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
-        // Manually verify admin from raw account data (offset 8, first 32 bytes after discriminator)
-        let config_data = ctx.accounts.config.try_borrow_data()?;
-        require!(config_data.len() >= 40, KeeprError::InvalidAmount); // 8 discriminator + 32 admin
+    /// Approve a pending proposal (member only)
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let council = &ctx.accounts.council;
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(!proposal.executed, KeeprError::ProposalAlreadyExecuted);
+        require!(
+            proposal.members_epoch == council.members_epoch,
+            KeeprError::StaleProposal
+        );
+
+        let member_index = council.member_index(&ctx.accounts.member.key())?;
+        let bit = 1u16 << member_index;
+        require!(proposal.approvals & bit == 0, KeeprError::ProposalAlreadyApproved);
+
+        proposal.approvals |= bit;
+
+        emit!(ProposalApproved {
+            proposal: proposal.key(),
+            member: ctx.accounts.member.key(),
+            approval_count: proposal.approvals.count_ones() as u8,
+        });
+
+        Ok(())
+    }
 
-        let admin_bytes = &config_data[8..40];
-        let stored_admin = Pubkey::try_from(admin_bytes)
-            .map_err(|_| KeeprError::InvalidAmount)?;
+    /// Execute a proposal once it has reached the threshold (anyone can call).
+    /// The stored instruction is invoked on this program with the council PDA as signer;
+    /// its accounts are passed, in order, as remaining accounts.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        let council = &ctx.accounts.council;
+        let proposal = &mut ctx.accounts.proposal;
 
+        require!(!proposal.executed, KeeprError::ProposalAlreadyExecuted);
+        require!(
+            proposal.members_epoch == council.members_epoch,
+            KeeprError::StaleProposal
+        );
+        require!(
+            proposal.approvals.count_ones() >= council.threshold as u32,
+            KeeprError::ProposalThresholdNotMet
+        );
         require!(
-            stored_admin == ctx.accounts.admin.key(),
-            KeeprError::InvalidAmount
+            ctx.remaining_accounts.len() == proposal.accounts.len(),
+            KeeprError::ProposalAccountMismatch
         );
 
-        // Manually close the account by transferring lamports to admin
-        let dest_starting_lamports = ctx.accounts.admin.lamports();
-        **ctx.accounts.admin.lamports.borrow_mut() = dest_starting_lamports
-            .checked_add(ctx.accounts.config.lamports())
-            .unwrap();
-        **ctx.accounts.config.lamports.borrow_mut() = 0;
+        let mut metas = Vec::with_capacity(proposal.accounts.len());
+        for (meta, info) in proposal.accounts.iter().zip(ctx.remaining_accounts) {
+            require_keys_eq!(meta.pubkey, info.key(), KeeprError::ProposalAccountMismatch);
+            metas.push(if meta.is_writable {
+                AccountMeta::new(meta.pubkey, meta.is_signer)
+            } else {
+                AccountMeta::new_readonly(meta.pubkey, meta.is_signer)
+            });
+        }
+
+        let instruction = Instruction {
+            program_id: crate::ID,
+            accounts: metas,
+            data: proposal.data.clone(),
+        };
+
+        // Mark executed before the CPI so the proposal can't be replayed from inside it
+        proposal.executed = true;
+        proposal.exit(&crate::ID)?;
+
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(council.to_account_info());
+        account_infos.push(ctx.accounts.keepr_program.to_account_info());
+
+        let seeds = &[b"council".as_ref(), &[council.bump]];
+        invoke_signed(&instruction, &account_infos, &[&seeds[..]])?;
+
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            proposal_id: proposal.proposal_id,
+            executor: ctx.accounts.executor.key(),
+        });
 
         Ok(())
     }
 
     /// Create a new vault (deposit separately)
+    /// `creation_fee_paid` must match the tier's scheduled fee, which is collected into the treasury
+    #[allow(clippy::too_many_arguments)]
     pub fn create_vault(
         ctx: Context<CreateVault>,
         beneficiary: Pubkey,
@@ -116,15 +736,24 @@ pub mod keepr_vault {
         grace_period_seconds: u32,
         tier: VaultTier,
         creation_fee_paid: u64,
+        release_schedule: Option<ReleaseSchedule>,
     ) -> Result<()> {
         let config = &ctx.accounts.config;
         let vault = &mut ctx.accounts.vault;
         let counter = &mut ctx.accounts.counter;
         let clock = Clock::get()?;
 
-        // Check if creator is an admin tester
+        config.require_not_paused(PAUSE_CREATE)?;
+        require_custody_safe_mint(&ctx.accounts.mint)?;
+        let mint = ctx.accounts.mint.key();
+        let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+        config.require_mint_open(&mint, supported_mint)?;
+        let (_, fee_schedule) = config.mint_terms(&mint, supported_mint)?;
+
+        // Capabilities granted to the creator via set_wallet_flags
         let creator_key = ctx.accounts.creator.key();
-        let is_admin_tester = config.admin_test_wallets.contains(&creator_key);
+        let wallet_flags = WalletFlags::of(ctx.accounts.wallet_flags.as_deref());
+        let is_admin_tester = wallet_flags & WALLET_FLAG_SELF_BENEFICIARY != 0;
 
         // Validate beneficiary is not creator (skip for admin testers)
         if !is_admin_tester {
@@ -146,27 +775,35 @@ pub mod keepr_vault {
         // Increment counter
         counter.last_id = vault_id;
 
-        // Validate check-in period (must be positive and reasonable)
-        require!(checkin_period_seconds > 0, KeeprError::InvalidCheckinPeriod);
-        require!(
-            checkin_period_seconds <= 31536000, // Max 1 year
-            KeeprError::InvalidCheckinPeriod
-        );
+        validate_vault_timers(checkin_period_seconds, notification_window_seconds, grace_period_seconds)?;
+        if let Some(schedule) = &release_schedule {
+            schedule.validate()?;
+        }
 
-        // Validate dead man's switch parameters
-        require!(
-            notification_window_seconds > 0,
-            KeeprError::InvalidNotificationWindow
-        );
-        require!(
-            notification_window_seconds < checkin_period_seconds,
-            KeeprError::InvalidNotificationWindow
-        );
+        // The caller's quoted fee must match the schedule for the chosen tier
+        let creation_fee = if wallet_flags & WALLET_FLAG_FEE_EXEMPT != 0 {
+            0
+        } else {
+            fee_schedule.for_tier(tier).creation_fee
+        };
         require!(
-            grace_period_seconds > 0,
-            KeeprError::InvalidGracePeriod
+            creation_fee_paid == creation_fee,
+            KeeprError::CreationFeeMismatch
         );
 
+        // Collect creation fee (if applicable)
+        if creation_fee > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.creator_usdc_ata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.treasury_usdc_ata.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            token_interface::transfer_checked(cpi_ctx, creation_fee, ctx.accounts.mint.decimals)?;
+        }
+
         // Calculate initial unlock time (creation time + checkin period)
         let unlock_unix = clock
             .unix_timestamp
@@ -176,7 +813,7 @@ pub mod keepr_vault {
         // Initialize vault
         vault.creator = ctx.accounts.creator.key();
         vault.beneficiary = beneficiary;
-        vault.usdc_mint = config.usdc_mint;
+        vault.mint = mint;
         vault.vault_token_account = ctx.accounts.vault_token_account.key();
         vault.amount_locked = 0;
         vault.unlock_unix = unlock_unix;
@@ -192,8 +829,10 @@ pub mod keepr_vault {
         vault.last_checkin_unix = 0; // Set to 0 on creation (not yet checked in)
         vault.tier = tier;
         vault.created_at = clock.unix_timestamp;
-        vault.creation_fee_paid = creation_fee_paid;
+        vault.creation_fee_paid = creation_fee;
         vault.checkin_period_seconds = checkin_period_seconds;
+        vault.schema_version = VAULT_SCHEMA_VERSION;
+        vault.release_schedule = release_schedule.unwrap_or_default();
 
         emit!(VaultCreated {
             creator: vault.creator,
@@ -211,264 +850,604 @@ pub mod keepr_vault {
         let clock = Clock::get()?;
 
         // Validations (optimized order: cheapest checks first)
+        ctx.accounts.config.require_not_paused(PAUSE_DEPOSIT)?;
+        require!(vault.kind == VaultKind::Token, KeeprError::SolVault);
         require!(amount > 0, KeeprError::InvalidAmount);
         require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
         require!(
             clock.unix_timestamp < vault.unlock_unix,
             KeeprError::DepositAfterUnlock
         );
 
+        let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+        ctx.accounts.config.require_mint_open(&vault.mint, supported_mint)?;
+        let (max_lock_per_vault, _) = ctx.accounts.config.mint_terms(&vault.mint, supported_mint)?;
+
         let new_total = vault
             .amount_locked
             .checked_add(amount)
             .ok_or(KeeprError::InvalidAmount)?;
 
-        require!(
-            new_total <= ctx.accounts.config.max_lock_per_vault,
-            KeeprError::AboveVaultCap
-        );
+        if WalletFlags::of(ctx.accounts.wallet_flags.as_deref()) & WALLET_FLAG_CAP_EXEMPT == 0 {
+            require!(
+                new_total <= max_lock_per_vault,
+                KeeprError::AboveVaultCap
+            );
+        }
 
         // Transfer USDC from creator to vault PDA's token account
-        let cpi_accounts = Transfer {
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.creator_usdc_ata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
             to: ctx.accounts.vault_token_account.to_account_info(),
             authority: ctx.accounts.creator.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
-
-        vault.amount_locked = new_total;
+        let balance_before = ctx.accounts.vault_token_account.amount;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        // Transfer-fee mints deliver less than was sent; lock only what arrived
+        ctx.accounts.vault_token_account.reload()?;
+        let received = ctx
+            .accounts
+            .vault_token_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(KeeprError::Overflow)?;
+        vault.amount_locked = vault
+            .amount_locked
+            .checked_add(received)
+            .ok_or(KeeprError::Overflow)?;
 
         emit!(VaultFunded {
             vault: vault.key(),
-            amount,
+            amount: received,
         });
 
         Ok(())
     }
 
-    /// Check-in to reset vault deadline (creator only, during notification window)
-    pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+    /// Escrow another token (SPL or Token-2022, including 1-of-1 NFTs) in the vault and
+    /// register it in the vault's VaultAssets PDA. Registered assets go to the beneficiary
+    /// in full at `release`, or back to the creator at `cancel_vault`. They are not valued,
+    /// so no cap or fee applies; the vault's own mint goes through `deposit_usdc`.
+    pub fn deposit_token(ctx: Context<DepositToken>, amount: u64) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         let clock = Clock::get()?;
 
-        // Safety checks
+        ctx.accounts.config.require_not_paused(PAUSE_DEPOSIT)?;
+        require!(vault.kind == VaultKind::Token, KeeprError::SolVault);
+        require!(amount > 0, KeeprError::InvalidAmount);
         require!(!vault.released, KeeprError::AlreadyReleased);
         require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
-
-        // Calculate notification window start time
-        let notification_start = vault
-            .unlock_unix
-            .checked_sub(vault.notification_window_seconds.into())
-            .ok_or(KeeprError::Overflow)?;
-
-        // Check if we're in the notification window
         require!(
-            clock.unix_timestamp >= notification_start,
-            KeeprError::NotInNotificationWindow
+            clock.unix_timestamp < vault.unlock_unix,
+            KeeprError::DepositAfterUnlock
         );
-
-        // Check if we haven't passed the grace period
-        let grace_end = vault
-            .unlock_unix
-            .checked_add(vault.grace_period_seconds.into())
-            .ok_or(KeeprError::Overflow)?;
-
+        // Assets are pushed whole to a single recipient by release
         require!(
-            clock.unix_timestamp < grace_end,
-            KeeprError::AlreadyReleased
+            vault.payout_mode == PayoutMode::Push
+                && vault.backup_beneficiary == Pubkey::default()
+                && !vault.has_release_schedule(),
+            KeeprError::VaultAssetsConflict
         );
 
-        // Reset unlock time using checkin_period (rolling deadline)
-        vault.unlock_unix = clock
-            .unix_timestamp
-            .checked_add(vault.checkin_period_seconds.into())
+        let asset_mint = &ctx.accounts.asset_mint;
+        require_keys_neq!(asset_mint.key(), vault.mint, KeeprError::PrimaryMintAsset);
+        require_custody_safe_mint(asset_mint)?;
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.creator_asset_account.to_account_info(),
+            mint: asset_mint.to_account_info(),
+            to: ctx.accounts.vault_asset_account.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let balance_before = ctx.accounts.vault_asset_account.amount;
+        token_interface::transfer_checked(cpi_ctx, amount, asset_mint.decimals)?;
+
+        // Transfer-fee mints deliver less than was sent; register only what arrived
+        ctx.accounts.vault_asset_account.reload()?;
+        let received = ctx
+            .accounts
+            .vault_asset_account
+            .amount
+            .checked_sub(balance_before)
             .ok_or(KeeprError::Overflow)?;
 
-        // Update last check-in timestamp
-        vault.last_checkin_unix = clock.unix_timestamp;
+        let registry = &mut ctx.accounts.vault_assets;
+        if registry.vault == Pubkey::default() {
+            registry.vault = vault.key();
+            registry.bump = ctx.bumps.vault_assets;
+        }
+        let asset_mint_key = ctx.accounts.asset_mint.key();
+        let total = match registry.assets.iter_mut().find(|asset| asset.mint == asset_mint_key) {
+            Some(asset) => {
+                asset.amount = asset.amount.checked_add(received).ok_or(KeeprError::Overflow)?;
+                asset.amount
+            }
+            None => {
+                require!(registry.assets.len() < MAX_VAULT_ASSETS, KeeprError::TooManyVaultAssets);
+                registry.assets.push(VaultAsset {
+                    mint: asset_mint_key,
+                    amount: received,
+                });
+                received
+            }
+        };
+        vault.asset_count = registry.assets.len() as u8;
 
-        emit!(VaultCheckedIn {
+        emit!(VaultAssetDeposited {
             vault: vault.key(),
-            creator: vault.creator,
-            new_unlock_unix: vault.unlock_unix,
+            mint: asset_mint_key,
+            amount: received,
+            total,
         });
 
         Ok(())
     }
 
-    /// Release funds to beneficiary (time-locked)
-    /// Note: Any signer can call this; PDA signs the transfer via seeds
-    pub fn release(ctx: Context<Release>) -> Result<()> {
+    /// Take part of the vault's USDC back (creator only, before the watchdog period).
+    /// What stays locked must meet `config.min_vault_balance`; use cancel_vault to empty it.
+    pub fn withdraw_usdc(ctx: Context<WithdrawUsdc>, amount: u64) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         let clock = Clock::get()?;
 
-        // Calculate grace period end time
-        let grace_end = vault
-            .unlock_unix
-            .checked_add(vault.grace_period_seconds.into())
-            .ok_or(KeeprError::Overflow)?;
-
-        // Safety checks (optimized order)
+        ctx.accounts.config.require_not_paused(PAUSE_WITHDRAW)?;
+        require!(vault.kind == VaultKind::Token, KeeprError::SolVault);
+        require!(amount > 0, KeeprError::InvalidAmount);
         require!(!vault.released, KeeprError::AlreadyReleased);
-        require!(vault.amount_locked > 0, KeeprError::NothingToRelease);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+
+        // Same rule as CannotCancelDuringWatchdog
+        let notification_start = vault
+            .unlock_unix
+            .checked_sub(vault.notification_window_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
         require!(
-            clock.unix_timestamp >= grace_end,
-            KeeprError::InvalidUnlockTime
+            clock.unix_timestamp < notification_start,
+            KeeprError::CannotWithdrawDuringWatchdog
+        );
+
+        let remaining = vault
+            .amount_locked
+            .checked_sub(amount)
+            .ok_or(KeeprError::InsufficientVaultBalance)?;
+        require!(
+            remaining >= ctx.accounts.config.min_vault_balance,
+            KeeprError::BelowMinimumVaultBalance
         );
 
-        let amount = vault.amount_locked;
         let creator_key = vault.creator;
         let vault_id = vault.vault_id;
-        let vault_bump = vault.bump;
-
-        // Transfer all funds from vault PDA to beneficiary
         let seeds = &[
             b"vault",
             creator_key.as_ref(),
             &vault_id.to_le_bytes(),
-            &[vault_bump],
+            &[vault.bump],
         ];
         let signer = &[&seeds[..]];
 
-        let cpi_accounts = Transfer {
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.vault_token_account.to_account_info(),
-            to: ctx.accounts.beneficiary_usdc_ata.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.creator_usdc_ata.to_account_info(),
             authority: vault.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
 
-        vault.released = true;
-        vault.amount_locked = 0;
+        vault.amount_locked = remaining;
 
-        emit!(VaultReleased {
+        emit!(VaultWithdrawn {
             vault: vault.key(),
             amount,
-            to: vault.beneficiary,
+            remaining,
         });
 
         Ok(())
     }
 
-    /// Cancel vault and return funds to creator (creator only, before release)
-    pub fn cancel_vault(ctx: Context<CancelVault>) -> Result<()> {
+    /// Check-in to reset vault deadline (creator or a check-in delegate, during notification window)
+    pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         let clock = Clock::get()?;
 
         // Safety checks
-        require!(!vault.released, KeeprError::CannotCancelAfterRelease);
+        ctx.accounts.config.require_not_paused(PAUSE_CHECK_IN)?;
+        let signer = ctx.accounts.signer.key();
+        CheckinDelegates::require_authority(ctx.accounts.checkin_delegates.as_deref(), &vault.creator, &signer)?;
+        let early = vault.record_check_in(clock.unix_timestamp)?;
+
+        emit!(VaultCheckedIn {
+            vault: vault.key(),
+            creator: vault.creator,
+            checked_in_by: signer,
+            new_unlock_unix: vault.unlock_unix,
+            relayed: false,
+            early,
+        });
+
+        Ok(())
+    }
+
+    /// Check in with a heartbeat the creator (or a check-in delegate) signed off-chain, so a
+    /// relayer pays the fee (permissionless). The instruction right before this one must be an
+    /// ed25519 precompile verification of `relay_check_in_message(vault, nonce, expiry)`.
+    /// The creator and delegates share one nonce sequence: each heartbeat must use the next
+    /// nonce, so it is accepted once and nobody can skip the sequence ahead. `expiry` may be
+    /// at most `MAX_RELAY_EXPIRY_SECONDS` away, so unsubmitted heartbeats go stale quickly.
+    pub fn relay_check_in(ctx: Context<RelayCheckIn>, nonce: u64, expiry: i64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        ctx.accounts.config.require_not_paused(PAUSE_CHECK_IN)?;
+        require!(clock.unix_timestamp <= expiry, KeeprError::RelayExpired);
+        require!(
+            expiry <= clock.unix_timestamp.saturating_add(MAX_RELAY_EXPIRY_SECONDS),
+            KeeprError::RelayExpiryTooFar
+        );
+        let next_nonce = vault.relay_nonce.checked_add(1).ok_or(KeeprError::Overflow)?;
+        require!(nonce == next_nonce, KeeprError::StaleRelayNonce);
+
+        let message = relay_check_in_message(&vault.key(), nonce, expiry);
+        let signer = verified_ed25519_signer(&ctx.accounts.instructions, &message)?;
+        CheckinDelegates::require_authority(ctx.accounts.checkin_delegates.as_deref(), &vault.creator, &signer)?;
+
+        let early = vault.record_check_in(clock.unix_timestamp)?;
+        vault.relay_nonce = nonce;
+
+        emit!(VaultCheckedIn {
+            vault: vault.key(),
+            creator: vault.creator,
+            checked_in_by: signer,
+            new_unlock_unix: vault.unlock_unix,
+            relayed: true,
+            early,
+        });
+
+        Ok(())
+    }
+
+    /// Allow check-ins before the notification window (creator only), e.g. before going
+    /// off-grid. An early check-in restarts the full period from now; `min_interval_seconds`
+    /// (0 = none) spaces early check-ins out. Check-ins inside the window are unaffected.
+    pub fn set_early_checkin(
+        ctx: Context<SetEarlyCheckin>,
+        enabled: bool,
+        min_interval_seconds: u32,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+
+        require!(!vault.released, KeeprError::AlreadyReleased);
         require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+        require!(
+            min_interval_seconds < vault.checkin_period_seconds,
+            KeeprError::InvalidCheckinInterval
+        );
 
-        // Calculate notification window start time (watchdog period starts here)
-        let notification_start = vault
-            .unlock_unix
-            .checked_sub(vault.notification_window_seconds.into())
-            .ok_or(KeeprError::Overflow)?;
+        vault.early_checkin = enabled;
+        vault.min_checkin_interval_seconds = min_interval_seconds;
 
-        // Cannot cancel during watchdog period (prevents gaming the system)
+        emit!(EarlyCheckinSet {
+            vault: vault.key(),
+            enabled,
+            min_interval_seconds,
+        });
+
+        Ok(())
+    }
+
+    /// Let another key (a phone hot key, a hardware key, a relative) check in for the vault
+    /// (creator only). Delegates can call check_in and nothing else.
+    pub fn add_checkin_delegate(ctx: Context<AddCheckinDelegate>, delegate: Pubkey) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
         require!(
-            clock.unix_timestamp < notification_start,
-            KeeprError::CannotCancelDuringWatchdog
+            delegate != Pubkey::default() && delegate != vault.creator,
+            KeeprError::InvalidCheckinDelegate
         );
 
-        // Calculate closing fee based on tier (NO free grace period)
-        let closing_fee = match vault.tier {
-            VaultTier::Base => 1_000_000,      // $1 USDC (6 decimals)
-            VaultTier::Plus => 5_000_000,      // $5 USDC
-            VaultTier::Premium => 10_000_000,  // $10 USDC
-            VaultTier::Lifetime => 0,          // FREE (white-glove perk)
-        };
+        let list = &mut ctx.accounts.checkin_delegates;
+        if list.vault == Pubkey::default() {
+            list.vault = vault.key();
+            list.bump = ctx.bumps.checkin_delegates;
+        }
+        require!(
+            !list.delegates.contains(&delegate),
+            KeeprError::InvalidCheckinDelegate
+        );
+        require!(
+            list.delegates.len() < MAX_CHECKIN_DELEGATES,
+            KeeprError::TooManyCheckinDelegates
+        );
+        list.delegates.push(delegate);
 
-        let vault_funds = vault.amount_locked;
-        let creator_key = vault.creator;
-        let vault_id = vault.vault_id;
-        let vault_bump = vault.bump;
+        emit!(CheckinDelegateAdded {
+            vault: vault.key(),
+            delegate,
+        });
 
-        let seeds = &[
-            b"vault",
-            creator_key.as_ref(),
-            &vault_id.to_le_bytes(),
-            &[vault_bump],
-        ];
-        let signer = &[&seeds[..]];
+        Ok(())
+    }
 
-        // Return vault funds to creator (if any)
-        if vault_funds > 0 {
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.vault_token_account.to_account_info(),
-                to: ctx.accounts.creator_usdc_ata.to_account_info(),
-                authority: vault.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-            token::transfer(cpi_ctx, vault_funds)?;
+    /// Revoke a check-in delegate (creator only)
+    pub fn remove_checkin_delegate(ctx: Context<RemoveCheckinDelegate>, delegate: Pubkey) -> Result<()> {
+        let list = &mut ctx.accounts.checkin_delegates;
+        let index = list
+            .delegates
+            .iter()
+            .position(|key| *key == delegate)
+            .ok_or(KeeprError::CheckinDelegateNotFound)?;
+        list.delegates.remove(index);
+
+        emit!(CheckinDelegateRemoved {
+            vault: ctx.accounts.vault.key(),
+            delegate,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the (primary) beneficiary after `config.beneficiary_cooldown_seconds` (creator only)
+    /// Blocked during the watchdog period; a newer request replaces a pending one, and
+    /// requesting the current beneficiary withdraws it
+    pub fn update_beneficiary(
+        ctx: Context<UpdateBeneficiary>,
+        new_beneficiary: Pubkey,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+        vault.settle_beneficiary_change(clock.unix_timestamp);
+
+        vault.require_terms_unlocked(clock.unix_timestamp)?;
+
+        // Test vaults were created by SELF_BENEFICIARY wallets
+        if !vault.is_test_vault {
+            require!(
+                new_beneficiary != vault.creator,
+                KeeprError::InvalidBeneficiary
+            );
         }
+        // Can't collapse two split payees into one
+        require!(
+            !vault.beneficiary_shares[1..vault.beneficiary_count.max(1) as usize]
+                .iter()
+                .any(|share| share.beneficiary == new_beneficiary),
+            KeeprError::InvalidBeneficiaryShares
+        );
+        require!(
+            new_beneficiary != vault.backup_beneficiary,
+            KeeprError::InvalidBackupBeneficiary
+        );
+
+        let effective_at = if new_beneficiary == vault.beneficiary {
+            vault.pending_beneficiary = Pubkey::default();
+            vault.beneficiary_effective_at = 0;
+            clock.unix_timestamp
+        } else {
+            let effective_at = clock
+                .unix_timestamp
+                .checked_add(ctx.accounts.config.beneficiary_cooldown_seconds.into())
+                .ok_or(KeeprError::Overflow)?;
+            vault.pending_beneficiary = new_beneficiary;
+            vault.beneficiary_effective_at = effective_at;
+            effective_at
+        };
+
+        emit!(BeneficiaryChanged {
+            vault: vault.key(),
+            old: vault.beneficiary,
+            new: new_beneficiary,
+            effective_at,
+        });
+
+        Ok(())
+    }
+
+    /// Split the release between up to MAX_BENEFICIARIES payees (creator only, before the watchdog period)
+    /// The first share must be the current `vault.beneficiary` and receives any rounding dust
+    pub fn set_beneficiaries(
+        ctx: Context<SetBeneficiaries>,
+        shares: Vec<BeneficiaryShare>,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+        vault.settle_beneficiary_change(clock.unix_timestamp);
 
-        // Collect closing fee (if applicable)
-        if closing_fee > 0 {
-            // Check creator has enough USDC for closing fee
+        vault.require_terms_unlocked(clock.unix_timestamp)?;
+
+        require!(
+            !shares.is_empty() && shares.len() <= MAX_BENEFICIARIES,
+            KeeprError::InvalidBeneficiaryShares
+        );
+        // A backup only stands in for a single payee
+        require!(
+            shares.len() == 1 || vault.backup_beneficiary == Pubkey::default(),
+            KeeprError::InvalidBackupBeneficiary
+        );
+        require!(
+            shares.len() == 1 || vault.payout_mode == PayoutMode::Push,
+            KeeprError::ClaimModeRequiresSinglePayee
+        );
+        let mut total_bps: u64 = 0;
+        for (i, share) in shares.iter().enumerate() {
+            require!(share.bps > 0, KeeprError::InvalidBeneficiaryShares);
             require!(
-                ctx.accounts.creator_usdc_ata.amount >= closing_fee,
-                KeeprError::InsufficientBalanceForClosingFee
+                !shares[..i].iter().any(|other| other.beneficiary == share.beneficiary),
+                KeeprError::InvalidBeneficiaryShares
             );
+            // Test vaults were created by SELF_BENEFICIARY wallets
+            if !vault.is_test_vault {
+                require!(
+                    share.beneficiary != vault.creator,
+                    KeeprError::InvalidBeneficiary
+                );
+            }
+            total_bps += u64::from(share.bps);
+        }
+        require!(
+            total_bps == BPS_DENOMINATOR,
+            KeeprError::InvalidBeneficiaryShares
+        );
 
-            // Transfer closing fee from creator to treasury
-            let cpi_accounts = Transfer {
-                from: ctx.accounts.creator_usdc_ata.to_account_info(),
-                to: ctx.accounts.treasury_usdc_ata.to_account_info(),
-                authority: ctx.accounts.creator.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-            token::transfer(cpi_ctx, closing_fee)?;
+        // The primary only changes through update_beneficiary (and its cooldown)
+        require_keys_eq!(
+            shares[0].beneficiary,
+            vault.beneficiary,
+            KeeprError::PrimaryBeneficiaryChange
+        );
+        if vault.beneficiary_effective_at != 0 {
+            require!(
+                !shares.iter().any(|share| share.beneficiary == vault.pending_beneficiary),
+                KeeprError::InvalidBeneficiaryShares
+            );
         }
 
-        vault.cancelled = true;
-        vault.amount_locked = 0;
+        vault.beneficiary_count = shares.len() as u8;
+        vault.beneficiary_shares = [BeneficiaryShare::default(); MAX_BENEFICIARIES];
+        vault.beneficiary_shares[..shares.len()].copy_from_slice(&shares);
 
-        emit!(VaultCancelled {
+        emit!(BeneficiariesUpdated {
             vault: vault.key(),
             creator: vault.creator,
-            amount_refunded: vault_funds,
-            closing_fee_paid: closing_fee,
-            in_grace_period: false, // No longer used - all cancellations are paid
+            shares,
         });
 
         Ok(())
     }
 
-    /// Fix stuck vault (admin only) - for vaults that are released but have incorrect amount_locked
-    /// This is a recovery function for a bug where released vaults weren't zeroing amount_locked
-    pub fn fix_released_vault(ctx: Context<FixReleasedVault>) -> Result<()> {
+    /// Name (or clear, with `None`) a backup who may claim if the beneficiary hasn't claimed
+    /// within `claim_window_seconds` of release (creator only, before the watchdog period).
+    /// Vaults with a backup are paid through `claim` instead of `release` pushing the funds.
+    pub fn set_backup_beneficiary(
+        ctx: Context<SetBackupBeneficiary>,
+        backup_beneficiary: Option<Pubkey>,
+        claim_window_seconds: u32,
+    ) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
 
-        // Safety checks
-        require!(vault.released, KeeprError::NotReleased);
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+        vault.settle_beneficiary_change(clock.unix_timestamp);
+
+        vault.require_terms_unlocked(clock.unix_timestamp)?;
+
+        match backup_beneficiary {
+            Some(backup) => {
+                require!(!vault.has_release_schedule(), KeeprError::ReleaseScheduleConflict);
+                require!(vault.kind == VaultKind::Token, KeeprError::SolVault);
+                require!(vault.asset_count == 0, KeeprError::VaultAssetsConflict);
+                require!(vault.beneficiary_count <= 1, KeeprError::InvalidBackupBeneficiary);
+                require!(
+                    backup != Pubkey::default()
+                        && backup != vault.beneficiary
+                        && (vault.beneficiary_effective_at == 0 || backup != vault.pending_beneficiary),
+                    KeeprError::InvalidBackupBeneficiary
+                );
+                // Test vaults were created by SELF_BENEFICIARY wallets
+                if !vault.is_test_vault {
+                    require!(backup != vault.creator, KeeprError::InvalidBeneficiary);
+                }
+                require!(
+                    claim_window_seconds > 0 && claim_window_seconds <= MAX_CLAIM_WINDOW_SECONDS,
+                    KeeprError::InvalidClaimWindow
+                );
+                vault.backup_beneficiary = backup;
+                vault.claim_window_seconds = claim_window_seconds;
+            }
+            None => {
+                vault.backup_beneficiary = Pubkey::default();
+                vault.claim_window_seconds = 0;
+            }
+        }
 
-        // Verify token account is actually empty
-        require!(
-            ctx.accounts.vault_token_account.amount == 0,
-            KeeprError::VaultNotEmpty
-        );
+        emit!(BackupBeneficiarySet {
+            vault: vault.key(),
+            backup_beneficiary,
+            claim_window_seconds: vault.claim_window_seconds,
+        });
 
-        // Fix the amount_locked field
-        vault.amount_locked = 0;
+        Ok(())
+    }
+
+    /// Choose whether the payout is pushed by `release` or pulled by the beneficiary with
+    /// `claim` (creator only, before the watchdog period). Claim mode needs a single payee.
+    pub fn set_payout_mode(ctx: Context<SetPayoutMode>, payout_mode: PayoutMode) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+
+        vault.require_terms_unlocked(clock.unix_timestamp)?;
+
+        if payout_mode == PayoutMode::Claim {
+            require!(!vault.has_release_schedule(), KeeprError::ReleaseScheduleConflict);
+            require!(vault.kind == VaultKind::Token, KeeprError::SolVault);
+            require!(vault.asset_count == 0, KeeprError::VaultAssetsConflict);
+            require!(
+                vault.beneficiary_count <= 1,
+                KeeprError::ClaimModeRequiresSinglePayee
+            );
+        }
+        vault.payout_mode = payout_mode;
+
+        emit!(PayoutModeSet {
+            vault: vault.key(),
+            payout_mode,
+        });
 
         Ok(())
     }
 
-    /// Close vault and reclaim rent (creator only, post-release)
-    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+    /// Pay out a claimable vault into any token account the claimant owns.
+    /// Claimable = claim-mode vault past its grace period, or a backup vault whose claim
+    /// window `release` opened. The beneficiary can claim any time; the backup once
+    /// `claim_deadline` has passed.
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+        let claimant = ctx.accounts.claimant.key();
 
-        // Safety checks
-        require!(vault.released, KeeprError::NotReleased);
-        require!(vault.amount_locked == 0, KeeprError::VaultNotEmpty);
+        ctx.accounts.config.require_not_paused(PAUSE_RELEASE)?;
+        require!(vault.kind == VaultKind::Token, KeeprError::SolVault);
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(vault.amount_locked > 0, KeeprError::NothingToRelease);
+        require!(
+            vault.is_claimable(clock.unix_timestamp)?,
+            KeeprError::ClaimNotOpen
+        );
+        vault.settle_beneficiary_change(clock.unix_timestamp);
+
+        let backup_eligible =
+            vault.claim_deadline != 0 && clock.unix_timestamp >= vault.claim_deadline;
+        require!(
+            claimant == vault.beneficiary
+                || (backup_eligible && claimant == vault.backup_beneficiary),
+            KeeprError::NotClaimant
+        );
+
+        // Release fee comes out of the payout
+        let gross_amount = vault.amount_locked;
+        let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+        let fee_schedule = ctx.accounts.config.payout_fees(&vault.mint, supported_mint)?;
+        let release_fee = fee_schedule.release_fee(vault.tier, gross_amount)?;
+        let amount = gross_amount - release_fee;
 
-        // Close token account first (returns rent to creator)
         let creator_key = vault.creator;
         let vault_id = vault.vault_id;
         let vault_bump = vault.bump;
@@ -481,28 +1460,582 @@ pub mod keepr_vault {
         ];
         let signer = &[&seeds[..]];
 
-        let cpi_accounts = anchor_spl::token::CloseAccount {
-            account: ctx.accounts.vault_token_account.to_account_info(),
-            destination: ctx.accounts.creator.to_account_info(),
-            authority: vault.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        anchor_spl::token::close_account(cpi_ctx)?;
+        if amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: vault.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        }
+
+        // Collect release fee (if applicable)
+        if release_fee > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.treasury_usdc_ata.to_account_info(),
+                authority: vault.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token_interface::transfer_checked(cpi_ctx, release_fee, ctx.accounts.mint.decimals)?;
+        }
+
+        vault.released = true;
+        vault.amount_locked = 0;
+
+        emit!(BeneficiaryPaid {
+            vault: vault.key(),
+            beneficiary: claimant,
+            bps: BPS_DENOMINATOR as u16,
+            amount,
+        });
+
+        emit!(VaultReleased {
+            vault: vault.key(),
+            gross_amount,
+            fee: release_fee,
+            net_amount: amount,
+            to: claimant,
+        });
 
-        // Vault account will be closed automatically via close constraint
         Ok(())
     }
-}
 
-// ============================================================================
-// Accounts
-// ============================================================================
+    /// Release funds to beneficiary (time-locked)
+    /// Note: Any signer can call this; PDA signs the transfer via seeds
+    /// Split vaults pass the token accounts of shares 2..n, in order, as remaining accounts
+    /// (their wallets for SOL vaults, which leave out the token accounts and pass `treasury`)
+    /// Vaults with a backup beneficiary only open the claim window here (see `claim`);
+    /// claim-mode vaults without one are rejected
+    pub fn release<'info>(ctx: Context<'_, '_, 'info, 'info, Release<'info>>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
 
-#[derive(Accounts)]
-pub struct InitConfig<'info> {
-    #[account(
-        init,
+        // A matured update_beneficiary applies now (the Release constraint already expects it)
+        vault.settle_beneficiary_change(clock.unix_timestamp);
+
+        // Calculate grace period end time
+        let grace_end = vault
+            .unlock_unix
+            .checked_add(vault.grace_period_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+
+        // Safety checks (optimized order)
+        ctx.accounts.config.require_not_paused(PAUSE_RELEASE)?;
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(vault.claim_deadline == 0, KeeprError::ClaimPending);
+        require!(
+            vault.amount_locked > 0 || vault.asset_count > 0,
+            KeeprError::NothingToRelease
+        );
+        require!(
+            clock.unix_timestamp >= grace_end,
+            KeeprError::InvalidUnlockTime
+        );
+
+        // With a backup, nothing is pushed: the beneficiary has to prove they hold their keys
+        if vault.backup_beneficiary != Pubkey::default() {
+            vault.claim_deadline = clock
+                .unix_timestamp
+                .checked_add(vault.claim_window_seconds.into())
+                .ok_or(KeeprError::Overflow)?;
+
+            emit!(ClaimWindowOpened {
+                vault: vault.key(),
+                beneficiary: vault.beneficiary,
+                backup_beneficiary: vault.backup_beneficiary,
+                claim_deadline: vault.claim_deadline,
+            });
+
+            return Ok(());
+        }
+
+        // Claim-mode vaults are already claimable; there is nothing to push
+        require!(
+            vault.payout_mode == PayoutMode::Push,
+            KeeprError::ClaimModeVault
+        );
+        require!(!vault.has_release_schedule(), KeeprError::ReleaseScheduled);
+
+        // Release fee comes out of the payout
+        let gross_amount = vault.amount_locked;
+        let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+        let fee_schedule = ctx.accounts.config.payout_fees(&vault.mint, supported_mint)?;
+        let release_fee = fee_schedule.release_fee(vault.tier, gross_amount)?;
+        let amount = gross_amount - release_fee;
+
+        // Payee token accounts first, then the registered assets' accounts
+        let payee_accounts = vault.payee_count() - 1;
+        require!(
+            ctx.remaining_accounts.len() >= payee_accounts,
+            KeeprError::BeneficiaryAccountMismatch
+        );
+        let (payee_accounts, asset_accounts) = ctx.remaining_accounts.split_at(payee_accounts);
+
+        ctx.accounts.pay_out(payee_accounts, amount, release_fee)?;
+
+        // Registered assets go whole to the primary beneficiary
+        let accounts = &mut *ctx.accounts;
+        transfer_vault_assets(
+            &accounts.vault,
+            accounts.vault_assets.as_deref_mut(),
+            asset_accounts,
+            &accounts.beneficiary,
+            accounts.creator.as_deref(),
+            &accounts.payer,
+            &accounts.associated_token_program,
+            &accounts.system_program,
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.released = true;
+        vault.amount_locked = 0;
+        vault.asset_count = 0;
+
+        emit!(VaultReleased {
+            vault: vault.key(),
+            gross_amount,
+            fee: release_fee,
+            net_amount: amount,
+            to: vault.beneficiary,
+        });
+
+        Ok(())
+    }
+
+    /// Pay whatever a vesting vault's release schedule has vested since the last tranche
+    /// (permissionless, same accounts as `release`). The release fee is charged pro rata,
+    /// so the tranches add up to what a single release would have paid.
+    pub fn release_tranche<'info>(ctx: Context<'_, '_, 'info, 'info, Release<'info>>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        vault.settle_beneficiary_change(clock.unix_timestamp);
+
+        let grace_end = vault
+            .unlock_unix
+            .checked_add(vault.grace_period_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+
+        ctx.accounts.config.require_not_paused(PAUSE_RELEASE)?;
+        require!(vault.kind == VaultKind::Token, KeeprError::SolVault);
+        require!(vault.has_release_schedule(), KeeprError::NoReleaseSchedule);
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(vault.amount_locked > 0, KeeprError::NothingToRelease);
+        require!(
+            clock.unix_timestamp >= grace_end,
+            KeeprError::InvalidUnlockTime
+        );
+
+        // Nothing can be deposited or withdrawn after the trigger, so this is the funded total
+        let total = vault
+            .amount_locked
+            .checked_add(vault.amount_released)
+            .ok_or(KeeprError::Overflow)?;
+        let vested = vault.vested_amount(clock.unix_timestamp)?;
+        let gross_amount = vested.saturating_sub(vault.amount_released);
+        require!(gross_amount > 0, KeeprError::NothingVested);
+
+        let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+        let fee_schedule = ctx.accounts.config.payout_fees(&vault.mint, supported_mint)?;
+        let total_fee = fee_schedule.release_fee(vault.tier, total)?;
+        let fee = pro_rata(total_fee, vested, total)? - pro_rata(total_fee, vault.amount_released, total)?;
+        let amount = gross_amount - fee;
+
+        ctx.accounts.pay_out(ctx.remaining_accounts, amount, fee)?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.amount_locked -= gross_amount;
+        vault.amount_released += gross_amount;
+        if vault.amount_locked == 0 {
+            vault.released = true;
+        }
+
+        emit!(TrancheReleased {
+            vault: vault.key(),
+            gross_amount,
+            fee,
+            net_amount: amount,
+            amount_released: vault.amount_released,
+            remaining: vault.amount_locked,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel vault and return funds to creator (creator only, before release)
+    /// Registered assets (deposit_token) come back too: pass their accounts as remaining
+    /// accounts, four per asset in registry order (see `transfer_vault_assets`).
+    /// SOL vaults refund and charge the closing fee in lamports: they leave out the token
+    /// accounts and pass `treasury`.
+    pub fn cancel_vault<'info>(ctx: Context<'_, '_, 'info, 'info, CancelVault<'info>>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        // Safety checks
+        ctx.accounts.config.require_not_paused(PAUSE_CANCEL)?;
+        require!(!vault.released, KeeprError::CannotCancelAfterRelease);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+
+        // Calculate notification window start time (watchdog period starts here)
+        let notification_start = vault
+            .unlock_unix
+            .checked_sub(vault.notification_window_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+
+        // Cannot cancel during watchdog period (prevents gaming the system)
+        require!(
+            clock.unix_timestamp < notification_start,
+            KeeprError::CannotCancelDuringWatchdog
+        );
+
+        // Closing fee from the on-chain fee schedule (NO free grace period)
+        let closing_fee = if WalletFlags::of(ctx.accounts.wallet_flags.as_deref()) & WALLET_FLAG_FEE_EXEMPT != 0 {
+            0
+        } else {
+            let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+            let fee_schedule = ctx.accounts.config.payout_fees(&vault.mint, supported_mint)?;
+            fee_schedule.for_tier(vault.tier).closing_fee
+        };
+
+        let vault_funds = vault.amount_locked;
+
+        {
+            let accounts = &*ctx.accounts;
+            let custody = Custody::of(
+                &accounts.vault,
+                accounts.vault_token_account.as_deref(),
+                accounts.mint.as_deref(),
+                accounts.token_program.as_ref(),
+            )?;
+
+            // Token vaults refund into the creator's ATA and take the fee from it
+            let (refund_to, fee_to) = match accounts.vault.kind {
+                VaultKind::Token => (
+                    accounts.creator_usdc_ata.as_ref().ok_or(KeeprError::VaultTokenAccountRequired)?.to_account_info(),
+                    accounts.treasury_usdc_ata.as_ref().ok_or(KeeprError::VaultTokenAccountRequired)?.to_account_info(),
+                ),
+                VaultKind::Sol => (
+                    accounts.creator.to_account_info(),
+                    accounts.treasury.as_ref().ok_or(KeeprError::TreasuryWalletRequired)?.to_account_info(),
+                ),
+            };
+
+            // Return vault funds to creator (if any), then collect the closing fee from them
+            custody.pay(&accounts.vault, &refund_to, vault_funds)?;
+            custody.collect_fee(&accounts.creator, &refund_to, &fee_to, &accounts.system_program, closing_fee)?;
+        }
+
+        let accounts = &mut *ctx.accounts;
+        transfer_vault_assets(
+            &accounts.vault,
+            accounts.vault_assets.as_deref_mut(),
+            ctx.remaining_accounts,
+            &accounts.creator,
+            Some(&accounts.creator),
+            &accounts.creator,
+            &accounts.associated_token_program,
+            &accounts.system_program,
+        )?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.cancelled = true;
+        vault.amount_locked = 0;
+        vault.asset_count = 0;
+
+        emit!(VaultCancelled {
+            vault: vault.key(),
+            creator: vault.creator,
+            amount_refunded: vault_funds,
+            closing_fee_paid: closing_fee,
+            in_grace_period: false, // No longer used - all cancellations are paid
+        });
+
+        Ok(())
+    }
+
+    /// Create a vault holding native SOL as lamports in the vault PDA itself.
+    /// Cap and fees (in lamports) come from the native mint's SupportedMint listing;
+    /// check-in, notification window and grace period work as for token vaults.
+    /// SOL vaults pay out with `release` and refund with `cancel_vault` (single release,
+    /// no backup or claim mode).
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_sol_vault(
+        ctx: Context<CreateSolVault>,
+        beneficiary: Pubkey,
+        checkin_period_seconds: u32,
+        name_hash: [u8; 32],
+        notification_window_seconds: u32,
+        grace_period_seconds: u32,
+        tier: VaultTier,
+        creation_fee_paid: u64,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let vault = &mut ctx.accounts.vault;
+        let counter = &mut ctx.accounts.counter;
+        let clock = Clock::get()?;
+
+        config.require_not_paused(PAUSE_CREATE)?;
+        let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+        config.require_mint_open(&native_mint::ID, supported_mint)?;
+        let (_, fee_schedule) = config.mint_terms(&native_mint::ID, supported_mint)?;
+
+        let creator_key = ctx.accounts.creator.key();
+        let wallet_flags = WalletFlags::of(ctx.accounts.wallet_flags.as_deref());
+        let is_admin_tester = wallet_flags & WALLET_FLAG_SELF_BENEFICIARY != 0;
+        if !is_admin_tester {
+            require!(
+                beneficiary != creator_key,
+                KeeprError::InvalidBeneficiary
+            );
+        }
+
+        let vault_id = counter.last_id.checked_add(1).ok_or(KeeprError::Overflow)?;
+        if counter.last_id == 0 {
+            require!(vault_id == 1, KeeprError::CounterNotInitialized);
+        }
+        counter.last_id = vault_id;
+
+        validate_vault_timers(checkin_period_seconds, notification_window_seconds, grace_period_seconds)?;
+
+        let creation_fee = if wallet_flags & WALLET_FLAG_FEE_EXEMPT != 0 {
+            0
+        } else {
+            fee_schedule.for_tier(tier).creation_fee
+        };
+        require!(
+            creation_fee_paid == creation_fee,
+            KeeprError::CreationFeeMismatch
+        );
+        if creation_fee > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                creation_fee,
+            )?;
+        }
+
+        let unlock_unix = clock
+            .unix_timestamp
+            .checked_add(checkin_period_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+
+        vault.creator = creator_key;
+        vault.beneficiary = beneficiary;
+        vault.mint = native_mint::ID;
+        vault.vault_token_account = Pubkey::default();
+        vault.amount_locked = 0;
+        vault.unlock_unix = unlock_unix;
+        vault.is_test_vault = is_admin_tester;
+        vault.bump = ctx.bumps.vault;
+        vault.name_hash = name_hash;
+        vault.vault_id = vault_id;
+        vault.vault_period_seconds = checkin_period_seconds;
+        vault.notification_window_seconds = notification_window_seconds;
+        vault.grace_period_seconds = grace_period_seconds;
+        vault.tier = tier;
+        vault.created_at = clock.unix_timestamp;
+        vault.creation_fee_paid = creation_fee;
+        vault.checkin_period_seconds = checkin_period_seconds;
+        vault.schema_version = VAULT_SCHEMA_VERSION;
+        vault.kind = VaultKind::Sol;
+
+        emit!(VaultCreated {
+            creator: vault.creator,
+            vault: vault.key(),
+            beneficiary: vault.beneficiary,
+            unlock_unix: vault.unlock_unix,
+        });
+
+        Ok(())
+    }
+
+    /// Deposit lamports into a SOL vault (creator only, before unlock)
+    pub fn deposit_sol(ctx: Context<DepositSol>, amount: u64) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        ctx.accounts.config.require_not_paused(PAUSE_DEPOSIT)?;
+        require!(vault.kind == VaultKind::Sol, KeeprError::NotSolVault);
+        require!(amount > 0, KeeprError::InvalidAmount);
+        require!(!vault.released, KeeprError::AlreadyReleased);
+        require!(!vault.cancelled, KeeprError::VaultAlreadyCancelled);
+        require!(
+            clock.unix_timestamp < vault.unlock_unix,
+            KeeprError::DepositAfterUnlock
+        );
+
+        let supported_mint = ctx.accounts.supported_mint.as_deref().map(|entry| &**entry);
+        ctx.accounts.config.require_mint_open(&vault.mint, supported_mint)?;
+        let (max_lock_per_vault, _) = ctx.accounts.config.mint_terms(&vault.mint, supported_mint)?;
+
+        let new_total = vault
+            .amount_locked
+            .checked_add(amount)
+            .ok_or(KeeprError::InvalidAmount)?;
+        if WalletFlags::of(ctx.accounts.wallet_flags.as_deref()) & WALLET_FLAG_CAP_EXEMPT == 0 {
+            require!(
+                new_total <= max_lock_per_vault,
+                KeeprError::AboveVaultCap
+            );
+        }
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        vault.amount_locked = new_total;
+
+        emit!(VaultFunded {
+            vault: vault.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Fix stuck vault (admin only) - for vaults that are released but have incorrect amount_locked
+    /// This is a recovery function for a bug where released vaults weren't zeroing amount_locked
+    pub fn fix_released_vault(ctx: Context<FixReleasedVault>) -> Result<()> {
+        require_role(
+            &ctx.accounts.config,
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.role_assignment,
+            Role::RecoveryOperator,
+        )?;
+        let vault = &mut ctx.accounts.vault;
+
+        // Safety checks
+        require!(vault.released, KeeprError::NotReleased);
+
+        // Verify token account is actually empty
+        require!(
+            ctx.accounts.vault_token_account.amount == 0,
+            KeeprError::VaultNotEmpty
+        );
+
+        // Fix the amount_locked field
+        vault.amount_locked = 0;
+
+        Ok(())
+    }
+
+    /// Upgrade a vault to the current schema in place (permissionless).
+    /// New fields are appended to the layout, so the account is grown,
+    /// zero-filled and then given per-version defaults.
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        let vault_info = ctx.accounts.vault.to_account_info();
+
+        {
+            let data = vault_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + VAULT_V1_SPACE && data[..8] == *Vault::DISCRIMINATOR,
+                KeeprError::UnsupportedVaultVersion
+            );
+            // v1 (no schema_version byte) is the only older layout ever deployed
+            if data.len() != 8 + VAULT_V1_SPACE {
+                return match data[8 + VAULT_V1_SPACE] {
+                    VAULT_SCHEMA_VERSION => err!(KeeprError::VaultUpToDate),
+                    _ => err!(KeeprError::UnsupportedVaultVersion),
+                };
+            }
+        }
+
+        resize_account(
+            &vault_info,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + Vault::INIT_SPACE,
+        )?;
+
+        let mut data = vault_info.try_borrow_mut_data()?;
+        let mut vault = Vault::try_deserialize(&mut &data[..])?;
+        // v2: every zero-filled field keeps the v1 behaviour, so nothing needs backfilling:
+        // a single payee (`beneficiary_count` 0), no pending beneficiary change, no backup,
+        // push payout, no release schedule, a token vault, no registered assets, no relayed
+        // check-in yet and check-ins only inside the notification window
+        vault.schema_version = VAULT_SCHEMA_VERSION;
+        vault.try_serialize(&mut &mut data[..])?;
+
+        emit!(VaultMigrated {
+            vault: vault_info.key(),
+            from_version: 1,
+            to_version: VAULT_SCHEMA_VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Close vault and reclaim rent (creator only, post-release)
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+
+        // Safety checks
+        require!(vault.released, KeeprError::NotReleased);
+        require!(vault.amount_locked == 0, KeeprError::VaultNotEmpty);
+
+        // SOL vaults have no token account; the close constraint returns their rent
+        if vault.kind == VaultKind::Sol {
+            return Ok(());
+        }
+        let (Some(vault_token_account), Some(token_program)) =
+            (&ctx.accounts.vault_token_account, &ctx.accounts.token_program)
+        else {
+            return err!(KeeprError::VaultTokenAccountRequired);
+        };
+
+        // Close token account first (returns rent to creator)
+        let creator_key = vault.creator;
+        let vault_id = vault.vault_id;
+        let vault_bump = vault.bump;
+
+        let seeds = &[
+            b"vault",
+            creator_key.as_ref(),
+            &vault_id.to_le_bytes(),
+            &[vault_bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let cpi_accounts = token_interface::CloseAccount {
+            account: vault_token_account.to_account_info(),
+            destination: ctx.accounts.creator.to_account_info(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_program = token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token_interface::close_account(cpi_ctx)?;
+
+        // Vault account will be closed automatically via close constraint
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(
+        init,
         payer = admin,
         space = 8 + Config::INIT_SPACE,
         seeds = [b"config"],
@@ -510,68 +2043,881 @@ pub struct InitConfig<'info> {
     )]
     pub config: Account<'info, Config>,
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+/// Config update callable by the admin or a holder of the instruction's role
+#[derive(Accounts)]
+pub struct RoleGatedConfig<'info> {
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    pub authority: Signer<'info>,
+
+    /// Required unless `authority` is the admin
+    pub role_assignment: Option<Account<'info, RoleAssignment>>,
+}
+
+impl RoleGatedConfig<'_> {
+    /// Returns true if the authority is the admin (rather than a role holder)
+    fn require_role(&self, role: Role) -> Result<bool> {
+        require_role(&self.config, &self.authority.key(), &self.role_assignment, role)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(role: Role, holder: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(seeds = [b"config"], bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RoleAssignment::INIT_SPACE,
+        seeds = [b"role".as_ref(), &[role as u8], holder.as_ref()],
+        bump
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+
+    /// Separate from admin so a council PDA (which can't pay rent) can grant roles
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSupportedMint<'info> {
+    #[account(seeds = [b"config"], bump, has_one = admin)]
+    pub config: Box<Account<'info, Config>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SupportedMint::INIT_SPACE,
+        seeds = [b"supported_mint", mint.key().as_ref()],
+        bump
+    )]
+    pub supported_mint: Box<Account<'info, SupportedMint>>,
+
+    /// CHECK: Only used as the authority of the treasury token account
+    #[account(address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// Receives this mint's fees
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub admin: Signer<'info>,
+
+    /// Separate from admin so a council PDA (which can't pay rent) can list mints
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct SetWalletFlags<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + WalletFlags::INIT_SPACE,
+        seeds = [b"flags", wallet.as_ref()],
+        bump
+    )]
+    pub wallet_flags: Account<'info, WalletFlags>,
+
+    pub authority: Signer<'info>,
+
+    /// Required unless `authority` is the admin
+    pub role_assignment: Option<Account<'info, RoleAssignment>>,
+
+    /// Separate from authority so a council PDA (which can't pay rent) can set flags
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveWalletFlags<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"flags", wallet_flags.wallet.as_ref()],
+        bump = wallet_flags.bump,
+        has_one = payer
+    )]
+    pub wallet_flags: Account<'info, WalletFlags>,
+
+    pub authority: Signer<'info>,
+
+    /// Required unless `authority` is the admin
+    pub role_assignment: Option<Account<'info, RoleAssignment>>,
+
+    /// CHECK: Original payer receiving the rent refund, validated via has_one
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(seeds = [b"config"], bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"role".as_ref(), &[role_assignment.role as u8], role_assignment.holder.as_ref()],
+        bump = role_assignment.bump,
+        has_one = payer
+    )]
+    pub role_assignment: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+
+    /// CHECK: Original payer receiving the rent refund, validated via has_one
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(seeds = [b"config"], bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PendingConfigChange::INIT_SPACE,
+        seeds = [b"config_change"],
+        bump
+    )]
+    pub config_change: Account<'info, PendingConfigChange>,
+
+    pub admin: Signer<'info>,
+
+    /// Separate from admin so a council PDA (which can't pay rent) can queue changes
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"config_change"],
+        bump = config_change.bump,
+        has_one = payer
+    )]
+    pub config_change: Account<'info, PendingConfigChange>,
+
+    /// Required when the change rotates the treasury (must be its USDC ATA)
+    pub new_treasury_usdc_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Original payer receiving the rent refund, validated via has_one
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(seeds = [b"config"], bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"config_change"],
+        bump = config_change.bump,
+        has_one = payer
+    )]
+    pub config_change: Account<'info, PendingConfigChange>,
+
+    pub admin: Signer<'info>,
+
+    /// CHECK: Original payer receiving the rent refund, validated via has_one
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.pending_admin == Some(new_admin.key()) @ KeeprError::NotPendingAdmin
+    )]
+    pub config: Account<'info, Config>,
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitCouncil<'info> {
+    #[account(seeds = [b"config"], bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AdminCouncil::INIT_SPACE,
+        seeds = [b"council"],
+        bump
+    )]
+    pub council: Account<'info, AdminCouncil>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCouncil<'info> {
+    /// Council PDA must sign (only possible through execute_proposal)
+    #[account(mut, signer, seeds = [b"council"], bump = council.bump)]
+    pub council: Account<'info, AdminCouncil>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut, seeds = [b"council"], bump = council.bump)]
+    pub council: Account<'info, AdminCouncil>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + CouncilProposal::INIT_SPACE,
+        seeds = [b"proposal", &council.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account(seeds = [b"council"], bump = council.bump)]
+    pub council: Account<'info, AdminCouncil>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+
+    pub member: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    /// Not `mut`: the executed instruction may update the council itself
+    #[account(seeds = [b"council"], bump = council.bump)]
+    pub council: Account<'info, AdminCouncil>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+
+    pub executor: Signer<'info>,
+
+    pub keepr_program: Program<'info, crate::program::KeeprVault>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVault<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + VaultCounter::INIT_SPACE,
+        seeds = [b"vault_counter", creator.key().as_ref()],
+        bump
+    )]
+    pub counter: Box<Account<'info, VaultCounter>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", creator.key().as_ref(), &(counter.last_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The config mint, or one listed with set_supported_mint
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program
+    )]
+    pub creator_usdc_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config.treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_usdc_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    /// Creator's capability flags, if any
+    #[account(seeds = [b"flags", creator.key().as_ref()], bump = wallet_flags.bump)]
+    pub wallet_flags: Option<Account<'info, WalletFlags>>,
+
+    /// Cap and fees for `mint` when it isn't the config mint
+    #[account(seeds = [b"supported_mint", mint.key().as_ref()], bump = supported_mint.bump)]
+    pub supported_mint: Option<Box<Account<'info, SupportedMint>>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositUsdc<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(seeds = [b"vault_counter", creator.key().as_ref()], bump)]
+    pub counter: Account<'info, VaultCounter>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program
+    )]
+    pub creator_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Creator's capability flags, if any
+    #[account(seeds = [b"flags", creator.key().as_ref()], bump = wallet_flags.bump)]
+    pub wallet_flags: Option<Account<'info, WalletFlags>>,
+
+    /// Cap and fees for `mint` when it isn't the config mint
+    #[account(seeds = [b"supported_mint", mint.key().as_ref()], bump = supported_mint.bump)]
+    pub supported_mint: Option<Box<Account<'info, SupportedMint>>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToken<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + VaultAssets::INIT_SPACE,
+        seeds = [b"vault_assets", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_assets: Box<Account<'info, VaultAssets>>,
+
+    #[account(mint::token_program = token_program)]
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = asset_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_asset_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = asset_mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program
+    )]
+    pub creator_asset_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUsdc<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = vault.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program
+    )]
+    pub creator_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CheckIn<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(seeds = [b"vault_counter", vault.creator.as_ref()], bump)]
+    pub counter: Account<'info, VaultCounter>,
+
+    /// The creator, or a key on the vault's check-in delegate list
+    pub signer: Signer<'info>,
+
+    /// Required when `signer` isn't the creator
+    #[account(seeds = [b"checkin_delegates", vault.key().as_ref()], bump = checkin_delegates.bump)]
+    pub checkin_delegates: Option<Account<'info, CheckinDelegates>>,
+}
+
+#[derive(Accounts)]
+pub struct SetEarlyCheckin<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RelayCheckIn<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Required when a check-in delegate signed the heartbeat
+    #[account(seeds = [b"checkin_delegates", vault.key().as_ref()], bump = checkin_delegates.bump)]
+    pub checkin_delegates: Option<Account<'info, CheckinDelegates>>,
+
+    /// CHECK: Instructions sysvar, read to find the ed25519 verification
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddCheckinDelegate<'info> {
+    #[account(
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + CheckinDelegates::INIT_SPACE,
+        seeds = [b"checkin_delegates", vault.key().as_ref()],
+        bump
+    )]
+    pub checkin_delegates: Account<'info, CheckinDelegates>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateConfig<'info> {
+pub struct RemoveCheckinDelegate<'info> {
+    #[account(
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
     #[account(
         mut,
-        seeds = [b"config"],
-        bump,
-        has_one = admin
+        seeds = [b"checkin_delegates", vault.key().as_ref()],
+        bump = checkin_delegates.bump
     )]
+    pub checkin_delegates: Account<'info, CheckinDelegates>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBeneficiary<'info> {
+    #[account(seeds = [b"config"], bump)]
     pub config: Account<'info, Config>,
-    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub creator: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct CreateVault<'info> {
+pub struct SetBackupBeneficiary<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayoutMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
     #[account(seeds = [b"config"], bump)]
     pub config: Box<Account<'info, Config>>,
 
     #[account(
-        init_if_needed,
-        payer = creator,
-        space = 8 + VaultCounter::INIT_SPACE,
-        seeds = [b"vault_counter", creator.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump
     )]
-    pub counter: Box<Account<'info, VaultCounter>>,
+    pub vault: Box<Account<'info, Vault>>,
 
     #[account(
-        init,
-        payer = creator,
-        space = 8 + Vault::INIT_SPACE,
-        seeds = [b"vault", creator.key().as_ref(), &(counter.last_id + 1).to_le_bytes()],
-        bump
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = vault.mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Beneficiary, or the backup once the claim deadline has passed
+    pub claimant: Signer<'info>,
+
+    /// Any token account the claimant owns (not necessarily their ATA)
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = destination.owner == claimant.key() @ KeeprError::InvalidClaimDestination
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config.treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_usdc_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Fees for `mint` when it isn't the config mint (see `Config::payout_fees`)
+    #[account(seeds = [b"supported_mint", mint.key().as_ref()], bump = supported_mint.bump)]
+    pub supported_mint: Option<Box<Account<'info, SupportedMint>>>,
+}
+
+#[derive(Accounts)]
+pub struct SetBeneficiaries<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Release<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        constraint = vault.beneficiary_at(Clock::get()?.unix_timestamp) == beneficiary.key()
+            @ KeeprError::BeneficiaryMismatch
     )]
     pub vault: Box<Account<'info, Vault>>,
 
+    #[account(seeds = [b"vault_counter", vault.creator.as_ref()], bump)]
+    pub counter: Account<'info, VaultCounter>,
+
+    /// Token vaults only
     #[account(
-        init,
-        payer = creator,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = vault
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token vaults only
+    #[account(address = vault.mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Token vaults only
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program
     )]
-    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+    pub beneficiary_usdc_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Validated against the vault's current beneficiary (including a matured change);
+    /// SOL vaults pay this wallet directly
+    #[account(mut)]
+    pub beneficiary: AccountInfo<'info>,
 
-    #[account(address = config.usdc_mint)]
-    pub usdc_mint: Box<Account<'info, Mint>>,
+    /// Token vaults only
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = config.treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_usdc_ata: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub payer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token vaults only
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+
+    /// Fees for the vault mint when it isn't the config mint (see `Config::payout_fees`)
+    #[account(seeds = [b"supported_mint", vault.mint.as_ref()], bump = supported_mint.bump)]
+    pub supported_mint: Option<Box<Account<'info, SupportedMint>>>,
+
+    /// Registered assets, required once deposit_token has been used
+    #[account(mut, seeds = [b"vault_assets", vault.key().as_ref()], bump = vault_assets.bump)]
+    pub vault_assets: Option<Box<Account<'info, VaultAssets>>>,
+
+    /// CHECK: Vault creator, required with `vault_assets`; gets back the registry's and asset ATAs' rent
+    #[account(mut, address = vault.creator)]
+    pub creator: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: SOL vaults only: the treasury wallet, which receives the release fee in lamports
+    #[account(mut, address = config.treasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> Release<'info> {
+    /// Split `net_amount` between the vault's payees and send `fee` to the treasury.
+    /// Payees after the first are paid into `remaining_accounts`, in share order.
+    fn pay_out(
+        &self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        net_amount: u64,
+        fee: u64,
+    ) -> Result<()> {
+        let vault = &self.vault;
+        let custody = Custody::of(
+            vault,
+            self.vault_token_account.as_ref(),
+            self.mint.as_ref(),
+            self.token_program.as_ref(),
+        )?;
+        let payouts = vault.payouts(net_amount);
+
+        // One account per additional payee: a token account it owns, or its wallet for SOL vaults
+        require!(
+            remaining_accounts.len() == payouts.len() - 1,
+            KeeprError::BeneficiaryAccountMismatch
+        );
+
+        // The primary beneficiary is paid into the ATA created by the instruction
+        let (beneficiary, treasury) = match vault.kind {
+            VaultKind::Token => (
+                self.beneficiary_usdc_ata.as_ref().ok_or(KeeprError::VaultTokenAccountRequired)?.to_account_info(),
+                self.treasury_usdc_ata.as_ref().ok_or(KeeprError::VaultTokenAccountRequired)?.to_account_info(),
+            ),
+            VaultKind::Sol => (
+                self.beneficiary.to_account_info(),
+                self.treasury.as_ref().ok_or(KeeprError::TreasuryWalletRequired)?.to_account_info(),
+            ),
+        };
+
+        for (i, &(payee, bps, payout)) in payouts.iter().enumerate() {
+            let to = if i == 0 {
+                beneficiary.clone()
+            } else {
+                let info = &remaining_accounts[i - 1];
+                custody.require_payee_account(info, payee)?;
+                info.clone()
+            };
+            custody.pay(vault, &to, payout)?;
+
+            emit!(BeneficiaryPaid {
+                vault: vault.key(),
+                beneficiary: payee,
+                bps,
+                amount: payout,
+            });
+        }
+
+        // Collect release fee (if applicable)
+        custody.pay(vault, &treasury, fee)
+    }
 }
 
 #[derive(Accounts)]
-pub struct DepositUsdc<'info> {
+pub struct CancelVault<'info> {
     #[account(seeds = [b"config"], bump)]
-    pub config: Account<'info, Config>,
+    pub config: Box<Account<'info, Config>>,
 
     #[account(
         mut,
@@ -579,226 +2925,987 @@ pub struct DepositUsdc<'info> {
         bump = vault.bump,
         has_one = creator
     )]
-    pub vault: Account<'info, Vault>,
+    pub vault: Box<Account<'info, Vault>>,
 
     #[account(seeds = [b"vault_counter", creator.key().as_ref()], bump)]
-    pub counter: Account<'info, VaultCounter>,
+    pub counter: Box<Account<'info, VaultCounter>>,
+
+    /// Token vaults only
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Token vaults only
+    #[account(address = vault.mint)]
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 
+    /// Token vaults only
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program
+    )]
+    pub creator_usdc_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Token vaults only
     #[account(
         mut,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = vault
+        associated_token::mint = mint,
+        associated_token::authority = config.treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_usdc_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// Token vaults only
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+
+    /// Creator's capability flags, if any
+    #[account(seeds = [b"flags", creator.key().as_ref()], bump = wallet_flags.bump)]
+    pub wallet_flags: Option<Account<'info, WalletFlags>>,
+
+    /// Fees for the vault mint when it isn't the config mint (see `Config::payout_fees`)
+    #[account(seeds = [b"supported_mint", vault.mint.as_ref()], bump = supported_mint.bump)]
+    pub supported_mint: Option<Box<Account<'info, SupportedMint>>>,
+
+    /// Registered assets, required once deposit_token has been used
+    #[account(mut, seeds = [b"vault_assets", vault.key().as_ref()], bump = vault_assets.bump)]
+    pub vault_assets: Option<Box<Account<'info, VaultAssets>>>,
+
+    /// CHECK: SOL vaults only: the treasury wallet, which receives the closing fee in lamports
+    #[account(mut, address = config.treasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSolVault<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + VaultCounter::INIT_SPACE,
+        seeds = [b"vault_counter", creator.key().as_ref()],
+        bump
+    )]
+    pub counter: Box<Account<'info, VaultCounter>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", creator.key().as_ref(), &(counter.last_id + 1).to_le_bytes()],
+        bump
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Receives the creation fee in lamports
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 
-    #[account(address = config.usdc_mint)]
-    pub usdc_mint: Account<'info, Mint>,
+    /// Creator's capability flags, if any
+    #[account(seeds = [b"flags", creator.key().as_ref()], bump = wallet_flags.bump)]
+    pub wallet_flags: Option<Account<'info, WalletFlags>>,
+
+    /// The native mint's listing: cap and fees in lamports
+    #[account(seeds = [b"supported_mint", native_mint::ID.as_ref()], bump = supported_mint.bump)]
+    pub supported_mint: Option<Box<Account<'info, SupportedMint>>>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSol<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
 
     #[account(
         mut,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = creator
+        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump,
+        has_one = creator
     )]
-    pub creator_usdc_ata: Account<'info, TokenAccount>,
+    pub vault: Account<'info, Vault>,
 
     #[account(mut)]
     pub creator: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Creator's capability flags, if any
+    #[account(seeds = [b"flags", creator.key().as_ref()], bump = wallet_flags.bump)]
+    pub wallet_flags: Option<Account<'info, WalletFlags>>,
+
+    /// The native mint's listing: cap in lamports
+    #[account(seeds = [b"supported_mint", vault.mint.as_ref()], bump = supported_mint.bump)]
+    pub supported_mint: Option<Box<Account<'info, SupportedMint>>>,
 }
 
 #[derive(Accounts)]
-pub struct CheckIn<'info> {
+pub struct FixReleasedVault<'info> {
+    #[account(seeds = [b"config"], bump)]
+    pub config: Account<'info, Config>,
+
     #[account(
         mut,
-        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
-        bump = vault.bump,
-        has_one = creator
+        seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
+        bump = vault.bump
     )]
     pub vault: Account<'info, Vault>,
 
-    #[account(seeds = [b"vault_counter", creator.key().as_ref()], bump)]
-    pub counter: Account<'info, VaultCounter>,
+    #[account(address = vault.vault_token_account)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub creator: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    /// Required unless `authority` is the admin
+    pub role_assignment: Option<Account<'info, RoleAssignment>>,
 }
 
 #[derive(Accounts)]
-pub struct Release<'info> {
+pub struct CloseVault<'info> {
     #[account(
         mut,
+        close = creator,
         seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
-        bump = vault.bump,
-        has_one = beneficiary
+        bump = vault.bump
     )]
     pub vault: Account<'info, Vault>,
 
-    #[account(seeds = [b"vault_counter", vault.creator.as_ref()], bump)]
-    pub counter: Account<'info, VaultCounter>,
-
+    /// Token vaults only
     #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = vault
+        mut,
+        associated_token::mint = vault.mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: This is the vault creator who will receive the rent refund
+    #[account(mut, address = vault.creator)]
+    pub creator: AccountInfo<'info>,
+
+    /// Anyone can sign to close a released vault
+    pub signer: Signer<'info>,
 
-    #[account(address = vault.usdc_mint)]
-    pub usdc_mint: Account<'info, Mint>,
+    /// Token vaults only
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 
+    /// Pass when the vault has a check-in delegate list; its rent goes back to the creator
     #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = beneficiary
+        mut,
+        close = creator,
+        seeds = [b"checkin_delegates", vault.key().as_ref()],
+        bump = checkin_delegates.bump
     )]
-    pub beneficiary_usdc_ata: Account<'info, TokenAccount>,
+    pub checkin_delegates: Option<Account<'info, CheckinDelegates>>,
+}
 
-    /// CHECK: Beneficiary address is validated via has_one constraint on vault
-    pub beneficiary: AccountInfo<'info>,
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: May hold the legacy Config layout; discriminator, version and admin are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: UncheckedAccount<'info>,
 
+    /// Receives the rent the smaller layout no longer needs
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub admin: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// Tops up rent should the account ever need to grow
+    #[account(mut)]
+    pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CancelVault<'info> {
-    #[account(seeds = [b"config"], bump)]
-    pub config: Box<Account<'info, Config>>,
+pub struct MigrateVault<'info> {
+    /// CHECK: May hold an older Vault layout; owner is this program, discriminator and version are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub vault: UncheckedAccount<'info>,
 
-    #[account(
-        mut,
-        seeds = [b"vault", creator.key().as_ref(), &vault.vault_id.to_le_bytes()],
-        bump = vault.bump,
-        has_one = creator
-    )]
-    pub vault: Box<Account<'info, Vault>>,
+    /// Pays the rent for the larger account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-    #[account(seeds = [b"vault_counter", creator.key().as_ref()], bump)]
-    pub counter: Box<Account<'info, VaultCounter>>,
+// ============================================================================
+// State
+// ============================================================================
 
-    #[account(
-        mut,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = vault
-    )]
-    pub vault_token_account: Box<Account<'info, TokenAccount>>,
+/// Vault pricing tier (prices live in `Config.fee_schedule`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum VaultTier {
+    Base,
+    Plus,
+    Premium,
+    Lifetime,
+}
 
-    #[account(address = vault.usdc_mint)]
-    pub usdc_mint: Box<Account<'info, Mint>>,
+/// How a vault pays out once its grace period ends
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PayoutMode {
+    Push,   // Anyone calls release, which transfers to the beneficiary's ATA
+    Claim,  // Vault becomes claimable; the beneficiary signs claim to a token account of their choice
+}
 
-    #[account(
-        init_if_needed,
-        payer = creator,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = creator
-    )]
-    pub creator_usdc_ata: Box<Account<'info, TokenAccount>>,
+/// What a vault holds; fixed at creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum VaultKind {
+    Token,  // `mint` tokens in `vault_token_account` (create_vault)
+    Sol,    // Lamports in the vault PDA above its rent-exempt minimum (create_sol_vault)
+}
 
-    #[account(
-        mut,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = config.treasury
-    )]
-    pub treasury_usdc_ata: Box<Account<'info, TokenAccount>>,
+/// Fees for a single tier, in the configured mint's base units
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct TierFees {
+    pub creation_fee: u64,
+    pub closing_fee: u64,
+    pub release_fee: u64,  // Flat amount deducted from the payout at release (0 = none)
+    pub release_fee_bps: Option<u16>,  // Overrides FeeSchedule.release_fee_bps for this tier
+}
 
-    #[account(mut)]
-    pub creator: Signer<'info>,
+impl TierFees {
+    pub const NONE: TierFees = TierFees { creation_fee: 0, closing_fee: 0, release_fee: 0, release_fee_bps: None };
+}
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct FeeSchedule {
+    pub base: TierFees,
+    pub plus: TierFees,
+    pub premium: TierFees,
+    pub lifetime: TierFees,
+    pub release_fee_bps: u16,  // Protocol release fee (bps of amount_locked), 0 = none
 }
 
-#[derive(Accounts)]
-pub struct FixReleasedVault<'info> {
-    #[account(seeds = [b"config"], bump, has_one = admin)]
-    pub config: Account<'info, Config>,
+impl FeeSchedule {
+    /// Launch pricing (USDC, 6 decimals)
+    pub const DEFAULT: FeeSchedule = FeeSchedule {
+        base: TierFees { creation_fee: 1_000_000, closing_fee: 1_000_000, release_fee: 0, release_fee_bps: None },         // $1 / $1
+        plus: TierFees { creation_fee: 8_000_000, closing_fee: 5_000_000, release_fee: 0, release_fee_bps: None },         // $8 / $5
+        premium: TierFees { creation_fee: 20_000_000, closing_fee: 10_000_000, release_fee: 0, release_fee_bps: None },    // $20 / $10
+        lifetime: TierFees { creation_fee: 100_000_000, closing_fee: 0, release_fee: 0, release_fee_bps: None },           // $100 / FREE closing
+        release_fee_bps: 0,
+    };
+
+    /// No fees at all
+    pub const NONE: FeeSchedule = FeeSchedule {
+        base: TierFees::NONE,
+        plus: TierFees::NONE,
+        premium: TierFees::NONE,
+        lifetime: TierFees::NONE,
+        release_fee_bps: 0,
+    };
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.release_fee_bps <= MAX_RELEASE_FEE_BPS,
+            KeeprError::InvalidReleaseFee
+        );
+        for tier in [&self.base, &self.plus, &self.premium, &self.lifetime] {
+            require!(
+                tier.release_fee_bps.unwrap_or(0) <= MAX_RELEASE_FEE_BPS,
+                KeeprError::InvalidReleaseFee
+            );
+        }
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
+    /// Fee taken from a release of `gross_amount`: the tier's bps (or the
+    /// protocol default) plus its flat fee, together never more than
+    /// MAX_RELEASE_FEE_BPS of the payout
+    pub fn release_fee(&self, tier: VaultTier, gross_amount: u64) -> Result<u64> {
+        let fees = self.for_tier(tier);
+        let bps = fees.release_fee_bps.unwrap_or(self.release_fee_bps);
+        let bps_fee = pro_rata(gross_amount, bps as u64, BPS_DENOMINATOR)?;
+        let max_fee = pro_rata(gross_amount, MAX_RELEASE_FEE_BPS as u64, BPS_DENOMINATOR)?;
+        Ok(bps_fee.saturating_add(fees.release_fee).min(max_fee))
+    }
 
-    #[account(
-        associated_token::mint = vault.usdc_mint,
-        associated_token::authority = vault
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub fn for_tier(&self, tier: VaultTier) -> &TierFees {
+        match tier {
+            VaultTier::Base => &self.base,
+            VaultTier::Plus => &self.plus,
+            VaultTier::Premium => &self.premium,
+            VaultTier::Lifetime => &self.lifetime,
+        }
+    }
+}
 
-    pub admin: Signer<'info>,
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub version: u8,  // CONFIG_VERSION; keep right after admin so migrations can read it raw
+    pub usdc_mint: Pubkey,
+    pub max_lock_per_vault: u64,
+    pub pause_flags: u8,  // Bitmask of PAUSE_* flags
+    pub treasury: Pubkey,  // Treasury wallet for closing fees
+    pub pending_admin: Option<Pubkey>,  // Set by propose_admin, cleared on accept/cancel
+    pub config_timelock_seconds: u32,   // Delay between queue_config_change and execution
+    pub fee_schedule: FeeSchedule,      // Per-tier creation/closing/release fees
+    pub beneficiary_cooldown_seconds: u32, // Delay before update_beneficiary takes effect
+    pub min_vault_balance: u64,         // Floor withdraw_usdc must leave locked (0 = none)
+}
+
+/// Config layout before versioning (v0); only read by `migrate_config`
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct ConfigV0 {
+    pub admin: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub max_lock_per_vault: u64,
+    pub paused: bool,
+    #[max_len(10)]
+    pub admin_test_wallets: Vec<Pubkey>,
+    pub treasury: Pubkey,
+}
+
+impl ConfigV0 {
+    fn migrate(self) -> Config {
+        Config {
+            admin: self.admin,
+            version: CONFIG_VERSION,
+            usdc_mint: self.usdc_mint,
+            max_lock_per_vault: self.max_lock_per_vault,
+            // `paused` was written but never read by any instruction; a legacy
+            // pause is deliberately carried over as PAUSE_CREATE only
+            pause_flags: if self.paused { PAUSE_CREATE } else { 0 },
+            treasury: self.treasury,
+            pending_admin: None,
+            config_timelock_seconds: 0,
+            fee_schedule: FeeSchedule::DEFAULT,
+            beneficiary_cooldown_seconds: DEFAULT_BENEFICIARY_COOLDOWN_SECONDS,
+            min_vault_balance: 0,
+        }
+    }
+}
+
+impl Config {
+    /// Fail with `Paused` if any of the given pause flags are set
+    pub fn require_not_paused(&self, flag: u8) -> Result<()> {
+        require!(self.pause_flags & flag == 0, KeeprError::Paused);
+        Ok(())
+    }
+
+    /// Vault cap and fee schedule for `mint`: the config's own for the config
+    /// mint, otherwise those of its SupportedMint entry
+    pub fn mint_terms(&self, mint: &Pubkey, supported_mint: Option<&SupportedMint>) -> Result<(u64, FeeSchedule)> {
+        if *mint == self.usdc_mint {
+            return Ok((self.max_lock_per_vault, self.fee_schedule));
+        }
+        match supported_mint {
+            Some(entry) if entry.mint == *mint => Ok((entry.max_lock_per_vault, entry.fee_schedule)),
+            _ => err!(KeeprError::UnsupportedMint),
+        }
+    }
+
+    /// Fee schedule for paying out a vault in `mint`: the config's own for the config mint,
+    /// otherwise that of its SupportedMint entry, even a disabled one. Payouts in a mint that
+    /// was never listed (e.g. the config mint after a rotation that skipped listing it) fail
+    /// closed instead of going fee-free; listing the mint unblocks them.
+    pub fn payout_fees(&self, mint: &Pubkey, supported_mint: Option<&SupportedMint>) -> Result<FeeSchedule> {
+        if *mint == self.usdc_mint {
+            return Ok(self.fee_schedule);
+        }
+        let entry = supported_mint.ok_or(KeeprError::SupportedMintRequired)?;
+        require_keys_eq!(entry.mint, *mint, KeeprError::UnsupportedMint);
+        Ok(entry.fee_schedule)
+    }
+
+    /// Fail with `UnsupportedMint` unless new funds may be locked in `mint`
+    pub fn require_mint_open(&self, mint: &Pubkey, supported_mint: Option<&SupportedMint>) -> Result<()> {
+        if *mint == self.usdc_mint {
+            return Ok(());
+        }
+        require!(
+            supported_mint.is_some_and(|entry| entry.mint == *mint && entry.enabled),
+            KeeprError::UnsupportedMint
+        );
+        Ok(())
+    }
+}
+
+/// Resize a program-owned account to `new_len` (new bytes zero-filled), topping up rent from `payer`
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(new_len);
+    let shortfall = required.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+/// Reject unknown bits, and pausing check-ins while releases stay live, so no vault is
+/// released while its owner is locked out of checking in. Deadlines keep running during a
+/// pause: vaults that come due stay releasable once PAUSE_RELEASE lifts, so lift
+/// PAUSE_CHECK_IN first and give owners time to check in.
+fn validate_pause_flags(flags: u8) -> Result<()> {
+    require!(flags & !PAUSE_ALL == 0, KeeprError::InvalidPauseFlags);
+    if flags & PAUSE_CHECK_IN != 0 {
+        require!(flags & PAUSE_RELEASE != 0, KeeprError::InvalidPauseFlags);
+    }
+    Ok(())
+}
+
+/// Check-in period, notification window and grace period rules shared by every vault kind
+fn validate_vault_timers(
+    checkin_period_seconds: u32,
+    notification_window_seconds: u32,
+    grace_period_seconds: u32,
+) -> Result<()> {
+    // Validate check-in period (must be positive and reasonable)
+    require!(checkin_period_seconds > 0, KeeprError::InvalidCheckinPeriod);
+    require!(
+        checkin_period_seconds <= 31536000, // Max 1 year
+        KeeprError::InvalidCheckinPeriod
+    );
+
+    // Validate dead man's switch parameters
+    require!(
+        notification_window_seconds > 0,
+        KeeprError::InvalidNotificationWindow
+    );
+    require!(
+        notification_window_seconds < checkin_period_seconds,
+        KeeprError::InvalidNotificationWindow
+    );
+    require!(
+        grace_period_seconds > 0,
+        KeeprError::InvalidGracePeriod
+    );
+    Ok(())
+}
+
+/// Hand a vault's registered assets (deposit_token) to `recipient`, then close the registry
+/// and the emptied vault ATAs to `creator`, who paid their rent.
+/// `accounts` holds four per asset, in registry order: asset mint, the vault's ATA for it,
+/// the recipient's ATA for it (created if missing, paid by `payer`) and the mint's token program.
+#[allow(clippy::too_many_arguments)]
+fn transfer_vault_assets<'info>(
+    vault: &Account<'info, Vault>,
+    vault_assets: Option<&mut Account<'info, VaultAssets>>,
+    accounts: &'info [AccountInfo<'info>],
+    recipient: &AccountInfo<'info>,
+    creator: Option<&AccountInfo<'info>>,
+    payer: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let Some(registry) = vault_assets else {
+        require!(vault.asset_count == 0, KeeprError::VaultAssetsRequired);
+        require!(accounts.is_empty(), KeeprError::AssetAccountMismatch);
+        return Ok(());
+    };
+    require!(
+        accounts.len() == registry.assets.len() * 4,
+        KeeprError::AssetAccountMismatch
+    );
+    let creator = creator.ok_or(KeeprError::VaultCreatorRequired)?;
+
+    let seeds = &[
+        b"vault",
+        vault.creator.as_ref(),
+        &vault.vault_id.to_le_bytes(),
+        &[vault.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    for (asset, chunk) in registry.assets.iter().zip(accounts.chunks_exact(4)) {
+        let [mint_info, from_info, to_info, token_program_info] = chunk else {
+            unreachable!();
+        };
+        require_keys_eq!(mint_info.key(), asset.mint, KeeprError::AssetAccountMismatch);
+        require!(
+            *mint_info.owner == token_program_info.key()
+                && (token_program_info.key() == anchor_spl::token::ID
+                    || token_program_info.key() == anchor_spl::token_2022::ID),
+            KeeprError::AssetAccountMismatch
+        );
+        let token_program_id = token_program_info.key();
+        require_keys_eq!(
+            from_info.key(),
+            get_associated_token_address_with_program_id(&vault.key(), &asset.mint, &token_program_id),
+            KeeprError::AssetAccountMismatch
+        );
+        require_keys_eq!(
+            to_info.key(),
+            get_associated_token_address_with_program_id(&recipient.key(), &asset.mint, &token_program_id),
+            KeeprError::AssetAccountMismatch
+        );
+
+        let cpi_accounts = associated_token::Create {
+            payer: payer.clone(),
+            associated_token: to_info.clone(),
+            authority: recipient.clone(),
+            mint: mint_info.clone(),
+            system_program: system_program.clone(),
+            token_program: token_program_info.clone(),
+        };
+        associated_token::create_idempotent(CpiContext::new(associated_token_program.clone(), cpi_accounts))?;
+
+        let decimals = InterfaceAccount::<Mint>::try_from(mint_info)?.decimals;
+        let cpi_accounts = TransferChecked {
+            from: from_info.clone(),
+            mint: mint_info.clone(),
+            to: to_info.clone(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program_info.clone(), cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, asset.amount, decimals)?;
+
+        // Dust sent to the ATA or withheld transfer fees keep it open rather than block the payout
+        if is_closable_token_account(from_info)? {
+            let cpi_accounts = token_interface::CloseAccount {
+                account: from_info.clone(),
+                destination: creator.clone(),
+                authority: vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program_info.clone(), cpi_accounts, signer);
+            token_interface::close_account(cpi_ctx)?;
+        }
+
+        emit!(VaultAssetTransferred {
+            vault: vault.key(),
+            mint: asset.mint,
+            amount: asset.amount,
+            to: recipient.key(),
+        });
+    }
+
+    registry.assets.clear();
+    registry.close(creator.clone())?;
+    Ok(())
+}
+
+/// A token account holding nothing, including Token-2022 transfer fees withheld in it
+fn is_closable_token_account(info: &AccountInfo) -> Result<bool> {
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+    if state.base.amount != 0 {
+        return Ok(false);
+    }
+    Ok(state
+        .get_extension::<TransferFeeAmount>()
+        .map_or(true, |fees| u64::from(fees.withheld_amount) == 0))
+}
+
+/// Heartbeat message for `relay_check_in`: domain || vault || nonce (u64 LE) || expiry (i64 LE)
+pub fn relay_check_in_message(vault: &Pubkey, nonce: u64, expiry: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(RELAY_CHECK_IN_DOMAIN.len() + 48);
+    message.extend_from_slice(RELAY_CHECK_IN_DOMAIN);
+    message.extend_from_slice(vault.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+/// Key that signed `expected_message`, taken from the ed25519 precompile instruction right
+/// before the current one. The precompile already failed the transaction if the signature
+/// was bad; this checks it verified exactly one signature over exactly this message, with
+/// signature, key and message all inside its own instruction data.
+fn verified_ed25519_signer(instructions: &AccountInfo, expected_message: &[u8]) -> Result<Pubkey> {
+    let current = sysvar_instructions::load_current_index_checked(instructions)?;
+    require!(current > 0, KeeprError::InvalidRelaySignature);
+    let ix = sysvar_instructions::load_instruction_at_checked(usize::from(current - 1), instructions)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, KeeprError::InvalidRelaySignature);
+
+    // Header: num_signatures (u8), padding (u8), then 7 u16 offsets for the one signature
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, KeeprError::InvalidRelaySignature);
+    let read_u16 = |at: usize| usize::from(u16::from_le_bytes([data[at], data[at + 1]]));
+    let public_key_offset = read_u16(6);
+    let message_offset = read_u16(10);
+    let message_size = read_u16(12);
+    // u16::MAX = "in this instruction"; anything else could point at unrelated data
+    require!(
+        [4, 8, 14].iter().all(|&at| read_u16(at) == usize::from(u16::MAX)),
+        KeeprError::InvalidRelaySignature
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(KeeprError::InvalidRelaySignature)?;
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(KeeprError::InvalidRelaySignature)?;
+    require!(message == expected_message, KeeprError::InvalidRelaySignature);
+
+    Ok(Pubkey::try_from(public_key).map_err(|_| KeeprError::InvalidRelaySignature)?)
+}
+
+/// Move lamports out of a program-owned account (a SOL vault), never below its rent-exempt minimum
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let rent_floor = Rent::get()?.minimum_balance(from.data_len());
+    let remaining = from
+        .lamports()
+        .checked_sub(amount)
+        .filter(|remaining| *remaining >= rent_floor)
+        .ok_or(KeeprError::InsufficientVaultBalance)?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? = to.lamports().checked_add(amount).ok_or(KeeprError::Overflow)?;
+    Ok(())
+}
+
+/// Where a vault's funds are held: its token account for token vaults, the vault PDA's
+/// own lamports for SOL vaults. Release and cancel pay through it for either kind.
+enum Custody<'a, 'info> {
+    Token {
+        vault_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+        mint: &'a InterfaceAccount<'info, Mint>,
+        token_program: &'a Interface<'info, TokenInterface>,
+    },
+    Sol,
+}
+
+impl<'a, 'info> Custody<'a, 'info> {
+    /// Custody for `vault`'s kind; token vaults must pass their token account, mint and token program
+    fn of(
+        vault: &Vault,
+        vault_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+        mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        token_program: Option<&'a Interface<'info, TokenInterface>>,
+    ) -> Result<Self> {
+        match (vault.kind, vault_token_account, mint, token_program) {
+            (VaultKind::Sol, ..) => Ok(Custody::Sol),
+            (VaultKind::Token, Some(vault_token_account), Some(mint), Some(token_program)) => Ok(Custody::Token {
+                vault_token_account,
+                mint,
+                token_program,
+            }),
+            (VaultKind::Token, ..) => err!(KeeprError::VaultTokenAccountRequired),
+        }
+    }
+
+    /// Check `info` is where `payee` gets paid: a token account of the vault's mint that it
+    /// owns, or its own wallet for SOL vaults
+    fn require_payee_account(&self, info: &'info AccountInfo<'info>, payee: Pubkey) -> Result<()> {
+        match self {
+            Custody::Token { mint, .. } => {
+                let token_account = InterfaceAccount::<TokenAccount>::try_from(info)?;
+                require_keys_eq!(token_account.owner, payee, KeeprError::BeneficiaryAccountMismatch);
+                require_keys_eq!(token_account.mint, mint.key(), KeeprError::MismatchedMint);
+            }
+            Custody::Sol => require_keys_eq!(info.key(), payee, KeeprError::BeneficiaryAccountMismatch),
+        }
+        Ok(())
+    }
+
+    /// Pay `amount` out of the vault into `to` (a token account of its mint, or a wallet)
+    fn pay(&self, vault: &Account<'info, Vault>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let Custody::Token { vault_token_account, mint, token_program } = self else {
+            return move_lamports(&vault.to_account_info(), to, amount);
+        };
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let seeds = &[
+            b"vault",
+            vault.creator.as_ref(),
+            &vault.vault_id.to_le_bytes(),
+            &[vault.bump],
+        ];
+        let signer = &[&seeds[..]];
+        let cpi_accounts = TransferChecked {
+            from: vault_token_account.to_account_info(),
+            mint: mint.to_account_info(),
+            to: to.clone(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+    }
+
+    /// Collect a fee from `payer` into `to`: out of `from`, their token account, for token
+    /// vaults, or out of their own lamports for SOL vaults
+    fn collect_fee(
+        &self,
+        payer: &Signer<'info>,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        system_program: &Program<'info, System>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        match self {
+            Custody::Token { mint, token_program, .. } => {
+                require!(
+                    anchor_spl::token::accessor::amount(from)? >= amount,
+                    KeeprError::InsufficientBalanceForClosingFee
+                );
+                let cpi_accounts = TransferChecked {
+                    from: from.clone(),
+                    mint: mint.to_account_info(),
+                    to: to.clone(),
+                    authority: payer.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+                token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+            }
+            Custody::Sol => {
+                require!(
+                    payer.lamports() >= amount,
+                    KeeprError::InsufficientBalanceForClosingFee
+                );
+                let cpi_accounts = anchor_lang::system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: to.clone(),
+                };
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(system_program.to_account_info(), cpi_accounts),
+                    amount,
+                )
+            }
+        }
+    }
+}
+
+/// Reject Token-2022 mints whose extensions let tokens leave a vault without the
+/// program (permanent delegate) or stop it paying out (non-transferable, transfer hook)
+fn require_custody_safe_mint(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let info = mint.to_account_info();
+    if *info.owner == anchor_spl::token::ID {
+        return Ok(());
+    }
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        require!(
+            !matches!(
+                extension,
+                ExtensionType::PermanentDelegate | ExtensionType::NonTransferable | ExtensionType::TransferHook
+            ),
+            KeeprError::UnsupportedMintExtension
+        );
+    }
+    Ok(())
+}
+
+/// `amount * numerator / denominator`, rounded down, without intermediate overflow
+fn pro_rata(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let scaled = (amount as u128)
+        .checked_mul(numerator as u128)
+        .and_then(|n| n.checked_div(denominator as u128))
+        .ok_or(KeeprError::Overflow)?;
+    u64::try_from(scaled).map_err(|_| error!(KeeprError::Overflow))
+}
+
+/// Delegated admin powers; each privileged instruction accepts the admin or one role
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum Role {
+    Pauser,             // update_config, may only add pause flags
+    FeeManager,         // update_fee_schedule
+    TestWalletManager,  // set_wallet_flags / remove_wallet_flags
+    RecoveryOperator,   // fix_released_vault
+}
+
+/// Role grant (PDA seeds [b"role", role, holder])
+#[account]
+#[derive(InitSpace)]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub holder: Pubkey,
+    pub granted_at: i64,
+    pub payer: Pubkey,  // Receives the rent back on revoke
+    pub bump: u8,
+}
+
+/// Listing for a mint other than the config mint (PDA seeds [b"supported_mint", mint])
+#[account]
+#[derive(InitSpace)]
+pub struct SupportedMint {
+    pub mint: Pubkey,
+    pub max_lock_per_vault: u64,  // In the mint's base units
+    pub fee_schedule: FeeSchedule,  // In the mint's base units
+    pub enabled: bool,  // false = no new vaults or deposits; existing vaults still pay out
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+/// Extra assets escrowed by deposit_token (PDA seeds [b"vault_assets", vault]).
+/// Each asset sits in the vault PDA's ATA for its mint.
+#[account]
+#[derive(InitSpace)]
+pub struct VaultAssets {
+    pub vault: Pubkey,
+    #[max_len(MAX_VAULT_ASSETS)]
+    pub assets: Vec<VaultAsset>,  // Handed over whole by release or cancel_vault, which then close the registry
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct VaultAsset {
+    pub mint: Pubkey,
+    pub amount: u64,  // In the asset mint's base units (1 for an NFT)
+}
+
+/// Keys allowed to check in for a vault besides its creator
+/// (PDA seeds [b"checkin_delegates", vault])
+#[account]
+#[derive(InitSpace)]
+pub struct CheckinDelegates {
+    pub vault: Pubkey,
+    #[max_len(MAX_CHECKIN_DELEGATES)]
+    pub delegates: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl CheckinDelegates {
+    /// `signer` may check in if it is the vault's creator or on the vault's delegate list
+    pub fn require_authority(list: Option<&CheckinDelegates>, creator: &Pubkey, signer: &Pubkey) -> Result<()> {
+        let delegated = list.is_some_and(|list| list.delegates.contains(signer));
+        require!(signer == creator || delegated, KeeprError::NotCheckinAuthority);
+        Ok(())
+    }
 }
 
-#[derive(Accounts)]
-pub struct CloseVault<'info> {
-    #[account(
-        mut,
-        close = creator,
-        seeds = [b"vault", vault.creator.as_ref(), &vault.vault_id.to_le_bytes()],
-        bump = vault.bump
-    )]
-    pub vault: Account<'info, Vault>,
+/// Per-wallet capabilities (PDA seeds [b"flags", wallet])
+#[account]
+#[derive(InitSpace)]
+pub struct WalletFlags {
+    pub wallet: Pubkey,
+    pub flags: u8,  // Bitmask of WALLET_FLAG_* values
+    pub updated_at: i64,
+    pub payer: Pubkey,  // Receives the rent back on removal
+    pub bump: u8,
+}
 
-    #[account(
-        mut,
-        associated_token::mint = vault.usdc_mint,
-        associated_token::authority = vault
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+impl WalletFlags {
+    /// Flags from an optional WalletFlags account (none = no capabilities)
+    pub fn of(account: Option<&WalletFlags>) -> u8 {
+        account.map_or(0, |flags| flags.flags)
+    }
+}
 
-    /// CHECK: This is the vault creator who will receive the rent refund
-    #[account(mut, address = vault.creator)]
-    pub creator: AccountInfo<'info>,
+/// Accept the admin, or a holder of `role` presenting its RoleAssignment.
+/// Returns true if the authority is the admin.
+fn require_role(
+    config: &Config,
+    authority: &Pubkey,
+    assignment: &Option<Account<RoleAssignment>>,
+    role: Role,
+) -> Result<bool> {
+    if *authority == config.admin {
+        return Ok(true);
+    }
+    match assignment {
+        Some(assignment) if assignment.holder == *authority && assignment.role == role => Ok(false),
+        _ => err!(KeeprError::MissingRole),
+    }
+}
 
-    /// Anyone can sign to close a released vault
-    pub signer: Signer<'info>,
+/// Config change waiting out the timelock (single slot, PDA seeds [b"config_change"])
+#[account]
+#[derive(InitSpace)]
+pub struct PendingConfigChange {
+    pub usdc_mint: Option<Pubkey>,
+    pub max_lock_per_vault: Option<u64>,
+    pub config_timelock_seconds: Option<u32>,
+    pub treasury: Option<Pubkey>,
+    pub beneficiary_cooldown_seconds: Option<u32>,
+    pub min_vault_balance: Option<u64>,
+    pub queued_at: i64,
+    pub eta: i64,       // Earliest execution timestamp
+    pub payer: Pubkey,  // Receives the rent back on execute/cancel
+    pub bump: u8,
+}
 
-    pub token_program: Program<'info, Token>,
+/// M-of-N council that can act as `Config.admin` through executed proposals
+#[account]
+#[derive(InitSpace)]
+pub struct AdminCouncil {
+    #[max_len(10)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub proposal_count: u64,
+    pub members_epoch: u32,  // Bumped on membership change; invalidates open proposals
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct CloseConfig<'info> {
-    /// CHECK: We use AccountInfo to avoid deserialization errors during schema migrations
-    #[account(
-        mut,
-        seeds = [b"config"],
-        bump
-    )]
-    pub config: AccountInfo<'info>,
-    #[account(mut)]
-    pub admin: Signer<'info>,
+impl AdminCouncil {
+    /// Position of `member` in the council, used as its approval bit
+    pub fn member_index(&self, member: &Pubkey) -> Result<usize> {
+        self.members
+            .iter()
+            .position(|m| m == member)
+            .ok_or_else(|| error!(KeeprError::NotCouncilMember))
+    }
 }
 
-// ============================================================================
-// State
-// ============================================================================
+fn validate_council(members: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !members.is_empty() && members.len() <= MAX_COUNCIL_MEMBERS,
+        KeeprError::InvalidCouncil
+    );
+    require!(
+        threshold > 0 && threshold as usize <= members.len(),
+        KeeprError::InvalidCouncil
+    );
+    for (i, member) in members.iter().enumerate() {
+        require!(!members[..i].contains(member), KeeprError::InvalidCouncil);
+    }
+    Ok(())
+}
 
-/// Vault pricing tier
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
-pub enum VaultTier {
-    Base,      // $1 creation, $1 closing fee
-    Plus,      // $8 creation, $5 closing fee
-    Premium,   // $20 creation, $10 closing fee
-    Lifetime,  // $100-$500 creation, FREE closing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
 }
 
 #[account]
 #[derive(InitSpace)]
-pub struct Config {
-    pub admin: Pubkey,
-    pub usdc_mint: Pubkey,
-    pub max_lock_per_vault: u64,
-    pub paused: bool,
-    #[max_len(10)]
-    pub admin_test_wallets: Vec<Pubkey>,
-    pub treasury: Pubkey,  // Treasury wallet for closing fees
+pub struct CouncilProposal {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub members_epoch: u32,
+    pub approvals: u16,  // Bitmap indexed by member position
+    pub executed: bool,
+    pub created_at: i64,
+    #[max_len(12)]
+    pub accounts: Vec<ProposalAccountMeta>,
+    #[max_len(512)]
+    pub data: Vec<u8>,
+    pub bump: u8,
 }
 
 #[account]
@@ -812,7 +3919,7 @@ pub struct VaultCounter {
 pub struct Vault {
     pub creator: Pubkey,
     pub beneficiary: Pubkey,
-    pub usdc_mint: Pubkey,
+    pub mint: Pubkey,  // Config mint or a SupportedMint at creation; fixed for the vault's life
     pub vault_token_account: Pubkey,
     pub amount_locked: u64,
     pub unlock_unix: i64,
@@ -829,10 +3936,210 @@ pub struct Vault {
     // New fields for dead man's switch model
     pub tier: VaultTier,           // Pricing tier (Base/Plus/Premium/Lifetime)
     pub created_at: i64,           // Creation timestamp for grace period calculation
-    pub creation_fee_paid: u64,    // Original creation fee (for analytics/refunds)
+    pub creation_fee_paid: u64,    // Creation fee actually collected (for analytics/refunds)
     pub checkin_period_seconds: u32, // Recurring check-in period (replaces fixed unlock)
+    // ---- v1 layout ends here; append new fields below and handle them in migrate_vault ----
+    pub schema_version: u8,        // VAULT_SCHEMA_VERSION (v1 accounts lack this byte)
+    // ---- v2 ----
+    pub beneficiary_count: u8,     // 0 = legacy single payee (`beneficiary` gets everything)
+    pub beneficiary_shares: [BeneficiaryShare; MAX_BENEFICIARIES], // First `beneficiary_count` entries are live
+    pub pending_beneficiary: Pubkey,    // Replaces `beneficiary` at beneficiary_effective_at
+    pub beneficiary_effective_at: i64,  // 0 = no pending update_beneficiary
+    pub backup_beneficiary: Pubkey,     // Default = none; may claim once claim_deadline passes
+    pub claim_window_seconds: u32,      // How long the beneficiary has to claim after release
+    pub claim_deadline: i64,            // Set by release on vaults with a backup; 0 = not opened
+    pub payout_mode: PayoutMode,        // Push (release) or Claim (beneficiary signs claim)
+    pub release_schedule: ReleaseSchedule, // Fixed at creation; tranches == 0 = single release
+    pub amount_released: u64,           // Gross paid out by release_tranche so far (fees included)
+    pub kind: VaultKind,                // Token (mint in vault_token_account) or Sol (lamports in this PDA)
+    pub asset_count: u8,                // Entries in the VaultAssets registry still held (deposit_token)
+    pub relay_nonce: u64,               // Last nonce relay_check_in accepted; the next must be one higher
+    pub early_checkin: bool,            // Check-ins allowed before the notification window
+    pub min_checkin_interval_seconds: u32, // Minimum spacing of early check-ins; 0 = none
+}
+
+impl Vault {
+    pub fn has_release_schedule(&self) -> bool {
+        self.release_schedule.tranches > 0
+    }
+
+    /// Proof of life at `now`: restart the check-in period (rolling deadline).
+    /// Accepted from the notification window until the grace period ends, or earlier on
+    /// vaults with early check-in, at most once per `min_checkin_interval_seconds`.
+    /// Returns whether the check-in was early.
+    pub fn record_check_in(&mut self, now: i64) -> Result<bool> {
+        require!(!self.released, KeeprError::AlreadyReleased);
+        require!(!self.cancelled, KeeprError::VaultAlreadyCancelled);
+
+        // Calculate notification window start time
+        let notification_start = self
+            .unlock_unix
+            .checked_sub(self.notification_window_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+
+        // Before the notification window only if the creator opted in, and not too often
+        let early = now < notification_start;
+        if early {
+            require!(self.early_checkin, KeeprError::NotInNotificationWindow);
+            let next_allowed = self
+                .last_checkin_unix
+                .max(self.created_at)
+                .checked_add(self.min_checkin_interval_seconds.into())
+                .ok_or(KeeprError::Overflow)?;
+            require!(now >= next_allowed, KeeprError::CheckinTooSoon);
+        }
+
+        // Check if we haven't passed the grace period
+        let grace_end = self
+            .unlock_unix
+            .checked_add(self.grace_period_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+
+        require!(now < grace_end, KeeprError::AlreadyReleased);
+
+        // Reset unlock time using checkin_period (rolling deadline)
+        self.unlock_unix = now
+            .checked_add(self.checkin_period_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+
+        // Update last check-in timestamp
+        self.last_checkin_unix = now;
+        self.settle_beneficiary_change(now);
+        Ok(early)
+    }
+
+    /// Number of payees a push release pays (legacy vaults have one)
+    pub fn payee_count(&self) -> usize {
+        usize::from(self.beneficiary_count.max(1))
+    }
+
+    /// Gross amount the release schedule has vested at `now`, out of everything funded.
+    /// The upfront share vests at trigger + cliff; tranche k (1..=tranches) one interval later each.
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        let schedule = &self.release_schedule;
+        let total = self
+            .amount_locked
+            .checked_add(self.amount_released)
+            .ok_or(KeeprError::Overflow)?;
+        let start = self
+            .unlock_unix
+            .checked_add(self.grace_period_seconds.into())
+            .and_then(|t| t.checked_add(schedule.cliff_seconds.into()))
+            .ok_or(KeeprError::Overflow)?;
+        if now < start {
+            return Ok(0);
+        }
+
+        let elapsed = ((now - start) / i64::from(schedule.interval_seconds)).min(schedule.tranches.into());
+        if elapsed == i64::from(schedule.tranches) {
+            return Ok(total);
+        }
+        let upfront = pro_rata(total, schedule.upfront_bps.into(), BPS_DENOMINATOR)?;
+        let vested_tranches = pro_rata(total - upfront, elapsed as u64, schedule.tranches.into())?;
+        Ok(upfront + vested_tranches)
+    }
+
+    /// Payees and payout terms are frozen once the watchdog period starts (same window as
+    /// `cancel_vault`), so the keeper never races a change it is about to act on
+    pub fn require_terms_unlocked(&self, now: i64) -> Result<()> {
+        let notification_start = self
+            .unlock_unix
+            .checked_sub(self.notification_window_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+        require!(now < notification_start, KeeprError::BeneficiariesLocked);
+        Ok(())
+    }
+
+    /// Whether `claim` can pay out at `now` (see `claim` for who may sign)
+    pub fn is_claimable(&self, now: i64) -> Result<bool> {
+        if self.released || self.cancelled {
+            return Ok(false);
+        }
+        if self.claim_deadline != 0 {
+            return Ok(true);
+        }
+        let grace_end = self
+            .unlock_unix
+            .checked_add(self.grace_period_seconds.into())
+            .ok_or(KeeprError::Overflow)?;
+        Ok(self.payout_mode == PayoutMode::Claim && now >= grace_end)
+    }
+
+    /// Beneficiary in effect at `now`, counting a pending change whose cooldown has elapsed
+    pub fn beneficiary_at(&self, now: i64) -> Pubkey {
+        if self.beneficiary_effective_at != 0 && now >= self.beneficiary_effective_at {
+            self.pending_beneficiary
+        } else {
+            self.beneficiary
+        }
+    }
+
+    /// Apply a pending beneficiary change once its cooldown has elapsed
+    pub fn settle_beneficiary_change(&mut self, now: i64) {
+        let beneficiary = self.beneficiary_at(now);
+        if beneficiary != self.beneficiary {
+            self.beneficiary = beneficiary;
+            if self.beneficiary_count > 0 {
+                self.beneficiary_shares[0].beneficiary = beneficiary;
+            }
+            self.pending_beneficiary = Pubkey::default();
+            self.beneficiary_effective_at = 0;
+        }
+    }
+
+    /// Split a net payout into (payee, bps, amount) in share order
+    /// Each share rounds down; the dust goes to the first (primary) payee
+    pub fn payouts(&self, net_amount: u64) -> Vec<(Pubkey, u16, u64)> {
+        if self.beneficiary_count == 0 {
+            return vec![(self.beneficiary, BPS_DENOMINATOR as u16, net_amount)];
+        }
+
+        let mut payouts: Vec<(Pubkey, u16, u64)> = self.beneficiary_shares
+            [..self.beneficiary_count as usize]
+            .iter()
+            .map(|share| {
+                let amount = u128::from(net_amount) * u128::from(share.bps) / u128::from(BPS_DENOMINATOR);
+                (share.beneficiary, share.bps, amount as u64)
+            })
+            .collect();
+        let paid: u64 = payouts.iter().map(|payout| payout.2).sum();
+        payouts[0].2 += net_amount - paid;
+        payouts
+    }
+}
+
+/// Vesting payout, fixed at vault creation: instead of one release, `release_tranche`
+/// pays the upfront share at trigger + cliff, then equal tranches every interval
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct ReleaseSchedule {
+    pub cliff_seconds: u32,     // Delay after the grace period ends before anything vests
+    pub interval_seconds: u32,  // Time between tranches
+    pub tranches: u16,          // 0 = no schedule
+    pub upfront_bps: u16,       // Share paid at the cliff, before the first tranche
+}
+
+impl ReleaseSchedule {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.tranches > 0
+                && self.tranches <= MAX_RELEASE_TRANCHES
+                && self.interval_seconds > 0
+                && u64::from(self.upfront_bps) < BPS_DENOMINATOR,
+            KeeprError::InvalidReleaseSchedule
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct BeneficiaryShare {
+    pub beneficiary: Pubkey,
+    pub bps: u16,  // Out of BPS_DENOMINATOR; shares sum to exactly 10_000
 }
 
+/// Size of the v1 Vault layout (everything before `schema_version`)
+pub const VAULT_V1_SPACE: usize = 229;
+
 // ============================================================================
 // Events
 // ============================================================================
@@ -842,6 +4149,153 @@ pub struct ConfigUpdated {
     pub admin: Pubkey,
 }
 
+#[event]
+pub struct ConfigMigrated {
+    pub admin: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub dropped_test_wallets: Vec<Pubkey>,  // v0 admin_test_wallets; re-add with set_wallet_flags
+    pub excess_rent: u64,                   // Lamports the smaller layout freed, sent to the admin
+}
+
+#[event]
+pub struct VaultMigrated {
+    pub vault: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct WalletFlagsSet {
+    pub authority: Pubkey,
+    pub wallet: Pubkey,
+    pub previous_flags: u8,
+    pub flags: u8,
+}
+
+#[event]
+pub struct WalletFlagsRemoved {
+    pub authority: Pubkey,
+    pub wallet: Pubkey,
+    pub flags: u8,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub admin: Pubkey,
+    pub role: Role,
+    pub holder: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub admin: Pubkey,
+    pub role: Role,
+    pub holder: Pubkey,
+}
+
+#[event]
+pub struct ProtocolPaused {
+    pub admin: Pubkey,
+    pub previous_flags: u8,
+    pub pause_flags: u8,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub usdc_mint: Option<Pubkey>,
+    pub max_lock_per_vault: Option<u64>,
+    pub config_timelock_seconds: Option<u32>,
+    pub treasury: Option<Pubkey>,
+    pub beneficiary_cooldown_seconds: Option<u32>,
+    pub min_vault_balance: Option<u64>,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub usdc_mint: Option<Pubkey>,
+    pub max_lock_per_vault: Option<u64>,
+    pub config_timelock_seconds: Option<u32>,
+    pub treasury: Option<Pubkey>,
+    pub beneficiary_cooldown_seconds: Option<u32>,
+    pub min_vault_balance: Option<u64>,
+}
+
+#[event]
+pub struct FeeScheduleUpdated {
+    pub admin: Pubkey,
+    pub fee_schedule: FeeSchedule,
+}
+
+#[event]
+pub struct SupportedMintSet {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub max_lock_per_vault: u64,
+    pub fee_schedule: FeeSchedule,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub old: Pubkey,
+    pub new: Pubkey,
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub admin: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferCancelled {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct CouncilUpdated {
+    pub council: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub members_epoch: u32,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub member: Pubkey,
+    pub approval_count: u8,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+}
+
 #[event]
 pub struct VaultCreated {
     pub creator: Pubkey,
@@ -857,17 +4311,118 @@ pub struct VaultFunded {
 }
 
 #[event]
-pub struct VaultReleased {
+pub struct VaultAssetDeposited {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub total: u64,
+}
+
+#[event]
+pub struct VaultAssetTransferred {
     pub vault: Pubkey,
+    pub mint: Pubkey,
     pub amount: u64,
     pub to: Pubkey,
 }
 
+#[event]
+pub struct VaultWithdrawn {
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct TrancheReleased {
+    pub vault: Pubkey,
+    pub gross_amount: u64,     // Vested since the previous tranche
+    pub fee: u64,              // Pro rata share of the release fee
+    pub net_amount: u64,       // Received by the beneficiaries (see BeneficiaryPaid for the split)
+    pub amount_released: u64,  // Gross released so far, this tranche included
+    pub remaining: u64,        // Still locked; 0 once the schedule is complete
+}
+
+#[event]
+pub struct VaultReleased {
+    pub vault: Pubkey,
+    pub gross_amount: u64,  // Vault balance before fees
+    pub fee: u64,           // Sent to the treasury
+    pub net_amount: u64,    // Received by the beneficiaries (see BeneficiaryPaid for the split)
+    pub to: Pubkey,         // Primary beneficiary
+}
+
+#[event]
+pub struct BeneficiaryPaid {
+    pub vault: Pubkey,
+    pub beneficiary: Pubkey,
+    pub bps: u16,     // Share of the net payout
+    pub amount: u64,  // Includes rounding dust for the primary beneficiary
+}
+
+#[event]
+pub struct BackupBeneficiarySet {
+    pub vault: Pubkey,
+    pub backup_beneficiary: Option<Pubkey>,
+    pub claim_window_seconds: u32,
+}
+
+#[event]
+pub struct PayoutModeSet {
+    pub vault: Pubkey,
+    pub payout_mode: PayoutMode,
+}
+
+#[event]
+pub struct ClaimWindowOpened {
+    pub vault: Pubkey,
+    pub beneficiary: Pubkey,
+    pub backup_beneficiary: Pubkey,
+    pub claim_deadline: i64,  // Backup may claim from here on
+}
+
+#[event]
+pub struct BeneficiaryChanged {
+    pub vault: Pubkey,
+    pub old: Pubkey,
+    pub new: Pubkey,           // Equal to `old` when a pending change is withdrawn
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct BeneficiariesUpdated {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub shares: Vec<BeneficiaryShare>,
+}
+
 #[event]
 pub struct VaultCheckedIn {
     pub vault: Pubkey,
     pub creator: Pubkey,
+    pub checked_in_by: Pubkey,  // The creator or a check-in delegate
     pub new_unlock_unix: i64,
+    pub relayed: bool,  // Signed off-chain and submitted by a relayer (relay_check_in)
+    pub early: bool,  // Before the notification window (early check-in enabled)
+}
+
+#[event]
+pub struct EarlyCheckinSet {
+    pub vault: Pubkey,
+    pub enabled: bool,
+    pub min_interval_seconds: u32,
+}
+
+#[event]
+pub struct CheckinDelegateAdded {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct CheckinDelegateRemoved {
+    pub vault: Pubkey,
+    pub delegate: Pubkey,
 }
 
 #[event]
@@ -922,11 +4477,155 @@ pub enum KeeprError {
     #[msg("Cannot cancel vault after it has been released.")]
     CannotCancelAfterRelease,
     #[msg("Admin test wallets list cannot exceed 10 wallets.")]
-    AdminTestWalletsLimitExceeded,
+    AdminTestWalletsLimitExceeded,  // Retired with Config.admin_test_wallets; kept so later codes don't shift
     #[msg("Cannot cancel during watchdog period - prevents gaming the system.")]
     CannotCancelDuringWatchdog,
     #[msg("Insufficient USDC balance to pay closing fee.")]
     InsufficientBalanceForClosingFee,
     #[msg("Invalid check-in period - must be between 1 second and 1 year.")]
     InvalidCheckinPeriod,
+    #[msg("Invalid pause flags - check-ins can only be paused together with releases.")]
+    InvalidPauseFlags,
+    #[msg("Invalid admin - must differ from the current admin.")]
+    InvalidAdmin,
+    #[msg("No admin transfer is pending.")]
+    NoPendingAdmin,
+    #[msg("Signer is not the pending admin.")]
+    NotPendingAdmin,
+    #[msg("Invalid council - 1 to 10 unique members and a threshold between 1 and the member count.")]
+    InvalidCouncil,
+    #[msg("Signer is not a council member.")]
+    NotCouncilMember,
+    #[msg("Member has already approved this proposal.")]
+    ProposalAlreadyApproved,
+    #[msg("Proposal has already been executed.")]
+    ProposalAlreadyExecuted,
+    #[msg("Proposal does not have enough approvals.")]
+    ProposalThresholdNotMet,
+    #[msg("Proposal was created under a previous council membership.")]
+    StaleProposal,
+    #[msg("Proposal instruction exceeds the size limit.")]
+    ProposalTooLarge,
+    #[msg("Remaining accounts do not match the proposal.")]
+    ProposalAccountMismatch,
+    #[msg("Config timelock cannot exceed 30 days.")]
+    InvalidTimelock,
+    #[msg("Config change must set at least one field.")]
+    EmptyConfigChange,
+    #[msg("Config change timelock has not elapsed yet.")]
+    ConfigChangeNotReady,
+    #[msg("New treasury must have a USDC associated token account.")]
+    MissingTreasuryAta,
+    #[msg("Creation fee does not match the fee schedule for this tier.")]
+    CreationFeeMismatch,
+    #[msg("Release fee exceeds the protocol maximum.")]
+    InvalidReleaseFee,
+    #[msg("Signer is neither the admin nor a holder of the required role.")]
+    MissingRole,
+    #[msg("Pausers can only add pause flags; unpausing requires the admin.")]
+    PauserCannotUnpause,
+    #[msg("Config account layout is not a known version.")]
+    UnsupportedConfigVersion,
+    #[msg("Config is already at the current version.")]
+    ConfigUpToDate,
+    #[msg("Signer is not the config admin.")]
+    NotConfigAdmin,
+    #[msg("Vault account layout is not a known version.")]
+    UnsupportedVaultVersion,
+    #[msg("Vault is already at the current schema version.")]
+    VaultUpToDate,
+    #[msg("Wallet flags must be a non-empty combination of known flags.")]
+    InvalidWalletFlags,
+    #[msg("Beneficiary shares must be 1 to 5 unique payees with non-zero bps summing to 10000.")]
+    InvalidBeneficiaryShares,
+    #[msg("Beneficiaries cannot be changed during the watchdog period.")]
+    BeneficiariesLocked,
+    #[msg("Remaining accounts must be one token account per additional beneficiary, in share order.")]
+    BeneficiaryAccountMismatch,
+    #[msg("Beneficiary cooldown cannot exceed 30 days.")]
+    InvalidBeneficiaryCooldown,
+    #[msg("Beneficiary account does not match the vault's current beneficiary.")]
+    BeneficiaryMismatch,
+    #[msg("The first share must be the current beneficiary; change it with update_beneficiary.")]
+    PrimaryBeneficiaryChange,
+    #[msg("Backup beneficiary must be a distinct payee on a single-beneficiary vault.")]
+    InvalidBackupBeneficiary,
+    #[msg("Claim window must be between 1 second and 1 year.")]
+    InvalidClaimWindow,
+    #[msg("Release already opened the claim window; the beneficiary must claim.")]
+    ClaimPending,
+    #[msg("Claim window has not been opened by release.")]
+    ClaimNotOpen,
+    #[msg("Signer is not eligible to claim this vault yet.")]
+    NotClaimant,
+    #[msg("Claim mode needs a single-beneficiary vault.")]
+    ClaimModeRequiresSinglePayee,
+    #[msg("Vault is in claim mode; the beneficiary must claim.")]
+    ClaimModeVault,
+    #[msg("Claim destination must be a token account owned by the claimant.")]
+    InvalidClaimDestination,
+    #[msg("Cannot withdraw during the watchdog period.")]
+    CannotWithdrawDuringWatchdog,
+    #[msg("Withdrawal exceeds the vault's locked balance.")]
+    InsufficientVaultBalance,
+    #[msg("Withdrawal would leave less than the minimum vault balance.")]
+    BelowMinimumVaultBalance,
+    #[msg("Release schedule needs 1 to 120 tranches, a non-zero interval and an upfront share below 100%.")]
+    InvalidReleaseSchedule,
+    #[msg("Vault has a release schedule; use release_tranche.")]
+    ReleaseScheduled,
+    #[msg("Vault has no release schedule; use release.")]
+    NoReleaseSchedule,
+    #[msg("Nothing has vested since the last tranche.")]
+    NothingVested,
+    #[msg("Vaults with a release schedule cannot use a backup beneficiary or claim mode.")]
+    ReleaseScheduleConflict,
+    #[msg("Mint is not supported for new vaults or deposits.")]
+    UnsupportedMint,
+    #[msg("The config mint uses the config's cap and fee schedule.")]
+    ConfigMintListed,
+    #[msg("Mint has a Token-2022 extension that breaks vault custody.")]
+    UnsupportedMintExtension,
+    #[msg("Vault holds SOL, which has no token deposits, backup beneficiary, claim mode or vesting.")]
+    SolVault,
+    #[msg("Vault holds tokens, not SOL.")]
+    NotSolVault,
+    #[msg("Token vaults need their token accounts, mint and token program.")]
+    VaultTokenAccountRequired,
+    #[msg("Vault already holds the maximum number of registered assets.")]
+    TooManyVaultAssets,
+    #[msg("The vault's own mint is deposited with deposit_usdc.")]
+    PrimaryMintAsset,
+    #[msg("Registered assets need a push-mode vault without a backup beneficiary or release schedule.")]
+    VaultAssetsConflict,
+    #[msg("Vault has registered assets; pass its asset registry.")]
+    VaultAssetsRequired,
+    #[msg("Asset accounts must follow the vault's asset registry.")]
+    AssetAccountMismatch,
+    #[msg("Signer is neither the vault's creator nor one of its check-in delegates.")]
+    NotCheckinAuthority,
+    #[msg("Check-in delegate must be a new key other than the creator.")]
+    InvalidCheckinDelegate,
+    #[msg("Vault already has the maximum number of check-in delegates.")]
+    TooManyCheckinDelegates,
+    #[msg("Key is not a check-in delegate of this vault.")]
+    CheckinDelegateNotFound,
+    #[msg("Relayed check-in needs an ed25519 verification of the heartbeat right before it.")]
+    InvalidRelaySignature,
+    #[msg("Signed heartbeat has expired.")]
+    RelayExpired,
+    #[msg("Heartbeat nonce must be exactly one above the last relayed one.")]
+    StaleRelayNonce,
+    #[msg("Minimum check-in interval must be shorter than the check-in period.")]
+    InvalidCheckinInterval,
+    #[msg("Too soon since the last check-in for an early check-in.")]
+    CheckinTooSoon,
+    #[msg("Vaults outside the config mint must pass their mint's SupportedMint account.")]
+    SupportedMintRequired,
+    #[msg("Vault has registered assets; pass its creator to refund their rent.")]
+    VaultCreatorRequired,
+    #[msg("SOL vaults pay their fees to the treasury wallet; pass it as treasury.")]
+    TreasuryWalletRequired,
+    #[msg("Signed heartbeat expires too far in the future.")]
+    RelayExpiryTooFar,
 }
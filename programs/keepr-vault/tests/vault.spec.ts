@@ -1,11 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { PublicKey, Keypair, SystemProgram, Transaction, sendAndConfirmTransaction, Ed25519Program, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
 import { 
   TOKEN_PROGRAM_ID, 
+  TOKEN_2022_PROGRAM_ID,
+  NATIVE_MINT,
   ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializePermanentDelegateInstruction,
   createMint,
   createAccount,
+  createAssociatedTokenAccount,
+  getOrCreateAssociatedTokenAccount,
   mintTo,
   getAccount,
 } from "@solana/spl-token";
@@ -22,10 +31,11 @@ describe("keepr-vault", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
-  const programId = new PublicKey("74v7NZh7A6SH9DmKZRC4tFUwaLvq19KfD1NGni62XQJK");
+  // Matches declare_id! and [programs.localnet] in the workspace Anchor.toml
+  const programId = new PublicKey("Aw5FwXAnbzB6e7A5zrw8G244VnwW3vV3Uz5rrDFt6ipj");
   
-  // Load IDL
-  const idlPath = path.join(__dirname, "../target/idl/keepr_vault.json");
+  // Load IDL (built into the workspace target/ by anchor build)
+  const idlPath = path.join(__dirname, "../../../target/idl/keepr_vault.json");
   const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
   idl.address = programId.toBase58();
   
@@ -35,6 +45,7 @@ describe("keepr-vault", () => {
   let admin: Keypair;
   let creator: Keypair;
   let beneficiary: Keypair;
+  let treasury: Keypair;
   let usdcMint: PublicKey;
   let configPda: PublicKey;
   let counterPda: PublicKey;
@@ -46,11 +57,170 @@ describe("keepr-vault", () => {
   const MAX_LOCK_PER_VAULT = new anchor.BN(500_000_000); // 500 USDC
   const MIN_UNLOCK_BUFFER = 300; // 5 minutes
 
+  // Pause flags (mirror PAUSE_* constants in lib.rs)
+  const PAUSE_CREATE = 1 << 0;
+  const PAUSE_DEPOSIT = 1 << 1;
+  const PAUSE_CANCEL = 1 << 2;
+  const PAUSE_RELEASE = 1 << 3;
+  const PAUSE_CHECK_IN = 1 << 4;
+  const PAUSE_WITHDRAW = 1 << 5;
+  const PAUSE_DEPOSIT_FREEZE = PAUSE_CREATE | PAUSE_DEPOSIT;
+
+  const sleep = (ms: number) => new Promise(resolve => setTimeout(resolve, ms));
+
+  const vaultPdaFor = (owner: PublicKey, vaultId: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("vault"),
+        owner.toBuffer(),
+        Buffer.from(new Uint8Array(new BigUint64Array([BigInt(vaultId)]).buffer).slice(0, 8)),
+      ],
+      programId
+    )[0];
+
+  const counterPdaFor = (owner: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_counter"), owner.toBuffer()],
+      programId
+    )[0];
+
+  const ataFor = (owner: PublicKey, mint: PublicKey, tokenProgram: PublicKey = TOKEN_PROGRAM_ID) =>
+    PublicKey.findProgramAddressSync(
+      [owner.toBuffer(), tokenProgram.toBuffer(), mint.toBuffer()],
+      ASSOCIATED_TOKEN_PROGRAM_ID
+    )[0];
+
+  // Token balance of `account`, 0 if it hasn't been created yet
+  const balanceOf = async (account: PublicKey) => {
+    const info = await provider.connection.getAccountInfo(account);
+    return info ? BigInt((await getAccount(provider.connection, account)).amount.toString()) : BigInt(0);
+  };
+
+  type VaultOptions = {
+    beneficiary?: PublicKey;        // Defaults to the shared beneficiary
+    checkinSecs?: number;
+    notificationSecs?: number;
+    graceSecs?: number;
+    tier?: object;
+    creationFeePaid?: number;       // Must match the tier's fee in `mint`
+    releaseSchedule?: object;
+    mint?: PublicKey;               // Defaults to the config mint
+    supportedMint?: PublicKey;      // Listing PDA for any other mint
+    amount?: number;                // Deposited right after creation (0 = none)
+  };
+
+  // Deposit into one of `owner`'s vaults from their ATA for the vault's mint
+  const depositInto = (owner: Keypair, vault: PublicKey, amount: number, options: VaultOptions = {}) => {
+    const mint = options.mint ?? usdcMint;
+    return program.methods
+      .depositUsdc(new anchor.BN(amount))
+      .accounts({
+        config: configPda,
+        vault,
+        counter: counterPdaFor(owner.publicKey),
+        vaultTokenAccount: ataFor(vault, mint),
+        mint,
+        creatorUsdcAta: ataFor(owner.publicKey, mint),
+        creator: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...(options.supportedMint ? { supportedMint: options.supportedMint } : {}),
+      })
+      .signers([owner])
+      .rpc();
+  };
+
+  // create_vault (Base tier, 1h check-in, 1s notification window and grace period
+  // unless overridden), then deposit `options.amount`; returns the vault PDA
+  const createFundedVault = async (owner: Keypair, vaultId: number, options: VaultOptions = {}) => {
+    const mint = options.mint ?? usdcMint;
+    const vault = vaultPdaFor(owner.publicKey, vaultId);
+    await program.methods
+      .createVault(
+        options.beneficiary ?? beneficiary.publicKey,
+        options.checkinSecs ?? 3600,
+        Array.from(Buffer.alloc(32, vaultId)),
+        options.notificationSecs ?? 1,
+        options.graceSecs ?? 1,
+        options.tier ?? { base: {} },
+        new anchor.BN(options.creationFeePaid ?? 1_000_000),
+        options.releaseSchedule ?? null
+      )
+      .accounts({
+        config: configPda,
+        counter: counterPdaFor(owner.publicKey),
+        vault,
+        vaultTokenAccount: ataFor(vault, mint),
+        mint,
+        creatorUsdcAta: ataFor(owner.publicKey, mint),
+        treasuryUsdcAta: ataFor(treasury.publicKey, mint),
+        creator: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        ...(options.supportedMint ? { supportedMint: options.supportedMint } : {}),
+      })
+      .signers([owner])
+      .rpc();
+
+    if (options.amount) {
+      await depositInto(owner, vault, options.amount, options);
+    }
+    return vault;
+  };
+
+  const configChangePda = PublicKey.findProgramAddressSync(
+    [Buffer.from("config_change")],
+    programId
+  )[0];
+
+  const queueConfigChange = (
+    usdcMint: PublicKey | null,
+    maxLockPerVault: anchor.BN | null,
+    configTimelockSeconds: number | null,
+    newTreasury: PublicKey | null = null,
+    beneficiaryCooldownSeconds: number | null = null,
+    minVaultBalance: anchor.BN | null = null
+  ) =>
+    program.methods
+      .queueConfigChange(usdcMint, maxLockPerVault, configTimelockSeconds, newTreasury, beneficiaryCooldownSeconds, minVaultBalance)
+      .accounts({
+        config: configPda,
+        configChange: configChangePda,
+        admin: admin.publicKey,
+        payer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+  const executeConfigChange = (newTreasuryUsdcAta: PublicKey | null = null) =>
+    program.methods
+      .executeConfigChange()
+      .accounts({
+        config: configPda,
+        configChange: configChangePda,
+        newTreasuryUsdcAta,
+        payer: admin.publicKey,
+      })
+      .rpc();
+
+  const setPauseFlags = (flags: number) =>
+    program.methods
+      .updateConfig(flags)
+      .accounts({
+        config: configPda,
+        authority: admin.publicKey,
+        roleAssignment: null,
+      })
+      .signers([admin])
+      .rpc();
+
   before(async () => {
     // Create test keypairs
     admin = Keypair.generate();
     creator = Keypair.generate();
     beneficiary = Keypair.generate();
+    treasury = Keypair.generate();
 
     // Airdrop SOL to test accounts
     const airdropAmount = 10 * anchor.web3.LAMPORTS_PER_SOL;
@@ -86,7 +256,7 @@ describe("keepr-vault", () => {
   describe("init_config", () => {
     it("initializes config successfully", async () => {
       const tx = await program.methods
-        .initConfig(usdcMint, MAX_LOCK_PER_VAULT, false)
+        .initConfig(usdcMint, MAX_LOCK_PER_VAULT, 0, treasury.publicKey, 0)
         .accounts({
           config: configPda,
           admin: admin.publicKey,
@@ -102,13 +272,13 @@ describe("keepr-vault", () => {
       assert.equal(config.admin.toBase58(), admin.publicKey.toBase58());
       assert.equal(config.usdcMint.toBase58(), usdcMint.toBase58());
       assert.equal(config.maxLockPerVault.toString(), MAX_LOCK_PER_VAULT.toString());
-      assert.equal(config.paused, false);
+      assert.equal(config.pauseFlags, 0);
     });
 
     it("fails to initialize config twice", async () => {
       try {
         await program.methods
-          .initConfig(usdcMint, MAX_LOCK_PER_VAULT, false)
+          .initConfig(usdcMint, MAX_LOCK_PER_VAULT, 0, treasury.publicKey, 0)
           .accounts({
             config: configPda,
             admin: admin.publicKey,
@@ -127,14 +297,9 @@ describe("keepr-vault", () => {
     it("updates config parameters", async () => {
       const newCap = new anchor.BN(1_000_000_000); // 1000 USDC
 
-      const tx = await program.methods
-        .updateConfig(null, newCap, null)
-        .accounts({
-          config: configPda,
-          admin: admin.publicKey,
-        })
-        .signers([admin])
-        .rpc();
+      // Config is initialized with a zero timelock, so the change can execute right away
+      await queueConfigChange(null, newCap, null);
+      const tx = await executeConfigChange();
 
       console.log("  ✓ Config updated:", tx);
 
@@ -145,109 +310,94 @@ describe("keepr-vault", () => {
     it("fails when non-admin tries to update", async () => {
       try {
         await program.methods
-          .updateConfig(null, MAX_LOCK_PER_VAULT, null)
+          .updateConfig(PAUSE_CREATE)
           .accounts({
             config: configPda,
-            admin: creator.publicKey,
+            authority: creator.publicKey,
+            roleAssignment: null,
           })
           .signers([creator])
           .rpc();
         assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "has_one");
+        assert.include(err.message, "MissingRole");
       }
     });
 
     it("pauses and unpauses vault creation", async () => {
       // Pause
-      await program.methods
-        .updateConfig(null, null, true)
-        .accounts({
-          config: configPda,
-          admin: admin.publicKey,
-        })
-        .signers([admin])
-        .rpc();
+      await setPauseFlags(PAUSE_CREATE);
 
       let config = await (program.account as any).config.fetch(configPda);
-      assert.equal(config.paused, true);
+      assert.equal(config.pauseFlags, PAUSE_CREATE);
 
       // Unpause
-      await program.methods
-        .updateConfig(null, null, false)
-        .accounts({
-          config: configPda,
-          admin: admin.publicKey,
-        })
-        .signers([admin])
-        .rpc();
+      await setPauseFlags(0);
 
       config = await (program.account as any).config.fetch(configPda);
-      assert.equal(config.paused, false);
+      assert.equal(config.pauseFlags, 0);
+    });
+
+    it("pauses releases on their own", async () => {
+      await setPauseFlags(PAUSE_RELEASE);
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.pauseFlags, PAUSE_RELEASE);
+
+      await setPauseFlags(0);
+    });
+
+    it("rejects pausing check-ins while releases stay live", async () => {
+      try {
+        await setPauseFlags(PAUSE_CHECK_IN);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidPauseFlags");
+      }
+    });
+
+    it("rejects unknown pause flags", async () => {
+      try {
+        await setPauseFlags(1 << 7);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidPauseFlags");
+      }
     });
   });
 
   describe("create_vault", () => {
-    const nameHash = Buffer.alloc(32, 1); // Mock name hash
-    let unlockUnix: anchor.BN;
-
     before(async () => {
-      // Set unlock time to 10 minutes from now
-      const now = Math.floor(Date.now() / 1000);
-      unlockUnix = new anchor.BN(now + 600);
-
-      // Derive PDAs
-      [counterPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("vault_counter"), creator.publicKey.toBuffer()],
-        programId
+      creatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        creator,
+        usdcMint,
+        creator.publicKey
       );
-
-      [vaultPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("vault"),
-          creator.publicKey.toBuffer(),
-          Buffer.from(new Uint8Array(new BigUint64Array([BigInt(1)]).buffer).slice(0, 8)),
-        ],
-        programId
+      await mintTo(
+        provider.connection,
+        admin,
+        usdcMint,
+        creatorUsdcAta,
+        admin,
+        200_000_000 // 200 USDC
       );
+      await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey);
 
-      // Derive vault token account (ATA)
-      [vaultTokenAccount] = PublicKey.findProgramAddressSync(
-        [
-          vaultPda.toBuffer(),
-          TOKEN_PROGRAM_ID.toBuffer(),
-          usdcMint.toBuffer(),
-        ],
-        ASSOCIATED_TOKEN_PROGRAM_ID
-      );
+      counterPda = counterPdaFor(creator.publicKey);
     });
 
     it("creates vault successfully", async () => {
-      const tx = await program.methods
-        .createVault(beneficiary.publicKey, unlockUnix, Array.from(nameHash))
-        .accounts({
-          config: configPda,
-          counter: counterPda,
-          vault: vaultPda,
-          vaultTokenAccount,
-          usdcMint,
-          creator: creator.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([creator])
-        .rpc();
-
-      console.log("  ✓ Vault created:", tx);
+      vaultPda = await createFundedVault(creator, 1, { notificationSecs: 60, graceSecs: 60 });
+      vaultTokenAccount = ataFor(vaultPda, usdcMint);
 
       // Verify vault state
       const vault = await (program.account as any).vault.fetch(vaultPda);
       assert.equal(vault.creator.toBase58(), creator.publicKey.toBase58());
       assert.equal(vault.beneficiary.toBase58(), beneficiary.publicKey.toBase58());
-      assert.equal(vault.usdcMint.toBase58(), usdcMint.toBase58());
+      assert.equal(vault.mint.toBase58(), usdcMint.toBase58());
       assert.equal(vault.amountLocked.toString(), "0");
-      assert.equal(vault.unlockUnix.toString(), unlockUnix.toString());
+      assert.equal(vault.checkinPeriodSeconds, 3600);
       assert.equal(vault.released, false);
       assert.equal(vault.vaultId.toString(), "1");
 
@@ -259,228 +409,56 @@ describe("keepr-vault", () => {
       const tokenAccount = await getAccount(provider.connection, vaultTokenAccount);
       assert.equal(tokenAccount.mint.toBase58(), usdcMint.toBase58());
       assert.equal(tokenAccount.owner.toBase58(), vaultPda.toBase58());
-    });
 
-    it("fails with unlock time in the past", async () => {
-      const pastUnlock = new anchor.BN(Math.floor(Date.now() / 1000) - 100);
-      const [badVaultPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("vault"),
-          creator.publicKey.toBuffer(),
-          Buffer.from(new Uint8Array(new BigUint64Array([BigInt(2)]).buffer).slice(0, 8)),
-        ],
-        programId
-      );
-      const [badVaultTokenAccount] = PublicKey.findProgramAddressSync(
-        [
-          badVaultPda.toBuffer(),
-          TOKEN_PROGRAM_ID.toBuffer(),
-          usdcMint.toBuffer(),
-        ],
-        ASSOCIATED_TOKEN_PROGRAM_ID
-      );
+      // Base tier creation fee
+      assert.equal(await balanceOf(creatorUsdcAta), BigInt(199_000_000));
+    });
 
+    it("fails with a zero check-in period", async () => {
       try {
-        await program.methods
-          .createVault(beneficiary.publicKey, pastUnlock, Array.from(nameHash))
-          .accounts({
-            config: configPda,
-            counter: counterPda,
-            vault: badVaultPda,
-            vaultTokenAccount: badVaultTokenAccount,
-            usdcMint,
-            creator: creator.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-          })
-          .signers([creator])
-          .rpc();
+        await createFundedVault(creator, 2, { checkinSecs: 0 });
         assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "InvalidUnlockTime");
+        assert.include(err.message, "InvalidCheckinPeriod");
       }
     });
 
     it("fails when paused", async () => {
-      // Pause vault creation
-      await program.methods
-        .updateConfig(null, null, true)
-        .accounts({
-          config: configPda,
-          admin: admin.publicKey,
-        })
-        .signers([admin])
-        .rpc();
-
-      const [pausedVaultPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("vault"),
-          creator.publicKey.toBuffer(),
-          Buffer.from(new Uint8Array(new BigUint64Array([BigInt(2)]).buffer).slice(0, 8)),
-        ],
-        programId
-      );
-      const [pausedVaultTokenAccount] = PublicKey.findProgramAddressSync(
-        [
-          pausedVaultPda.toBuffer(),
-          TOKEN_PROGRAM_ID.toBuffer(),
-          usdcMint.toBuffer(),
-        ],
-        ASSOCIATED_TOKEN_PROGRAM_ID
-      );
+      await setPauseFlags(PAUSE_CREATE);
 
       try {
-        await program.methods
-          .createVault(beneficiary.publicKey, unlockUnix, Array.from(nameHash))
-          .accounts({
-            config: configPda,
-            counter: counterPda,
-            vault: pausedVaultPda,
-            vaultTokenAccount: pausedVaultTokenAccount,
-            usdcMint,
-            creator: creator.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-          })
-          .signers([creator])
-          .rpc();
+        await createFundedVault(creator, 2);
         assert.fail("Should have failed");
       } catch (err) {
         assert.include(err.message, "Paused");
-      }
-
-      // Unpause for next tests
-      await program.methods
-        .updateConfig(null, null, false)
-        .accounts({
-          config: configPda,
-          admin: admin.publicKey,
-        })
-        .signers([admin])
-        .rpc();
-    });
-
-    it("enforces USDC mint", async () => {
-      // Create wrong mint
-      const wrongMint = await createMint(
-        provider.connection,
-        admin,
-        admin.publicKey,
-        null,
-        6
-      );
-
-      const [wrongVaultPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("vault"),
-          creator.publicKey.toBuffer(),
-          Buffer.from(new Uint8Array(new BigUint64Array([BigInt(2)]).buffer).slice(0, 8)),
-        ],
-        programId
-      );
-      const [wrongVaultTokenAccount] = PublicKey.findProgramAddressSync(
-        [
-          wrongVaultPda.toBuffer(),
-          TOKEN_PROGRAM_ID.toBuffer(),
-          wrongMint.toBuffer(),
-        ],
-        ASSOCIATED_TOKEN_PROGRAM_ID
-      );
-
-      try {
-        await program.methods
-          .createVault(beneficiary.publicKey, unlockUnix, Array.from(nameHash))
-          .accounts({
-            config: configPda,
-            counter: counterPda,
-            vault: wrongVaultPda,
-            vaultTokenAccount: wrongVaultTokenAccount,
-            usdcMint: wrongMint,
-            creator: creator.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-          })
-          .signers([creator])
-          .rpc();
-        assert.fail("Should have failed");
-      } catch (err) {
-        assert.include(err.message, "address");
+      } finally {
+        // Unpause for next tests
+        await setPauseFlags(0);
       }
     });
   });
 
   describe("deposit_usdc", () => {
-    const depositAmount = new anchor.BN(100_000_000); // 100 USDC
-
-    before(async () => {
-      // Create creator's USDC token account and mint tokens
-      creatorUsdcAta = await createAccount(
-        provider.connection,
-        creator,
-        usdcMint,
-        creator.publicKey
-      );
-
-      await mintTo(
-        provider.connection,
-        admin,
-        usdcMint,
-        creatorUsdcAta,
-        admin,
-        200_000_000 // 200 USDC
-      );
-    });
+    const depositAmount = 100_000_000; // 100 USDC
 
     it("deposits USDC successfully", async () => {
-      const tx = await program.methods
-        .depositUsdc(depositAmount)
-        .accounts({
-          config: configPda,
-          vault: vaultPda,
-          counter: counterPda,
-          vaultTokenAccount,
-          usdcMint,
-          creatorUsdcAta,
-          creator: creator.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-        })
-        .signers([creator])
-        .rpc();
-
-      console.log("  ✓ USDC deposited:", tx);
+      await depositInto(creator, vaultPda, depositAmount);
 
       // Verify vault balance updated
       const vault = await (program.account as any).vault.fetch(vaultPda);
-      assert.equal(vault.amountLocked.toString(), depositAmount.toString());
+      assert.equal(vault.amountLocked.toNumber(), depositAmount);
 
       // Verify token account balance
       const tokenAccount = await getAccount(provider.connection, vaultTokenAccount);
       assert.equal(tokenAccount.amount.toString(), depositAmount.toString());
 
       // Verify creator balance decreased
-      const creatorAccount = await getAccount(provider.connection, creatorUsdcAta);
-      assert.equal(creatorAccount.amount.toString(), "100000000"); // 100 USDC remaining
+      assert.equal(await balanceOf(creatorUsdcAta), BigInt(99_000_000)); // 99 USDC remaining
     });
 
     it("fails with zero amount", async () => {
       try {
-        await program.methods
-          .depositUsdc(new anchor.BN(0))
-          .accounts({
-            config: configPda,
-            vault: vaultPda,
-            counter: counterPda,
-            vaultTokenAccount,
-            usdcMint,
-            creatorUsdcAta,
-            creator: creator.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-          })
-          .signers([creator])
-          .rpc();
+        await depositInto(creator, vaultPda, 0);
         assert.fail("Should have failed");
       } catch (err) {
         assert.include(err.message, "InvalidAmount");
@@ -488,23 +466,11 @@ describe("keepr-vault", () => {
     });
 
     it("fails when exceeding vault cap", async () => {
-      const excessAmount = new anchor.BN(500_000_000); // Would total 600 USDC
+      const config = await (program.account as any).config.fetch(configPda);
+      const excessAmount = config.maxLockPerVault.toNumber() - depositAmount + 1;
 
       try {
-        await program.methods
-          .depositUsdc(excessAmount)
-          .accounts({
-            config: configPda,
-            vault: vaultPda,
-            counter: counterPda,
-            vaultTokenAccount,
-            usdcMint,
-            creatorUsdcAta,
-            creator: creator.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-          })
-          .signers([creator])
-          .rpc();
+        await depositInto(creator, vaultPda, excessAmount);
         assert.fail("Should have failed");
       } catch (err) {
         assert.include(err.message, "AboveVaultCap");
@@ -514,247 +480,3524 @@ describe("keepr-vault", () => {
 
   describe("release", () => {
     let releasableVaultPda: PublicKey;
-    let releasableVaultTokenAccount: PublicKey;
-    let releasableUnlockUnix: anchor.BN;
+
+    const releaseAccounts = (vault: PublicKey) => ({
+      config: configPda,
+      vault,
+      counter: counterPda,
+      vaultTokenAccount: ataFor(vault, usdcMint),
+      mint: usdcMint,
+      beneficiaryUsdcAta,
+      beneficiary: beneficiary.publicKey,
+      treasuryUsdcAta: ataFor(treasury.publicKey, usdcMint),
+      payer: beneficiary.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
 
     before(async () => {
-      // Create a vault with unlock time in the past (for immediate release)
-      const now = Math.floor(Date.now() / 1000);
-      releasableUnlockUnix = new anchor.BN(now - 100); // Already unlocked
+      // 2s check-in period with a 1s notification window and grace period
+      releasableVaultPda = await createFundedVault(creator, 2, { checkinSecs: 2, amount: 50_000_000 });
+      beneficiaryUsdcAta = ataFor(beneficiary.publicKey, usdcMint);
+    });
 
-      [releasableVaultPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("vault"),
-          creator.publicKey.toBuffer(),
-          Buffer.from(new Uint8Array(new BigUint64Array([BigInt(2)]).buffer).slice(0, 8)),
-        ],
-        programId
-      );
+    it("fails before the grace period ends", async () => {
+      // The first vault's check-in period is still running
+      try {
+        await program.methods.release().accounts(releaseAccounts(vaultPda)).signers([beneficiary]).rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidUnlockTime");
+      }
+    });
 
-      [releasableVaultTokenAccount] = PublicKey.findProgramAddressSync(
-        [
-          releasableVaultPda.toBuffer(),
-          TOKEN_PROGRAM_ID.toBuffer(),
-          usdcMint.toBuffer(),
-        ],
-        ASSOCIATED_TOKEN_PROGRAM_ID
-      );
+    it("releases funds successfully after the grace period", async () => {
+      await sleep(4000);
+      const before = await balanceOf(beneficiaryUsdcAta);
 
-      // Create vault
-      await program.methods
-        .createVault(beneficiary.publicKey, releasableUnlockUnix, Array.from(Buffer.alloc(32, 2)))
-        .accounts({
-          config: configPda,
-          counter: counterPda,
-          vault: releasableVaultPda,
-          vaultTokenAccount: releasableVaultTokenAccount,
-          usdcMint,
-          creator: creator.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-        })
-        .signers([creator])
-        .rpc();
+      await program.methods.release().accounts(releaseAccounts(releasableVaultPda)).signers([beneficiary]).rpc();
 
-      // Deposit funds
-      await program.methods
-        .depositUsdc(new anchor.BN(50_000_000))
+      // Verify vault marked as released
+      const vault = await (program.account as any).vault.fetch(releasableVaultPda);
+      assert.equal(vault.released, true);
+
+      // Verify beneficiary received funds (no release fee at launch pricing)
+      assert.equal((await balanceOf(beneficiaryUsdcAta)) - before, BigInt(50_000_000));
+
+      // Verify vault token account is empty
+      const vaultAccount = await getAccount(provider.connection, ataFor(releasableVaultPda, usdcMint));
+      assert.equal(vaultAccount.amount.toString(), "0");
+    });
+
+    it("fails to release twice", async () => {
+      try {
+        await program.methods.release().accounts(releaseAccounts(releasableVaultPda)).signers([beneficiary]).rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "AlreadyReleased");
+      }
+    });
+  });
+
+  describe("close_vault", () => {
+    const releasedVaultPda = () => vaultPdaFor(creator.publicKey, 2);
+
+    const closeVault = (vault: PublicKey) =>
+      program.methods
+        .closeVault()
         .accounts({
-          config: configPda,
-          vault: releasableVaultPda,
-          counter: counterPda,
-          vaultTokenAccount: releasableVaultTokenAccount,
-          usdcMint,
-          creatorUsdcAta,
+          vault,
+          vaultTokenAccount: ataFor(vault, usdcMint),
           creator: creator.publicKey,
+          signer: creator.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([creator])
         .rpc();
 
-      // Derive beneficiary ATA
-      [beneficiaryUsdcAta] = PublicKey.findProgramAddressSync(
-        [
-          beneficiary.publicKey.toBuffer(),
-          TOKEN_PROGRAM_ID.toBuffer(),
-          usdcMint.toBuffer(),
-        ],
-        ASSOCIATED_TOKEN_PROGRAM_ID
-      );
-    });
-
-    it("fails before unlock time", async () => {
-      // Try to release the first vault (still locked)
-      const [beneficiaryAta] = PublicKey.findProgramAddressSync(
-        [
-          beneficiary.publicKey.toBuffer(),
-          TOKEN_PROGRAM_ID.toBuffer(),
-          usdcMint.toBuffer(),
-        ],
-        ASSOCIATED_TOKEN_PROGRAM_ID
-      );
+    it("fails to close unreleased vault", async () => {
+      try {
+        await closeVault(vaultPda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotReleased");
+      }
+    });
 
+    it("fails when the rent goes to anyone but the creator", async () => {
       try {
         await program.methods
-          .release()
+          .closeVault()
           .accounts({
-            vault: vaultPda,
-            counter: counterPda,
-            vaultTokenAccount,
-            usdcMint,
-            beneficiaryUsdcAta: beneficiaryAta,
-            beneficiary: beneficiary.publicKey,
+            vault: releasedVaultPda(),
+            vaultTokenAccount: ataFor(releasedVaultPda(), usdcMint),
+            creator: beneficiary.publicKey, // Wrong rent recipient
+            signer: beneficiary.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
-            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
           })
           .signers([beneficiary])
           .rpc();
         assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "InvalidUnlockTime");
+        assert.include(err.message, "ConstraintAddress");
       }
     });
 
-    it("releases funds successfully after unlock", async () => {
-      const tx = await program.methods
+    it("closes vault and reclaims rent", async () => {
+      // Get creator's SOL balance before
+      const balanceBefore = await provider.connection.getBalance(creator.publicKey);
+
+      await closeVault(releasedVaultPda());
+
+      // Verify vault account is closed
+      try {
+        await (program.account as any).vault.fetch(releasedVaultPda());
+        assert.fail("Vault should be closed");
+      } catch (err) {
+        assert.include(err.message, "Account does not exist");
+      }
+
+      // Verify token account is closed
+      try {
+        await getAccount(provider.connection, ataFor(releasedVaultPda(), usdcMint));
+        assert.fail("Token account should be closed");
+      } catch (err) {
+        assert.include(err.message, "could not find account");
+      }
+
+      // Verify creator received rent (balance should increase)
+      const balanceAfter = await provider.connection.getBalance(creator.publicKey);
+      assert.isTrue(balanceAfter > balanceBefore);
+    });
+  });
+
+  describe("pause flags", () => {
+    let pauseCreator: Keypair;
+    let pauseVaultPda: PublicKey;
+    let pauseVaultTokenAccount: PublicKey;
+    let pauseCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+
+    const depositToPauseVault = (amount: anchor.BN) =>
+      program.methods
+        .depositUsdc(amount)
+        .accounts({
+          config: configPda,
+          vault: pauseVaultPda,
+          counter: counterPdaFor(pauseCreator.publicKey),
+          vaultTokenAccount: pauseVaultTokenAccount,
+          mint: usdcMint,
+          creatorUsdcAta: pauseCreatorUsdcAta,
+          creator: pauseCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([pauseCreator])
+        .rpc();
+
+    const checkInPauseVault = () =>
+      program.methods
+        .checkIn()
+        .accounts({
+          config: configPda,
+          vault: pauseVaultPda,
+          counter: counterPdaFor(pauseCreator.publicKey),
+          signer: pauseCreator.publicKey,
+        })
+        .signers([pauseCreator])
+        .rpc();
+
+    const releasePauseVault = () =>
+      program.methods
         .release()
         .accounts({
-          vault: releasableVaultPda,
-          counter: counterPda,
-          vaultTokenAccount: releasableVaultTokenAccount,
-          usdcMint,
-          beneficiaryUsdcAta,
+          config: configPda,
+          vault: pauseVaultPda,
+          counter: counterPdaFor(pauseCreator.publicKey),
+          vaultTokenAccount: pauseVaultTokenAccount,
+          mint: usdcMint,
+          beneficiaryUsdcAta: ataFor(beneficiary.publicKey, usdcMint),
           beneficiary: beneficiary.publicKey,
+          treasuryUsdcAta,
+          payer: pauseCreator.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
         })
-        .signers([beneficiary])
+        .signers([pauseCreator])
         .rpc();
 
-      console.log("  ✓ Funds released:", tx);
+    before(async () => {
+      pauseCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(pauseCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
 
-      // Verify vault marked as released
-      const vault = await (program.account as any).vault.fetch(releasableVaultPda);
-      assert.equal(vault.released, true);
+      pauseCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        pauseCreator,
+        usdcMint,
+        pauseCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, pauseCreatorUsdcAta, admin, 100_000_000);
 
-      // Verify beneficiary received funds
-      const beneficiaryAccount = await getAccount(provider.connection, beneficiaryUsdcAta);
-      assert.equal(beneficiaryAccount.amount.toString(), "50000000");
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
 
-      // Verify vault token account is empty
-      const vaultAccount = await getAccount(provider.connection, releasableVaultTokenAccount);
-      assert.equal(vaultAccount.amount.toString(), "0");
+      pauseVaultPda = vaultPdaFor(pauseCreator.publicKey, 1);
+      pauseVaultTokenAccount = ataFor(pauseVaultPda, usdcMint);
+
+      // 4s check-in period, 3s notification window, 1s grace period
+      await program.methods
+        .createVault(beneficiary.publicKey, 4, Array.from(Buffer.alloc(32, 7)), 3, 1, { base: {} }, new anchor.BN(1_000_000), null)
+        .accounts({
+          config: configPda,
+          counter: counterPdaFor(pauseCreator.publicKey),
+          vault: pauseVaultPda,
+          vaultTokenAccount: pauseVaultTokenAccount,
+          mint: usdcMint,
+          creatorUsdcAta: pauseCreatorUsdcAta,
+          treasuryUsdcAta,
+          creator: pauseCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([pauseCreator])
+        .rpc();
+
+      await depositToPauseVault(new anchor.BN(10_000_000));
     });
 
-    it("fails to release twice", async () => {
+    after(async () => {
+      await setPauseFlags(0);
+    });
+
+    it("blocks deposits during a deposit freeze", async () => {
+      await setPauseFlags(PAUSE_DEPOSIT_FREEZE);
+
+      try {
+        await depositToPauseVault(new anchor.BN(1_000_000));
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      }
+    });
+
+    it("keeps check-ins working during a deposit freeze", async () => {
+      // Wait for the notification window to open
+      await sleep(2000);
+
+      await checkInPauseVault();
+
+      const vault = await (program.account as any).vault.fetch(pauseVaultPda);
+      assert.isTrue(vault.lastCheckinUnix.toNumber() > 0);
+    });
+
+    it("blocks cancellation when cancels are paused", async () => {
+      await setPauseFlags(PAUSE_DEPOSIT_FREEZE | PAUSE_CANCEL);
+
       try {
         await program.methods
-          .release()
+          .cancelVault()
           .accounts({
-            vault: releasableVaultPda,
-            counter: counterPda,
-            vaultTokenAccount: releasableVaultTokenAccount,
-            usdcMint,
-            beneficiaryUsdcAta,
-            beneficiary: beneficiary.publicKey,
+            config: configPda,
+            vault: pauseVaultPda,
+            counter: counterPdaFor(pauseCreator.publicKey),
+            vaultTokenAccount: pauseVaultTokenAccount,
+            mint: usdcMint,
+            creatorUsdcAta: pauseCreatorUsdcAta,
+            treasuryUsdcAta,
+            creator: pauseCreator.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
           })
-          .signers([beneficiary])
+          .signers([pauseCreator])
           .rpc();
         assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "AlreadyReleased");
+        assert.include(err.message, "Paused");
       }
     });
+
+    it("blocks check-ins and releases when both are paused", async () => {
+      await setPauseFlags(PAUSE_CHECK_IN | PAUSE_RELEASE);
+
+      try {
+        await checkInPauseVault();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      }
+
+      try {
+        await releasePauseVault();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      }
+    });
+
+    it("releases once the protocol is unpaused", async () => {
+      await setPauseFlags(0);
+
+      // Wait past unlock + grace period
+      await sleep(6000);
+
+      await releasePauseVault();
+
+      const vault = await (program.account as any).vault.fetch(pauseVaultPda);
+      assert.equal(vault.released, true);
+    });
   });
 
-  describe("close_vault", () => {
-    it("closes vault and reclaims rent", async () => {
-      // Get creator's SOL balance before
-      const balanceBefore = await provider.connection.getBalance(creator.publicKey);
+  describe("admin handover", () => {
+    let newAdmin: Keypair;
 
-      const tx = await program.methods
-        .closeVault()
+    const proposeAdmin = (signer: Keypair, candidate: PublicKey) =>
+      program.methods
+        .proposeAdmin(candidate)
         .accounts({
-          vault: releasableVaultPda,
-          vaultTokenAccount: releasableVaultTokenAccount,
-          creator: creator.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
+          config: configPda,
+          admin: signer.publicKey,
         })
-        .signers([creator])
+        .signers([signer])
+        .rpc();
+
+    const acceptAdmin = (signer: Keypair) =>
+      program.methods
+        .acceptAdmin()
+        .accounts({
+          config: configPda,
+          newAdmin: signer.publicKey,
+        })
+        .signers([signer])
         .rpc();
 
-      console.log("  ✓ Vault closed:", tx);
+    before(async () => {
+      newAdmin = Keypair.generate();
+      await provider.connection.requestAirdrop(newAdmin.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+    });
 
-      // Verify vault account is closed
+    it("fails when non-admin proposes", async () => {
       try {
-        await (program.account as any).vault.fetch(releasableVaultPda);
-        assert.fail("Vault should be closed");
+        await proposeAdmin(creator, creator.publicKey);
+        assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "Account does not exist");
+        assert.include(err.message, "has_one");
       }
+    });
 
-      // Verify token account is closed
+    it("rejects proposing the current admin", async () => {
       try {
-        await getAccount(provider.connection, releasableVaultTokenAccount);
-        assert.fail("Token account should be closed");
+        await proposeAdmin(admin, admin.publicKey);
+        assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "could not find account");
+        assert.include(err.message, "InvalidAdmin");
       }
+    });
 
-      // Verify creator received rent (balance should increase)
-      const balanceAfter = await provider.connection.getBalance(creator.publicKey);
-      assert.isTrue(balanceAfter > balanceBefore - 10000); // Account for tx fee
+    it("proposes and cancels a handover", async () => {
+      await proposeAdmin(admin, newAdmin.publicKey);
+
+      let config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.pendingAdmin.toBase58(), newAdmin.publicKey.toBase58());
+
+      await program.methods
+        .cancelAdminProposal()
+        .accounts({
+          config: configPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      config = await (program.account as any).config.fetch(configPda);
+      assert.isNull(config.pendingAdmin);
+
+      try {
+        await acceptAdmin(newAdmin);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotPendingAdmin");
+      }
     });
 
-    it("fails to close unreleased vault", async () => {
+    it("fails when someone other than the pending admin accepts", async () => {
+      await proposeAdmin(admin, newAdmin.publicKey);
+
       try {
-        await program.methods
-          .closeVault()
-          .accounts({
-            vault: vaultPda,
-            vaultTokenAccount,
-            creator: creator.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
-          })
-          .signers([creator])
-          .rpc();
+        await acceptAdmin(creator);
         assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "NotReleased");
+        assert.include(err.message, "NotPendingAdmin");
       }
     });
 
-    it("fails when non-creator tries to close", async () => {
-      // Create and release another vault first
-      const [vault3Pda] = PublicKey.findProgramAddressSync(
+    it("transfers admin once the pending admin accepts", async () => {
+      await acceptAdmin(newAdmin);
+
+      let config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.admin.toBase58(), newAdmin.publicKey.toBase58());
+      assert.isNull(config.pendingAdmin);
+
+      // Hand admin back for the remaining tests
+      await proposeAdmin(newAdmin, admin.publicKey);
+      await acceptAdmin(admin);
+
+      config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.admin.toBase58(), admin.publicKey.toBase58());
+    });
+  });
+
+  describe("admin council", () => {
+    let members: Keypair[];
+    let outsider: Keypair;
+    let councilPda: PublicKey;
+
+    const proposalPdaFor = (proposalId: number) =>
+      PublicKey.findProgramAddressSync(
         [
-          Buffer.from("vault"),
-          creator.publicKey.toBuffer(),
-          Buffer.from(new Uint8Array(new BigUint64Array([BigInt(3)]).buffer).slice(0, 8)),
+          Buffer.from("proposal"),
+          Buffer.from(new Uint8Array(new BigUint64Array([BigInt(proposalId)]).buffer).slice(0, 8)),
         ],
         programId
-      );
+      )[0];
+
+    // Wrap a keepr_vault instruction into a council proposal
+    const propose = async (proposer: Keypair, ix: anchor.web3.TransactionInstruction) => {
+      const council = await (program.account as any).adminCouncil.fetch(councilPda);
+      const proposalPda = proposalPdaFor(council.proposalCount.toNumber());
+
+      await program.methods
+        .createProposal(
+          ix.data,
+          ix.keys.map(key => ({ pubkey: key.pubkey, isSigner: key.isSigner, isWritable: key.isWritable }))
+        )
+        .accounts({
+          council: councilPda,
+          proposal: proposalPda,
+          proposer: proposer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([proposer])
+        .rpc();
 
+      return { proposalPda, keys: ix.keys };
+    };
+
+    const approve = (member: Keypair, proposalPda: PublicKey) =>
+      program.methods
+        .approveProposal()
+        .accounts({
+          council: councilPda,
+          proposal: proposalPda,
+          member: member.publicKey,
+        })
+        .signers([member])
+        .rpc();
+
+    const execute = (proposalPda: PublicKey, keys: anchor.web3.AccountMeta[]) =>
+      program.methods
+        .executeProposal()
+        .accounts({
+          council: councilPda,
+          proposal: proposalPda,
+          executor: members[0].publicKey,
+          keeprProgram: programId,
+        })
+        .remainingAccounts(keys.map(key => ({ ...key, isSigner: false })))
+        .signers([members[0]])
+        .rpc();
+
+    before(async () => {
+      members = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+      outsider = Keypair.generate();
+      for (const kp of [...members, outsider]) {
+        await provider.connection.requestAirdrop(kp.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      }
+      await sleep(1000);
+
+      [councilPda] = PublicKey.findProgramAddressSync([Buffer.from("council")], programId);
+    });
+
+    it("rejects a threshold above the member count", async () => {
       try {
         await program.methods
-          .closeVault()
+          .initCouncil(members.map(m => m.publicKey), 4)
           .accounts({
-            vault: vault3Pda,
-            vaultTokenAccount,
-            creator: beneficiary.publicKey, // Wrong signer
-            tokenProgram: TOKEN_PROGRAM_ID,
+            config: configPda,
+            council: councilPda,
+            admin: admin.publicKey,
+            systemProgram: SystemProgram.programId,
           })
-          .signers([beneficiary])
+          .signers([admin])
           .rpc();
         assert.fail("Should have failed");
       } catch (err) {
-        assert.include(err.message, "has_one");
+        assert.include(err.message, "InvalidCouncil");
+      }
+    });
+
+    it("initializes a 2-of-3 council", async () => {
+      await program.methods
+        .initCouncil(members.map(m => m.publicKey), 2)
+        .accounts({
+          config: configPda,
+          council: councilPda,
+          admin: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const council = await (program.account as any).adminCouncil.fetch(councilPda);
+      assert.equal(council.members.length, 3);
+      assert.equal(council.threshold, 2);
+    });
+
+    it("hands the config over to the council", async () => {
+      await program.methods
+        .proposeAdmin(councilPda)
+        .accounts({ config: configPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      const acceptIx = await program.methods
+        .acceptAdmin()
+        .accounts({ config: configPda, newAdmin: councilPda })
+        .instruction();
+      const { proposalPda, keys } = await propose(members[0], acceptIx);
+
+      // One approval (the proposer's) is below the threshold
+      try {
+        await execute(proposalPda, keys);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ProposalThresholdNotMet");
+      }
+
+      try {
+        await approve(outsider, proposalPda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotCouncilMember");
+      }
+
+      await approve(members[1], proposalPda);
+      await execute(proposalPda, keys);
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.admin.toBase58(), councilPda.toBase58());
+
+      const proposal = await (program.account as any).councilProposal.fetch(proposalPda);
+      assert.equal(proposal.executed, true);
+    });
+
+    it("blocks the old admin key from privileged instructions", async () => {
+      try {
+        await program.methods
+          .updateConfig(0)
+          .accounts({ config: configPda, authority: admin.publicKey, roleAssignment: null })
+          .signers([admin])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "MissingRole");
+      }
+    });
+
+    it("applies config changes once the threshold is met", async () => {
+      const updateIx = await program.methods
+        .updateConfig(PAUSE_DEPOSIT_FREEZE)
+        .accounts({ config: configPda, authority: councilPda, roleAssignment: null })
+        .instruction();
+      const { proposalPda, keys } = await propose(members[1], updateIx);
+
+      try {
+        await approve(members[1], proposalPda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ProposalAlreadyApproved");
+      }
+
+      await approve(members[2], proposalPda);
+      await execute(proposalPda, keys);
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.pauseFlags, PAUSE_DEPOSIT_FREEZE);
+
+      try {
+        await execute(proposalPda, keys);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ProposalAlreadyExecuted");
+      }
+    });
+
+    it("hands admin back to a single key", async () => {
+      const proposeIx = await program.methods
+        .proposeAdmin(admin.publicKey)
+        .accounts({ config: configPda, admin: councilPda })
+        .instruction();
+      const { proposalPda, keys } = await propose(members[2], proposeIx);
+      await approve(members[0], proposalPda);
+      await execute(proposalPda, keys);
+
+      await program.methods
+        .acceptAdmin()
+        .accounts({ config: configPda, newAdmin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.admin.toBase58(), admin.publicKey.toBase58());
+
+      await setPauseFlags(0);
+    });
+  });
+
+  describe("config timelock", () => {
+    const newCap = new anchor.BN(2_000_000_000); // 2000 USDC
+
+    it("rejects an empty change", async () => {
+      try {
+        await queueConfigChange(null, null, null);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "EmptyConfigChange");
+      }
+    });
+
+    it("fails when non-admin queues a change", async () => {
+      try {
+        await program.methods
+          .queueConfigChange(null, newCap, null, null, null, null)
+          .accounts({
+            config: configPda,
+            configChange: configChangePda,
+            admin: creator.publicKey,
+            payer: creator.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "has_one");
+      }
+    });
+
+    it("sets a 3 second timelock", async () => {
+      await queueConfigChange(null, null, 3);
+      await executeConfigChange();
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.configTimelockSeconds, 3);
+    });
+
+    it("refuses to execute before the timelock elapses", async () => {
+      await queueConfigChange(null, newCap, null);
+
+      const change = await (program.account as any).pendingConfigChange.fetch(configChangePda);
+      assert.equal(change.maxLockPerVault.toString(), newCap.toString());
+      assert.equal(change.eta.sub(change.queuedAt).toNumber(), 3);
+
+      try {
+        await executeConfigChange();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ConfigChangeNotReady");
+      }
+    });
+
+    it("cancels a queued change", async () => {
+      await program.methods
+        .cancelConfigChange()
+        .accounts({
+          config: configPda,
+          configChange: configChangePda,
+          admin: admin.publicKey,
+          payer: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      const info = await provider.connection.getAccountInfo(configChangePda);
+      assert.isNull(info);
+    });
+
+    it("applies a queued change after the timelock", async () => {
+      await queueConfigChange(null, newCap, null);
+      await sleep(4000);
+      await executeConfigChange();
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.maxLockPerVault.toString(), newCap.toString());
+
+      // Restore the zero timelock for the remaining tests
+      await queueConfigChange(null, MAX_LOCK_PER_VAULT, 0);
+      await sleep(4000);
+      await executeConfigChange();
+    });
+  });
+
+  describe("treasury rotation", () => {
+    let treasuryCreator: Keypair;
+    let newTreasury: Keypair;
+    let treasuryCreatorUsdcAta: PublicKey;
+    let oldTreasuryUsdcAta: PublicKey;
+    let newTreasuryUsdcAta: PublicKey;
+    let treasuryVaultPda: PublicKey;
+
+    const cancelTreasuryVault = (treasuryUsdcAta: PublicKey) =>
+      program.methods
+        .cancelVault()
+        .accounts({
+          config: configPda,
+          vault: treasuryVaultPda,
+          counter: counterPdaFor(treasuryCreator.publicKey),
+          vaultTokenAccount: ataFor(treasuryVaultPda, usdcMint),
+          mint: usdcMint,
+          creatorUsdcAta: treasuryCreatorUsdcAta,
+          treasuryUsdcAta,
+          creator: treasuryCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([treasuryCreator])
+        .rpc();
+
+    before(async () => {
+      treasuryCreator = Keypair.generate();
+      newTreasury = Keypair.generate();
+      await provider.connection.requestAirdrop(treasuryCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      treasuryCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        treasuryCreator,
+        usdcMint,
+        treasuryCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, treasuryCreatorUsdcAta, admin, 10_000_000);
+
+      oldTreasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+
+      treasuryVaultPda = vaultPdaFor(treasuryCreator.publicKey, 1);
+      await program.methods
+        .createVault(beneficiary.publicKey, 3600, Array.from(Buffer.alloc(32, 5)), 60, 60, { base: {} }, new anchor.BN(1_000_000), null)
+        .accounts({
+          config: configPda,
+          counter: counterPdaFor(treasuryCreator.publicKey),
+          vault: treasuryVaultPda,
+          vaultTokenAccount: ataFor(treasuryVaultPda, usdcMint),
+          mint: usdcMint,
+          creatorUsdcAta: treasuryCreatorUsdcAta,
+          treasuryUsdcAta: oldTreasuryUsdcAta,
+          creator: treasuryCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([treasuryCreator])
+        .rpc();
+    });
+
+    it("requires the new treasury's USDC ATA", async () => {
+      await queueConfigChange(null, null, null, newTreasury.publicKey);
+
+      try {
+        await executeConfigChange();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "MissingTreasuryAta");
+      }
+
+      // The ATA address is checked too, not just the presence of a token account
+      try {
+        await executeConfigChange(oldTreasuryUsdcAta);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "MissingTreasuryAta");
+      }
+    });
+
+    it("rotates the treasury", async () => {
+      newTreasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, newTreasury.publicKey)
+      ).address;
+
+      await executeConfigChange(newTreasuryUsdcAta);
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.treasury.toBase58(), newTreasury.publicKey.toBase58());
+    });
+
+    it("rejects the old treasury ATA in cancel_vault", async () => {
+      try {
+        await cancelTreasuryVault(oldTreasuryUsdcAta);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ConstraintTokenOwner");
+      }
+    });
+
+    it("sends closing fees to the new treasury", async () => {
+      await cancelTreasuryVault(newTreasuryUsdcAta);
+
+      const treasuryAccount = await getAccount(provider.connection, newTreasuryUsdcAta);
+      assert.equal(treasuryAccount.amount.toString(), "1000000"); // Base tier closing fee
+
+      // Restore the original treasury for the remaining tests
+      await queueConfigChange(null, null, null, treasury.publicKey);
+      await executeConfigChange(oldTreasuryUsdcAta);
+    });
+  });
+
+
+  describe("fee schedule", () => {
+    let feeCreator: Keypair;
+    let feeCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+
+    const tierFees = (creation: number, closing: number, release: number, releaseBps: number | null = null) => ({
+      creationFee: new anchor.BN(creation),
+      closingFee: new anchor.BN(closing),
+      releaseFee: new anchor.BN(release),
+      releaseFeeBps: releaseBps,
+    });
+
+    const defaultSchedule = {
+      base: tierFees(1_000_000, 1_000_000, 0),
+      plus: tierFees(8_000_000, 5_000_000, 0),
+      premium: tierFees(20_000_000, 10_000_000, 0),
+      lifetime: tierFees(100_000_000, 0, 0),
+      releaseFeeBps: 0,
+    };
+
+    const updateFeeSchedule = (schedule: any, signer: Keypair = admin) =>
+      program.methods
+        .updateFeeSchedule(schedule)
+        .accounts({ config: configPda, authority: signer.publicKey, roleAssignment: null })
+        .signers([signer])
+        .rpc();
+
+    const releaseFeeVault = (vaultPda: PublicKey) =>
+      program.methods
+        .release()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(feeCreator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          beneficiaryUsdcAta: ataFor(beneficiary.publicKey, usdcMint),
+          beneficiary: beneficiary.publicKey,
+          treasuryUsdcAta,
+          payer: feeCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([feeCreator])
+        .rpc();
+
+    const treasuryBalance = () => balanceOf(treasuryUsdcAta);
+
+    before(async () => {
+      feeCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(feeCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      feeCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        feeCreator,
+        usdcMint,
+        feeCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, feeCreatorUsdcAta, admin, 100_000_000);
+
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+    });
+
+    after(async () => {
+      await updateFeeSchedule(defaultSchedule);
+    });
+
+    it("starts with the launch pricing", async () => {
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.feeSchedule.base.closingFee.toNumber(), 1_000_000);
+      assert.equal(config.feeSchedule.plus.creationFee.toNumber(), 8_000_000);
+      assert.equal(config.feeSchedule.lifetime.closingFee.toNumber(), 0);
+      assert.equal(config.feeSchedule.premium.releaseFee.toNumber(), 0);
+    });
+
+    it("fails when non-admin updates the schedule", async () => {
+      try {
+        await updateFeeSchedule(defaultSchedule, creator);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "MissingRole");
+      }
+    });
+
+    it("charges the scheduled closing fee on cancel", async () => {
+      await updateFeeSchedule({ ...defaultSchedule, base: tierFees(1_000_000, 3_000_000, 300_000) });
+
+      const vaultPda = await createFundedVault(feeCreator, 1, { notificationSecs: 60, graceSecs: 60, amount: 10_000_000 });
+      const before = await treasuryBalance();
+
+      await program.methods
+        .cancelVault()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(feeCreator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          creatorUsdcAta: feeCreatorUsdcAta,
+          treasuryUsdcAta,
+          creator: feeCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([feeCreator])
+        .rpc();
+
+      assert.equal((await treasuryBalance()) - before, BigInt(3_000_000));
+    });
+
+    it("deducts the scheduled release fee from the payout", async () => {
+      // 2s check-in period, 1s notification window, 1s grace period
+      const vaultPda = await createFundedVault(feeCreator, 2, { checkinSecs: 2, amount: 10_000_000 });
+      const beneficiaryUsdcAta = ataFor(beneficiary.publicKey, usdcMint);
+      const treasuryBefore = await treasuryBalance();
+      const beneficiaryBefore = await balanceOf(beneficiaryUsdcAta);
+
+      await sleep(4000);
+
+      await releaseFeeVault(vaultPda);
+
+      assert.equal((await balanceOf(beneficiaryUsdcAta)) - beneficiaryBefore, BigInt(9_700_000));
+      assert.equal((await treasuryBalance()) - treasuryBefore, BigInt(300_000));
+    });
+
+    it("clamps a flat release fee to the protocol maximum", async () => {
+      // 2 USDC flat on a 10 USDC payout would be 20%; the cap is 5%
+      await updateFeeSchedule({ ...defaultSchedule, base: tierFees(1_000_000, 1_000_000, 2_000_000) });
+
+      const vaultPda = await createFundedVault(feeCreator, 3, { checkinSecs: 2, amount: 10_000_000 });
+      const treasuryBefore = await treasuryBalance();
+
+      await sleep(4000);
+
+      await releaseFeeVault(vaultPda);
+
+      assert.equal((await treasuryBalance()) - treasuryBefore, BigInt(500_000));
+    });
+
+    it("rejects release fees above the protocol maximum", async () => {
+      try {
+        await updateFeeSchedule({ ...defaultSchedule, releaseFeeBps: 501 });
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidReleaseFee");
+      }
+
+      try {
+        await updateFeeSchedule({ ...defaultSchedule, plus: tierFees(8_000_000, 5_000_000, 0, 10_000) });
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidReleaseFee");
+      }
+    });
+
+    it("charges the tier's bps override and reports gross, fee and net", async () => {
+      // 1% protocol-wide, 2.5% for Base
+      await updateFeeSchedule({ ...defaultSchedule, base: tierFees(1_000_000, 1_000_000, 0, 250), releaseFeeBps: 100 });
+
+      const vaultPda = await createFundedVault(feeCreator, 4, { checkinSecs: 2, amount: 10_000_000 });
+      const treasuryBefore = await treasuryBalance();
+
+      await sleep(4000);
+
+      let released: any = null;
+      const listener = program.addEventListener("vaultReleased", (event) => {
+        released = event;
+      });
+      await releaseFeeVault(vaultPda);
+      await sleep(1000);
+      await program.removeEventListener(listener);
+
+      assert.equal((await treasuryBalance()) - treasuryBefore, BigInt(250_000));
+      assert.isNotNull(released);
+      assert.equal(released.grossAmount.toNumber(), 10_000_000);
+      assert.equal(released.fee.toNumber(), 250_000);
+      assert.equal(released.netAmount.toNumber(), 9_750_000);
+    });
+  });
+
+
+  describe("creation fee", () => {
+    let feeCreator: Keypair;
+    let feeCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+
+    const createTierVault = (tier: any, creationFeePaid: number) =>
+      program.methods
+        .createVault(beneficiary.publicKey, 3600, Array.from(Buffer.alloc(32, 11)), 60, 60, tier, new anchor.BN(creationFeePaid), null)
+        .accounts({
+          config: configPda,
+          counter: counterPdaFor(feeCreator.publicKey),
+          vault: vaultPdaFor(feeCreator.publicKey, 1),
+          vaultTokenAccount: ataFor(vaultPdaFor(feeCreator.publicKey, 1), usdcMint),
+          mint: usdcMint,
+          creatorUsdcAta: feeCreatorUsdcAta,
+          treasuryUsdcAta,
+          creator: feeCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([feeCreator])
+        .rpc();
+
+    before(async () => {
+      feeCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(feeCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      feeCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        feeCreator,
+        usdcMint,
+        feeCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, feeCreatorUsdcAta, admin, 150_000_000);
+
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+    });
+
+    it("rejects a fee that does not match the tier", async () => {
+      try {
+        // Claiming a Lifetime vault for the Base price
+        await createTierVault({ lifetime: {} }, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "CreationFeeMismatch");
+      }
+    });
+
+    it("collects the tier's creation fee into the treasury", async () => {
+      const treasuryBefore = BigInt((await getAccount(provider.connection, treasuryUsdcAta)).amount.toString());
+
+      await createTierVault({ lifetime: {} }, 100_000_000);
+
+      const treasuryAfter = BigInt((await getAccount(provider.connection, treasuryUsdcAta)).amount.toString());
+      assert.equal(treasuryAfter - treasuryBefore, BigInt(100_000_000));
+
+      const creatorAccount = await getAccount(provider.connection, feeCreatorUsdcAta);
+      assert.equal(creatorAccount.amount.toString(), "50000000");
+
+      const vault = await (program.account as any).vault.fetch(vaultPdaFor(feeCreator.publicKey, 1));
+      assert.equal(vault.creationFeePaid.toNumber(), 100_000_000);
+      assert.equal(vault.schemaVersion, 2);
+    });
+  });
+
+
+  describe("roles", () => {
+    const ROLE_PAUSER = { pauser: {} };
+    const ROLE_FEE_MANAGER = { feeManager: {} };
+    let pauser: Keypair;
+    let feeManager: Keypair;
+    let pauserRolePda: PublicKey;
+    let feeManagerRolePda: PublicKey;
+
+    const rolePdaFor = (roleIndex: number, holder: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("role"), Buffer.from([roleIndex]), holder.toBuffer()],
+        programId
+      )[0];
+
+    const grantRole = (role: any, roleIndex: number, holder: PublicKey, signer: Keypair = admin) =>
+      program.methods
+        .grantRole(role, holder)
+        .accounts({
+          config: configPda,
+          roleAssignment: rolePdaFor(roleIndex, holder),
+          admin: signer.publicKey,
+          payer: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    const pauseAs = (flags: number, signer: Keypair, roleAssignment: PublicKey | null) =>
+      program.methods
+        .updateConfig(flags)
+        .accounts({ config: configPda, authority: signer.publicKey, roleAssignment })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      pauser = Keypair.generate();
+      feeManager = Keypair.generate();
+      await provider.connection.requestAirdrop(pauser.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.requestAirdrop(feeManager.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      pauserRolePda = rolePdaFor(0, pauser.publicKey);
+      feeManagerRolePda = rolePdaFor(1, feeManager.publicKey);
+    });
+
+    after(async () => {
+      await setPauseFlags(0);
+    });
+
+    it("fails when non-admin grants a role", async () => {
+      try {
+        await grantRole(ROLE_PAUSER, 0, pauser.publicKey, pauser);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "has_one");
+      }
+    });
+
+    it("grants the pauser and fee manager roles", async () => {
+      await grantRole(ROLE_PAUSER, 0, pauser.publicKey);
+      await grantRole(ROLE_FEE_MANAGER, 1, feeManager.publicKey);
+
+      const assignment = await (program.account as any).roleAssignment.fetch(pauserRolePda);
+      assert.deepEqual(assignment.role, ROLE_PAUSER);
+      assert.equal(assignment.holder.toBase58(), pauser.publicKey.toBase58());
+    });
+
+    it("lets the pauser pause but not unpause", async () => {
+      await pauseAs(PAUSE_CREATE, pauser, pauserRolePda);
+      await pauseAs(PAUSE_DEPOSIT_FREEZE, pauser, pauserRolePda);
+
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.pauseFlags, PAUSE_DEPOSIT_FREEZE);
+
+      try {
+        await pauseAs(PAUSE_CREATE, pauser, pauserRolePda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "PauserCannotUnpause");
+      }
+
+      await setPauseFlags(0);
+    });
+
+    it("rejects a role assignment for a different role", async () => {
+      try {
+        await pauseAs(PAUSE_CREATE, feeManager, feeManagerRolePda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "MissingRole");
+      }
+    });
+
+    it("lets the fee manager update the fee schedule", async () => {
+      const config = await (program.account as any).config.fetch(configPda);
+
+      await program.methods
+        .updateFeeSchedule({ ...config.feeSchedule, releaseFeeBps: 10 })
+        .accounts({ config: configPda, authority: feeManager.publicKey, roleAssignment: feeManagerRolePda })
+        .signers([feeManager])
+        .rpc();
+
+      const updated = await (program.account as any).config.fetch(configPda);
+      assert.equal(updated.feeSchedule.releaseFeeBps, 10);
+
+      await program.methods
+        .updateFeeSchedule(config.feeSchedule)
+        .accounts({ config: configPda, authority: admin.publicKey, roleAssignment: null })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("revokes the pauser role", async () => {
+      await program.methods
+        .revokeRole()
+        .accounts({
+          config: configPda,
+          roleAssignment: pauserRolePda,
+          admin: admin.publicKey,
+          payer: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      try {
+        await pauseAs(PAUSE_CREATE, pauser, pauserRolePda);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "AccountNotInitialized");
+      }
+    });
+  });
+
+
+  describe("config migration", () => {
+    it("initializes config at the current version", async () => {
+      const config = await (program.account as any).config.fetch(configPda);
+      assert.equal(config.version, 1);
+      assert.equal(config.beneficiaryCooldownSeconds, 604_800);
+      assert.equal(config.minVaultBalance.toNumber(), 0);
+    });
+
+    it("refuses to migrate a config that is already current", async () => {
+      try {
+        await program.methods
+          .migrateConfig()
+          .accounts({
+            config: configPda,
+            admin: admin.publicKey,
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ConfigUpToDate");
+      }
+    });
+  });
+
+
+  describe("vault migration", () => {
+    // Loaded from tests/fixtures/legacy-vault-v1.json: a 237-byte v1 vault (vault_id 1, Plus tier)
+    const legacyVault = new PublicKey("7SWc4chJxnBSpgs6fX1F2Ja3SA2S6DAPMyKLcjfAYW7c");
+    const legacyCreator = new PublicKey("2MEcMufJGXXMcAESXQjBkcM9AujZNWecLwyPhA5AJNwr");
+
+    const migrateVault = (vault: PublicKey) =>
+      program.methods
+        .migrateVault()
+        .accounts({
+          vault,
+          payer: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+    it("cannot deserialize a v1 vault before migration", async () => {
+      const info = await provider.connection.getAccountInfo(legacyVault);
+      assert.equal(info.data.length, 237);
+
+      let decoded = true;
+      try {
+        await (program.account as any).vault.fetch(legacyVault);
+      } catch (err) {
+        decoded = false;
+      }
+      assert.isFalse(decoded);
+    });
+
+    it("migrates a v1 vault byte-blob to the current schema", async () => {
+      await migrateVault(legacyVault);
+
+      const info = await provider.connection.getAccountInfo(legacyVault);
+      // v1 + schema_version + the v2 fields: beneficiary_count and 5 shares, pending beneficiary,
+      // backup beneficiary, claim window and deadline, payout mode, release schedule and amount
+      // released, kind, asset count, relay nonce, early check-in
+      assert.equal(info.data.length, 8 + 229 + 1 + 1 + 5 * 34 + 32 + 8 + 32 + 4 + 8 + 1 + 12 + 8 + 1 + 1 + 8 + 1 + 4);
+
+      const vault = await (program.account as any).vault.fetch(legacyVault);
+      assert.equal(vault.schemaVersion, 2);
+      assert.equal(vault.releaseSchedule.tranches, 0);
+      assert.deepEqual(vault.kind, { token: {} });
+      assert.equal(vault.assetCount, 0);
+      assert.equal(vault.relayNonce.toNumber(), 0);
+      assert.equal(vault.earlyCheckin, false);
+      assert.equal(vault.minCheckinIntervalSeconds, 0);
+      assert.deepEqual(vault.payoutMode, { push: {} });
+      assert.equal(vault.claimDeadline.toNumber(), 0);
+      assert.equal(vault.beneficiaryCount, 0);
+      assert.equal(vault.beneficiaryEffectiveAt.toNumber(), 0);
+      assert.equal(vault.creator.toBase58(), legacyCreator.toBase58());
+      assert.equal(vault.vaultId.toNumber(), 1);
+      assert.deepEqual(vault.tier, { plus: {} });
+      assert.equal(vault.creationFeePaid.toNumber(), 8_000_000);
+      assert.equal(vault.checkinPeriodSeconds, 2_592_000);
+      assert.deepEqual(vault.nameHash, Array(32).fill(7));
+    });
+
+    it("refuses to migrate a vault that is already current", async () => {
+      try {
+        await migrateVault(legacyVault);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "VaultUpToDate");
+      }
+    });
+  });
+
+
+  describe("wallet flags", () => {
+    // Mirror WALLET_FLAG_* constants in lib.rs
+    const WALLET_FLAG_SELF_BENEFICIARY = 1 << 0;
+    const WALLET_FLAG_FEE_EXEMPT = 1 << 1;
+    let tester: Keypair;
+    let testerUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let flagsPda: PublicKey;
+
+    const setWalletFlags = (wallet: PublicKey, flags: number, signer: Keypair = admin) =>
+      program.methods
+        .setWalletFlags(wallet, flags)
+        .accounts({
+          config: configPda,
+          walletFlags: PublicKey.findProgramAddressSync([Buffer.from("flags"), wallet.toBuffer()], programId)[0],
+          authority: signer.publicKey,
+          roleAssignment: null,
+          payer: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    const createSelfVault = (walletFlags: PublicKey | null, creationFeePaid: number) =>
+      program.methods
+        .createVault(tester.publicKey, 3600, Array.from(Buffer.alloc(32, 12)), 60, 60, { base: {} }, new anchor.BN(creationFeePaid), null)
+        .accounts({
+          config: configPda,
+          counter: counterPdaFor(tester.publicKey),
+          vault: vaultPdaFor(tester.publicKey, 1),
+          vaultTokenAccount: ataFor(vaultPdaFor(tester.publicKey, 1), usdcMint),
+          mint: usdcMint,
+          creatorUsdcAta: testerUsdcAta,
+          treasuryUsdcAta,
+          creator: tester.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          walletFlags,
+        })
+        .signers([tester])
+        .rpc();
+
+    before(async () => {
+      tester = Keypair.generate();
+      await provider.connection.requestAirdrop(tester.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      testerUsdcAta = await createAssociatedTokenAccount(provider.connection, tester, usdcMint, tester.publicKey);
+      await mintTo(provider.connection, admin, usdcMint, testerUsdcAta, admin, 10_000_000);
+
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+      flagsPda = PublicKey.findProgramAddressSync([Buffer.from("flags"), tester.publicKey.toBuffer()], programId)[0];
+    });
+
+    it("fails when non-admin sets flags", async () => {
+      try {
+        await setWalletFlags(tester.publicKey, WALLET_FLAG_SELF_BENEFICIARY, tester);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "MissingRole");
+      }
+    });
+
+    it("rejects empty or unknown flags", async () => {
+      for (const flags of [0, 1 << 3]) {
+        try {
+          await setWalletFlags(tester.publicKey, flags);
+          assert.fail("Should have failed");
+        } catch (err) {
+          assert.include(err.message, "InvalidWalletFlags");
+        }
+      }
+    });
+
+    it("still rejects an unflagged creator as their own beneficiary", async () => {
+      try {
+        await createSelfVault(null, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidBeneficiary");
+      }
+    });
+
+    it("lets a self-beneficiary, fee-exempt wallet create a free test vault", async () => {
+      await setWalletFlags(tester.publicKey, WALLET_FLAG_SELF_BENEFICIARY | WALLET_FLAG_FEE_EXEMPT);
+
+      const flags = await (program.account as any).walletFlags.fetch(flagsPda);
+      assert.equal(flags.wallet.toBase58(), tester.publicKey.toBase58());
+      assert.equal(flags.flags, WALLET_FLAG_SELF_BENEFICIARY | WALLET_FLAG_FEE_EXEMPT);
+
+      const treasuryBefore = (await getAccount(provider.connection, treasuryUsdcAta)).amount;
+      await createSelfVault(flagsPda, 0);
+      const treasuryAfter = (await getAccount(provider.connection, treasuryUsdcAta)).amount;
+      assert.equal(treasuryAfter.toString(), treasuryBefore.toString());
+
+      const vault = await (program.account as any).vault.fetch(vaultPdaFor(tester.publicKey, 1));
+      assert.isTrue(vault.isTestVault);
+      assert.equal(vault.creationFeePaid.toNumber(), 0);
+    });
+
+    it("removes the flags and refunds the payer", async () => {
+      await program.methods
+        .removeWalletFlags()
+        .accounts({
+          config: configPda,
+          walletFlags: flagsPda,
+          authority: admin.publicKey,
+          roleAssignment: null,
+          payer: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(flagsPda));
+    });
+  });
+
+
+  describe("beneficiary splits", () => {
+    let splitCreator: Keypair;
+    let splitCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let second: Keypair;
+    let third: Keypair;
+    let secondUsdcAta: PublicKey;
+    let thirdUsdcAta: PublicKey;
+
+    const share = (payee: PublicKey, bps: number) => ({ beneficiary: payee, bps });
+
+    const setBeneficiaries = (vaultPda: PublicKey, shares: any[]) =>
+      program.methods
+        .setBeneficiaries(shares)
+        .accounts({ vault: vaultPda, creator: splitCreator.publicKey })
+        .signers([splitCreator])
+        .rpc();
+
+    const releaseSplitVault = (vaultPda: PublicKey, payeeAtas: PublicKey[]) =>
+      program.methods
+        .release()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(splitCreator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          beneficiaryUsdcAta: ataFor(beneficiary.publicKey, usdcMint),
+          beneficiary: beneficiary.publicKey,
+          treasuryUsdcAta,
+          payer: splitCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(payeeAtas.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+        .signers([splitCreator])
+        .rpc();
+
+    before(async () => {
+      splitCreator = Keypair.generate();
+      second = Keypair.generate();
+      third = Keypair.generate();
+      await provider.connection.requestAirdrop(splitCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      splitCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        splitCreator,
+        usdcMint,
+        splitCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, splitCreatorUsdcAta, admin, 20_000_000);
+
+      secondUsdcAta = await createAssociatedTokenAccount(provider.connection, splitCreator, usdcMint, second.publicKey);
+      thirdUsdcAta = await createAssociatedTokenAccount(provider.connection, splitCreator, usdcMint, third.publicKey);
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+    });
+
+    it("rejects shares that do not sum to 10000 or repeat a payee", async () => {
+      const vaultPda = await createFundedVault(splitCreator, 1, { checkinSecs: 4, amount: 1_000_000 });
+
+      for (const shares of [
+        [share(beneficiary.publicKey, 5_000), share(second.publicKey, 4_000)],
+        [share(beneficiary.publicKey, 5_000), share(beneficiary.publicKey, 5_000)],
+        [share(beneficiary.publicKey, 10_000), share(second.publicKey, 0)],
+      ]) {
+        try {
+          await setBeneficiaries(vaultPda, shares);
+          assert.fail("Should have failed");
+        } catch (err) {
+          assert.include(err.message, "InvalidBeneficiaryShares");
+        }
+      }
+
+      try {
+        await setBeneficiaries(vaultPda, [share(splitCreator.publicKey, 10_000)]);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidBeneficiary");
+      }
+    });
+
+    it("splits the release by bps and gives rounding dust to the primary beneficiary", async () => {
+      const vaultPda = await createFundedVault(splitCreator, 2, { checkinSecs: 4, amount: 1_000_003 });
+      await setBeneficiaries(vaultPda, [
+        share(beneficiary.publicKey, 3_334),
+        share(second.publicKey, 3_333),
+        share(third.publicKey, 3_333),
+      ]);
+
+      const vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.equal(vault.beneficiaryCount, 3);
+      assert.equal(vault.beneficiary.toBase58(), beneficiary.publicKey.toBase58());
+
+      await sleep(6000);
+
+      // Missing the third payee's account
+      try {
+        await releaseSplitVault(vaultPda, [secondUsdcAta]);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "BeneficiaryAccountMismatch");
+      }
+
+      // Payee accounts out of order
+      try {
+        await releaseSplitVault(vaultPda, [thirdUsdcAta, secondUsdcAta]);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "BeneficiaryAccountMismatch");
+      }
+
+      const primaryAta = ataFor(beneficiary.publicKey, usdcMint);
+      const primaryBefore = await balanceOf(primaryAta);
+
+      const paid: any[] = [];
+      const listener = program.addEventListener("beneficiaryPaid", (event) => {
+        paid.push(event);
+      });
+      await releaseSplitVault(vaultPda, [secondUsdcAta, thirdUsdcAta]);
+      await sleep(1000);
+      await program.removeEventListener(listener);
+
+      // 1_000_003 * 3333 / 10000 rounds down to 333_300; the 2 units of dust go to the primary
+      assert.equal((await balanceOf(primaryAta)) - primaryBefore, BigInt(333_403));
+      assert.equal(await balanceOf(secondUsdcAta), BigInt(333_300));
+      assert.equal(await balanceOf(thirdUsdcAta), BigInt(333_300));
+
+      assert.equal(paid.length, 3);
+      assert.deepEqual(paid.map((event) => event.bps), [3_334, 3_333, 3_333]);
+      assert.equal(paid[0].amount.toNumber(), 333_403);
+    });
+  });
+
+
+  describe("beneficiary change", () => {
+    let changeCreator: Keypair;
+    let changeCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let heir: Keypair;
+
+    const updateBeneficiary = (vaultPda: PublicKey, newBeneficiary: PublicKey) =>
+      program.methods
+        .updateBeneficiary(newBeneficiary)
+        .accounts({ config: configPda, vault: vaultPda, creator: changeCreator.publicKey })
+        .signers([changeCreator])
+        .rpc();
+
+    const releaseTo = (vaultPda: PublicKey, payee: PublicKey) =>
+      program.methods
+        .release()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(changeCreator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          beneficiaryUsdcAta: ataFor(payee, usdcMint),
+          beneficiary: payee,
+          treasuryUsdcAta,
+          payer: changeCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([changeCreator])
+        .rpc();
+
+    before(async () => {
+      changeCreator = Keypair.generate();
+      heir = Keypair.generate();
+      await provider.connection.requestAirdrop(changeCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      changeCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        changeCreator,
+        usdcMint,
+        changeCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, changeCreatorUsdcAta, admin, 10_000_000);
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+
+      // Config timelock is zero here, so the cooldown change applies immediately
+      await queueConfigChange(null, null, null, null, 2);
+      await executeConfigChange();
+    });
+
+    after(async () => {
+      await queueConfigChange(null, null, null, null, 604_800);
+      await executeConfigChange();
+    });
+
+    it("rejects cooldowns above 30 days", async () => {
+      try {
+        await queueConfigChange(null, null, null, null, 2_592_001);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidBeneficiaryCooldown");
+      }
+    });
+
+    it("rejects the creator as the new beneficiary", async () => {
+      const vaultPda = await createFundedVault(changeCreator, 1, { amount: 2_000_000 });
+      try {
+        await updateBeneficiary(vaultPda, changeCreator.publicKey);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidBeneficiary");
+      }
+    });
+
+    it("queues the change behind the cooldown and lets it be withdrawn", async () => {
+      const vaultPda = vaultPdaFor(changeCreator.publicKey, 1);
+
+      let changed: any = null;
+      const listener = program.addEventListener("beneficiaryChanged", (event) => {
+        changed = event;
+      });
+      await updateBeneficiary(vaultPda, heir.publicKey);
+      await sleep(1000);
+      await program.removeEventListener(listener);
+
+      let vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.equal(vault.beneficiary.toBase58(), beneficiary.publicKey.toBase58());
+      assert.equal(vault.pendingBeneficiary.toBase58(), heir.publicKey.toBase58());
+      assert.isNotNull(changed);
+      assert.equal(changed.old.toBase58(), beneficiary.publicKey.toBase58());
+      assert.equal(changed.new.toBase58(), heir.publicKey.toBase58());
+      assert.equal(changed.effectiveAt.toNumber(), vault.beneficiaryEffectiveAt.toNumber());
+
+      // Naming the current beneficiary again withdraws the pending change
+      await updateBeneficiary(vaultPda, beneficiary.publicKey);
+      vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.equal(vault.beneficiaryEffectiveAt.toNumber(), 0);
+    });
+
+    it("blocks changes during the watchdog window and pays the new beneficiary once the cooldown elapses", async () => {
+      // 5s check-in period, 1s notification window, 1s grace period, 2s cooldown
+      const vaultPda = await createFundedVault(changeCreator, 2, { checkinSecs: 5, amount: 2_000_000 });
+      await updateBeneficiary(vaultPda, heir.publicKey);
+
+      await sleep(7000);
+
+      try {
+        await updateBeneficiary(vaultPda, beneficiary.publicKey);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "BeneficiariesLocked");
+      }
+
+      try {
+        await releaseTo(vaultPda, beneficiary.publicKey);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "BeneficiaryMismatch");
+      }
+
+      await releaseTo(vaultPda, heir.publicKey);
+
+      const heirAccount = await getAccount(provider.connection, ataFor(heir.publicKey, usdcMint));
+      assert.equal(heirAccount.amount.toString(), "2000000");
+
+      const vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.equal(vault.beneficiary.toBase58(), heir.publicKey.toBase58());
+      assert.equal(vault.beneficiaryEffectiveAt.toNumber(), 0);
+    });
+  });
+
+
+  describe("backup beneficiary", () => {
+    let backupCreator: Keypair;
+    let backupCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let backup: Keypair;
+    let primaryVault: PublicKey;
+    let backupVault: PublicKey;
+
+    const setBackup = (vaultPda: PublicKey, backupKey: PublicKey | null, claimWindowSeconds: number) =>
+      program.methods
+        .setBackupBeneficiary(backupKey, claimWindowSeconds)
+        .accounts({ vault: vaultPda, creator: backupCreator.publicKey })
+        .signers([backupCreator])
+        .rpc();
+
+    const release = (vaultPda: PublicKey) =>
+      program.methods
+        .release()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(backupCreator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          beneficiaryUsdcAta: ataFor(beneficiary.publicKey, usdcMint),
+          beneficiary: beneficiary.publicKey,
+          treasuryUsdcAta,
+          payer: backupCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backupCreator])
+        .rpc();
+
+    const claim = (vaultPda: PublicKey, claimant: Keypair) =>
+      program.methods
+        .claim()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          claimant: claimant.publicKey,
+          destination: ataFor(claimant.publicKey, usdcMint),
+          treasuryUsdcAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimant])
+        .rpc();
+
+    before(async () => {
+      backupCreator = Keypair.generate();
+      backup = Keypair.generate();
+      await provider.connection.requestAirdrop(backupCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.requestAirdrop(backup.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.requestAirdrop(beneficiary.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      backupCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        backupCreator,
+        usdcMint,
+        backupCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, backupCreatorUsdcAta, admin, 10_000_000);
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+      await createAssociatedTokenAccount(provider.connection, backupCreator, usdcMint, backup.publicKey);
+      await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, beneficiary.publicKey);
+
+      // 5s check-in period
+      primaryVault = await createFundedVault(backupCreator, 1, { checkinSecs: 5, amount: 3_000_000 });
+      backupVault = await createFundedVault(backupCreator, 2, { checkinSecs: 5, amount: 3_000_000 });
+    });
+
+    it("rejects the beneficiary as their own backup", async () => {
+      try {
+        await setBackup(primaryVault, beneficiary.publicKey, 3);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidBackupBeneficiary");
+      }
+    });
+
+    it("names a backup with a 3 second claim window", async () => {
+      await setBackup(primaryVault, backup.publicKey, 3);
+      await setBackup(backupVault, backup.publicKey, 3);
+
+      const vault = await (program.account as any).vault.fetch(primaryVault);
+      assert.equal(vault.backupBeneficiary.toBase58(), backup.publicKey.toBase58());
+      assert.equal(vault.claimWindowSeconds, 3);
+    });
+
+    it("opens the claim window on release instead of pushing funds", async () => {
+      await sleep(7000);
+
+      const before = await balanceOf(ataFor(beneficiary.publicKey, usdcMint));
+      await release(primaryVault);
+      await release(backupVault);
+      assert.equal(await balanceOf(ataFor(beneficiary.publicKey, usdcMint)), before);
+
+      const vault = await (program.account as any).vault.fetch(primaryVault);
+      assert.isFalse(vault.released);
+      assert.notEqual(vault.claimDeadline.toNumber(), 0);
+
+      try {
+        await release(primaryVault);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ClaimPending");
+      }
+    });
+
+    it("keeps the backup out until the deadline", async () => {
+      try {
+        await claim(backupVault, backup);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotClaimant");
+      }
+    });
+
+    it("lets the beneficiary claim within the window", async () => {
+      const before = await balanceOf(ataFor(beneficiary.publicKey, usdcMint));
+      await claim(primaryVault, beneficiary);
+      assert.equal((await balanceOf(ataFor(beneficiary.publicKey, usdcMint))) - before, BigInt(3_000_000));
+
+      const vault = await (program.account as any).vault.fetch(primaryVault);
+      assert.isTrue(vault.released);
+      assert.equal(vault.amountLocked.toNumber(), 0);
+    });
+
+    it("blocks claims while releases are paused", async () => {
+      await sleep(4000);
+
+      await setPauseFlags(PAUSE_RELEASE);
+      try {
+        await claim(backupVault, backup);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      } finally {
+        await setPauseFlags(0);
+      }
+    });
+
+    it("lets the backup claim once the beneficiary misses the deadline", async () => {
+      await claim(backupVault, backup);
+      assert.equal(await balanceOf(ataFor(backup.publicKey, usdcMint)), BigInt(3_000_000));
+
+      try {
+        await claim(backupVault, beneficiary);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "AlreadyReleased");
+      }
+    });
+  });
+
+
+  describe("claim mode", () => {
+    let claimCreator: Keypair;
+    let claimCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let vaultPda: PublicKey;
+
+    const claimTo = (claimant: Keypair, destination: PublicKey) =>
+      program.methods
+        .claim()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          claimant: claimant.publicKey,
+          destination,
+          treasuryUsdcAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([claimant])
+        .rpc();
+
+    before(async () => {
+      claimCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(claimCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.requestAirdrop(beneficiary.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      claimCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        claimCreator,
+        usdcMint,
+        claimCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, claimCreatorUsdcAta, admin, 10_000_000);
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+
+      // 5s check-in period
+      vaultPda = await createFundedVault(claimCreator, 1, { checkinSecs: 5, amount: 2_000_000 });
+    });
+
+    it("switches the vault to claim mode", async () => {
+      await program.methods
+        .setPayoutMode({ claim: {} })
+        .accounts({ vault: vaultPda, creator: claimCreator.publicKey })
+        .signers([claimCreator])
+        .rpc();
+
+      const vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.deepEqual(vault.payoutMode, { claim: {} });
+    });
+
+    it("rejects split beneficiaries in claim mode", async () => {
+      try {
+        await program.methods
+          .setBeneficiaries([
+            { beneficiary: beneficiary.publicKey, bps: 5_000 },
+            { beneficiary: Keypair.generate().publicKey, bps: 5_000 },
+          ])
+          .accounts({ vault: vaultPda, creator: claimCreator.publicKey })
+          .signers([claimCreator])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ClaimModeRequiresSinglePayee");
+      }
+    });
+
+    it("keeps the vault closed until the grace period ends", async () => {
+      try {
+        await claimTo(beneficiary, ataFor(beneficiary.publicKey, usdcMint));
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ClaimNotOpen");
+      }
+    });
+
+    it("rejects release once the vault is claimable", async () => {
+      await sleep(7000);
+
+      try {
+        await program.methods
+          .release()
+          .accounts({
+            config: configPda,
+            vault: vaultPda,
+            counter: counterPdaFor(claimCreator.publicKey),
+            vaultTokenAccount: ataFor(vaultPda, usdcMint),
+            mint: usdcMint,
+            beneficiaryUsdcAta: ataFor(beneficiary.publicKey, usdcMint),
+            beneficiary: beneficiary.publicKey,
+            treasuryUsdcAta,
+            payer: claimCreator.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([claimCreator])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ClaimModeVault");
+      }
+    });
+
+    it("rejects a destination the claimant does not own", async () => {
+      try {
+        await claimTo(beneficiary, claimCreatorUsdcAta);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidClaimDestination");
+      }
+    });
+
+    it("pays the beneficiary into a non-ATA token account", async () => {
+      const destination = await createAccount(
+        provider.connection,
+        admin,
+        usdcMint,
+        beneficiary.publicKey,
+        Keypair.generate()
+      );
+      await claimTo(beneficiary, destination);
+
+      const destinationAccount = await getAccount(provider.connection, destination);
+      assert.equal(destinationAccount.amount.toString(), "2000000");
+
+      const vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.isTrue(vault.released);
+      assert.equal(vault.amountLocked.toNumber(), 0);
+    });
+  });
+
+
+  describe("withdrawals", () => {
+    let withdrawCreator: Keypair;
+    let withdrawCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let vaultPda: PublicKey;
+
+    const withdraw = (pda: PublicKey, amount: number) =>
+      program.methods
+        .withdrawUsdc(new anchor.BN(amount))
+        .accounts({
+          config: configPda,
+          vault: pda,
+          vaultTokenAccount: ataFor(pda, usdcMint),
+          mint: usdcMint,
+          creatorUsdcAta: withdrawCreatorUsdcAta,
+          creator: withdrawCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([withdrawCreator])
+        .rpc();
+
+    before(async () => {
+      withdrawCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(withdrawCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      withdrawCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        withdrawCreator,
+        usdcMint,
+        withdrawCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, withdrawCreatorUsdcAta, admin, 10_000_000);
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+
+      vaultPda = await createFundedVault(withdrawCreator, 1, { amount: 3_000_000 });
+    });
+
+    after(async () => {
+      await queueConfigChange(null, null, null, null, null, new anchor.BN(0));
+      await executeConfigChange();
+    });
+
+    it("returns part of the vault to the creator", async () => {
+      const before = await balanceOf(withdrawCreatorUsdcAta);
+      await withdraw(vaultPda, 1_000_000);
+
+      const after = await balanceOf(withdrawCreatorUsdcAta);
+      assert.equal(after - before, BigInt(1_000_000));
+
+      const vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.equal(vault.amountLocked.toNumber(), 2_000_000);
+      const vaultAccount = await getAccount(provider.connection, ataFor(vaultPda, usdcMint));
+      assert.equal(vaultAccount.amount.toString(), "2000000");
+    });
+
+    it("rejects withdrawing more than is locked", async () => {
+      try {
+        await withdraw(vaultPda, 2_000_001);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InsufficientVaultBalance");
+      }
+    });
+
+    it("enforces the configured minimum balance", async () => {
+      // Config timelock is zero here, so the minimum applies immediately
+      await queueConfigChange(null, null, null, null, null, new anchor.BN(1_500_000));
+      await executeConfigChange();
+
+      try {
+        await withdraw(vaultPda, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "BelowMinimumVaultBalance");
+      }
+
+      await withdraw(vaultPda, 500_000);
+      const vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.equal(vault.amountLocked.toNumber(), 1_500_000);
+    });
+
+    it("blocks withdrawals during the watchdog period", async () => {
+      // 5s check-in period with a 1s notification window
+      const watchedVault = await createFundedVault(withdrawCreator, 2, { checkinSecs: 5, amount: 3_000_000 });
+      await sleep(5000);
+
+      try {
+        await withdraw(watchedVault, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "CannotWithdrawDuringWatchdog");
+      }
+    });
+
+    it("blocks withdrawals only while withdrawals are paused", async () => {
+      const pausedVault = await createFundedVault(withdrawCreator, 3, { amount: 2_000_000 });
+
+      await setPauseFlags(PAUSE_WITHDRAW);
+      try {
+        await withdraw(pausedVault, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      } finally {
+        await setPauseFlags(0);
+      }
+
+      // Pausing cancels leaves withdrawals open
+      await setPauseFlags(PAUSE_CANCEL);
+      try {
+        await withdraw(pausedVault, 500_000);
+      } finally {
+        await setPauseFlags(0);
+      }
+      const vault = await (program.account as any).vault.fetch(pausedVault);
+      assert.equal(vault.amountLocked.toNumber(), 1_500_000);
+    });
+  });
+
+
+  describe("vesting", () => {
+    let vestCreator: Keypair;
+    let vestCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let vaultPda: PublicKey;
+
+    // Half at trigger, then two tranches 6 seconds apart
+    const schedule = { cliffSeconds: 0, intervalSeconds: 6, tranches: 2, upfrontBps: 5_000 };
+
+    const releaseAccounts = () => ({
+      config: configPda,
+      vault: vaultPda,
+      counter: counterPdaFor(vestCreator.publicKey),
+      vaultTokenAccount: ataFor(vaultPda, usdcMint),
+      mint: usdcMint,
+      beneficiaryUsdcAta: ataFor(beneficiary.publicKey, usdcMint),
+      beneficiary: beneficiary.publicKey,
+      treasuryUsdcAta,
+      payer: vestCreator.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    const releaseTranche = () =>
+      program.methods.releaseTranche().accounts(releaseAccounts()).signers([vestCreator]).rpc();
+
+    const beneficiaryBalance = () => balanceOf(ataFor(beneficiary.publicKey, usdcMint));
+
+    before(async () => {
+      vestCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(vestCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      vestCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        vestCreator,
+        usdcMint,
+        vestCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, vestCreatorUsdcAta, admin, 10_000_000);
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+      await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, beneficiary.publicKey);
+      vaultPda = vaultPdaFor(vestCreator.publicKey, 1);
+    });
+
+    it("rejects a schedule without tranches", async () => {
+      try {
+        await createFundedVault(vestCreator, 1, { checkinSecs: 4, releaseSchedule: { ...schedule, tranches: 0 } });
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidReleaseSchedule");
+      }
+    });
+
+    it("stores the schedule chosen at creation", async () => {
+      await createFundedVault(vestCreator, 1, { checkinSecs: 4, releaseSchedule: schedule, amount: 4_000_000 });
+
+      const vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.equal(vault.releaseSchedule.tranches, 2);
+      assert.equal(vault.releaseSchedule.upfrontBps, 5_000);
+      assert.equal(vault.amountReleased.toNumber(), 0);
+    });
+
+    it("rejects a backup beneficiary on a vesting vault", async () => {
+      try {
+        await program.methods
+          .setBackupBeneficiary(Keypair.generate().publicKey, 60)
+          .accounts({ vault: vaultPda, creator: vestCreator.publicKey })
+          .signers([vestCreator])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ReleaseScheduleConflict");
+      }
+    });
+
+    it("releases nothing before the switch triggers", async () => {
+      try {
+        await releaseTranche();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidUnlockTime");
+      }
+    });
+
+    it("pays the upfront share at trigger and refuses a lump-sum release", async () => {
+      await sleep(6000);
+
+      try {
+        await program.methods.release().accounts(releaseAccounts()).signers([vestCreator]).rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ReleaseScheduled");
+      }
+
+      const before = await beneficiaryBalance();
+      await releaseTranche();
+      assert.equal((await beneficiaryBalance()) - before, BigInt(2_000_000));
+
+      try {
+        await releaseTranche();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NothingVested");
+      }
+    });
+
+    it("pays each tranche as it vests and completes the vault", async () => {
+      await sleep(5000);
+
+      let before = await beneficiaryBalance();
+      await releaseTranche();
+      assert.equal((await beneficiaryBalance()) - before, BigInt(1_000_000));
+
+      let vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.isFalse(vault.released);
+      assert.equal(vault.amountReleased.toNumber(), 3_000_000);
+      assert.equal(vault.amountLocked.toNumber(), 1_000_000);
+
+      await sleep(6000);
+
+      before = await beneficiaryBalance();
+      await releaseTranche();
+      assert.equal((await beneficiaryBalance()) - before, BigInt(1_000_000));
+
+      vault = await (program.account as any).vault.fetch(vaultPda);
+      assert.isTrue(vault.released);
+      assert.equal(vault.amountReleased.toNumber(), 4_000_000);
+      assert.equal(vault.amountLocked.toNumber(), 0);
+    });
+  });
+
+
+  describe("supported mints", () => {
+    let altMint: PublicKey;
+    let supportedMintPda: PublicKey;
+    let mintCreator: Keypair;
+    let mintCreatorAta: PublicKey;
+    let treasuryAltAta: PublicKey;
+
+    const altTierFees = (creation: number) => ({
+      creationFee: new anchor.BN(creation),
+      closingFee: new anchor.BN(250_000),
+      releaseFee: new anchor.BN(0),
+      releaseFeeBps: null,
+    });
+
+    const altSchedule = {
+      base: altTierFees(500_000),
+      plus: altTierFees(4_000_000),
+      premium: altTierFees(10_000_000),
+      lifetime: altTierFees(50_000_000),
+      releaseFeeBps: 0,
+    };
+
+    const setSupportedMint = (mint: PublicKey, cap: number, enabled: boolean, signer: Keypair = admin) =>
+      program.methods
+        .setSupportedMint(new anchor.BN(cap), altSchedule, enabled)
+        .accounts({
+          config: configPda,
+          mint,
+          supportedMint: PublicKey.findProgramAddressSync(
+            [Buffer.from("supported_mint"), mint.toBuffer()],
+            program.programId
+          )[0],
+          treasury: treasury.publicKey,
+          treasuryTokenAccount: ataFor(treasury.publicKey, mint),
+          admin: signer.publicKey,
+          payer: signer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    const altVaultOptions = () => ({
+      mint: altMint,
+      supportedMint: supportedMintPda,
+      notificationSecs: 60,
+      graceSecs: 60,
+      creationFeePaid: 500_000,
+    });
+
+    const createAltVault = (vaultId: number) => createFundedVault(mintCreator, vaultId, altVaultOptions());
+
+    const depositAlt = (vaultId: number, amount: number) =>
+      depositInto(mintCreator, vaultPdaFor(mintCreator.publicKey, vaultId), amount, altVaultOptions());
+
+    const cancelAlt = (vaultId: number, supportedMint: PublicKey | null = supportedMintPda) => {
+      const vaultPda = vaultPdaFor(mintCreator.publicKey, vaultId);
+      return program.methods
+        .cancelVault()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(mintCreator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, altMint),
+          mint: altMint,
+          creatorUsdcAta: mintCreatorAta,
+          treasuryUsdcAta: treasuryAltAta,
+          creator: mintCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          supportedMint,
+        })
+        .signers([mintCreator])
+        .rpc();
+    };
+
+    before(async () => {
+      mintCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(mintCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      altMint = await createMint(provider.connection, admin, admin.publicKey, null, 6);
+      [supportedMintPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("supported_mint"), altMint.toBuffer()],
+        program.programId
+      );
+      mintCreatorAta = await createAssociatedTokenAccount(
+        provider.connection,
+        mintCreator,
+        altMint,
+        mintCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, altMint, mintCreatorAta, admin, 100_000_000);
+
+      // Exists before listing so the unlisted-mint case reaches the program's check
+      treasuryAltAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, altMint, treasury.publicKey)
+      ).address;
+    });
+
+    it("rejects vaults in an unlisted mint", async () => {
+      try {
+        await createAltVault(1);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "UnsupportedMint");
+      }
+    });
+
+    it("rejects listing the config mint", async () => {
+      try {
+        await setSupportedMint(usdcMint, 50_000_000, true);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ConfigMintListed");
+      }
+    });
+
+    it("fails when non-admin lists a mint", async () => {
+      try {
+        await setSupportedMint(altMint, 50_000_000, true, creator);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "ConstraintHasOne");
+      }
+    });
+
+    it("lists a mint with its own cap and fees", async () => {
+      await setSupportedMint(altMint, 50_000_000, true);
+
+      const entry = await (program.account as any).supportedMint.fetch(supportedMintPda);
+      assert.equal(entry.mint.toBase58(), altMint.toBase58());
+      assert.equal(entry.maxLockPerVault.toNumber(), 50_000_000);
+      assert.equal(entry.feeSchedule.base.creationFee.toNumber(), 500_000);
+      assert.isTrue(entry.enabled);
+    });
+
+    it("creates a vault in the listed mint, charging that mint's fee", async () => {
+      const before = await balanceOf(treasuryAltAta);
+
+      await createAltVault(1);
+
+      const vault = await program.account.vault.fetch(vaultPdaFor(mintCreator.publicKey, 1));
+      assert.equal(vault.mint.toBase58(), altMint.toBase58());
+      assert.equal((await balanceOf(treasuryAltAta)) - before, BigInt(500_000));
+    });
+
+    it("enforces the mint's cap on deposits", async () => {
+      try {
+        await depositAlt(1, 60_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "AboveVaultCap");
+      }
+
+      await depositAlt(1, 20_000_000);
+      const vault = await program.account.vault.fetch(vaultPdaFor(mintCreator.publicKey, 1));
+      assert.equal(vault.amountLocked.toNumber(), 20_000_000);
+    });
+
+    it("charges the listing's fees even when supported_mint is omitted", async () => {
+      await createAltVault(2);
+      await depositAlt(2, 10_000_000);
+
+      try {
+        await cancelAlt(2, null);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "SupportedMintRequired");
+      }
+
+      const before = await balanceOf(treasuryAltAta);
+      await cancelAlt(2);
+      assert.equal((await balanceOf(treasuryAltAta)) - before, BigInt(250_000));
+    });
+
+    it("blocks deposits once disabled but still lets the vault pay out", async () => {
+      await setSupportedMint(altMint, 50_000_000, false);
+
+      try {
+        await depositAlt(1, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "UnsupportedMint");
+      }
+
+      const before = await balanceOf(mintCreatorAta);
+      await cancelAlt(1);
+
+      // A disabled listing still charges its own closing fee
+      assert.equal((await balanceOf(mintCreatorAta)) - before, BigInt(20_000_000 - 250_000));
+    });
+
+    it("fails closed for payouts in a mint that was never listed", async () => {
+      // A config-mint vault, then a rotation that leaves the old config mint unlisted
+      const creatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        mintCreator,
+        usdcMint,
+        mintCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, creatorUsdcAta, admin, 5_000_000);
+      const vaultPda = await createFundedVault(mintCreator, 3, { amount: 2_000_000 });
+      const [usdcListing] = PublicKey.findProgramAddressSync(
+        [Buffer.from("supported_mint"), usdcMint.toBuffer()],
+        program.programId
+      );
+
+      const cancelUsdc = (supportedMint: PublicKey | null) =>
+        program.methods
+          .cancelVault()
+          .accounts({
+            config: configPda,
+            vault: vaultPda,
+            counter: counterPdaFor(mintCreator.publicKey),
+            vaultTokenAccount: ataFor(vaultPda, usdcMint),
+            mint: usdcMint,
+            creatorUsdcAta,
+            treasuryUsdcAta: ataFor(treasury.publicKey, usdcMint),
+            creator: mintCreator.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            supportedMint,
+          })
+          .signers([mintCreator])
+          .rpc();
+
+      const newConfigMint = await createMint(provider.connection, admin, admin.publicKey, null, 6);
+      await queueConfigChange(newConfigMint, null, null);
+      await executeConfigChange();
+
+      try {
+        // No fee-free payout without a listing
+        try {
+          await cancelUsdc(usdcListing);
+          assert.fail("Should have failed");
+        } catch (err) {
+          assert.include(err.message, "AccountNotInitialized");
+        }
+        try {
+          await cancelUsdc(null);
+          assert.fail("Should have failed");
+        } catch (err) {
+          assert.include(err.message, "SupportedMintRequired");
+        }
+
+        // Listing the old mint, even disabled, unblocks it at the listing's fees
+        await setSupportedMint(usdcMint, 50_000_000, false);
+        const before = await balanceOf(creatorUsdcAta);
+        await cancelUsdc(usdcListing);
+        assert.equal((await balanceOf(creatorUsdcAta)) - before, BigInt(2_000_000 - 250_000));
+      } finally {
+        await queueConfigChange(usdcMint, null, null);
+        await executeConfigChange();
+      }
+    });
+  });
+
+
+  describe("token-2022 mints", () => {
+    let t22Creator: Keypair;
+
+    const t22TierFees = {
+      creationFee: new anchor.BN(0),
+      closingFee: new anchor.BN(0),
+      releaseFee: new anchor.BN(0),
+      releaseFeeBps: null,
+    };
+    const t22Schedule = {
+      base: t22TierFees,
+      plus: t22TierFees,
+      premium: t22TierFees,
+      lifetime: t22TierFees,
+      releaseFeeBps: 0,
+    };
+
+    // 6-decimal Token-2022 mint with a single extension
+    const createToken2022Mint = async (extension: ExtensionType) => {
+      const mintKeypair = Keypair.generate();
+      const mintLen = getMintLen([extension]);
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+      const initExtension =
+        extension === ExtensionType.TransferFeeConfig
+          ? createInitializeTransferFeeConfigInstruction(
+              mintKeypair.publicKey,
+              admin.publicKey,
+              admin.publicKey,
+              100, // 1%
+              BigInt(1_000_000_000),
+              TOKEN_2022_PROGRAM_ID
+            )
+          : createInitializePermanentDelegateInstruction(mintKeypair.publicKey, admin.publicKey, TOKEN_2022_PROGRAM_ID);
+      const tx = new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: admin.publicKey,
+          newAccountPubkey: mintKeypair.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        initExtension,
+        createInitializeMintInstruction(mintKeypair.publicKey, 6, admin.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      );
+      await sendAndConfirmTransaction(provider.connection, tx, [admin, mintKeypair]);
+      return mintKeypair.publicKey;
+    };
+
+    const supportedMintPdaFor = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("supported_mint"), mint.toBuffer()], program.programId)[0];
+
+    const listMint = (mint: PublicKey) =>
+      program.methods
+        .setSupportedMint(new anchor.BN(100_000_000), t22Schedule, true)
+        .accounts({
+          config: configPda,
+          mint,
+          supportedMint: supportedMintPdaFor(mint),
+          treasury: treasury.publicKey,
+          treasuryTokenAccount: ataFor(treasury.publicKey, mint, TOKEN_2022_PROGRAM_ID),
+          admin: admin.publicKey,
+          payer: admin.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+    before(async () => {
+      t22Creator = Keypair.generate();
+      await provider.connection.requestAirdrop(t22Creator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+    });
+
+    it("rejects mints with a permanent delegate", async () => {
+      const mint = await createToken2022Mint(ExtensionType.PermanentDelegate);
+      try {
+        await listMint(mint);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "UnsupportedMintExtension");
+      }
+    });
+
+    it("locks only what arrives from a transfer-fee mint", async () => {
+      const mint = await createToken2022Mint(ExtensionType.TransferFeeConfig);
+      await listMint(mint);
+
+      const creatorAta = await createAssociatedTokenAccount(
+        provider.connection,
+        t22Creator,
+        mint,
+        t22Creator.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await mintTo(provider.connection, admin, mint, creatorAta, admin, 50_000_000, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+      const vaultPda = vaultPdaFor(t22Creator.publicKey, 1);
+      await program.methods
+        .createVault(beneficiary.publicKey, 3600, Array.from(Buffer.alloc(32, 12)), 60, 60, { base: {} }, new anchor.BN(0), null)
+        .accounts({
+          config: configPda,
+          counter: counterPdaFor(t22Creator.publicKey),
+          vault: vaultPda,
+          vaultTokenAccount: ataFor(vaultPda, mint, TOKEN_2022_PROGRAM_ID),
+          mint,
+          creatorUsdcAta: creatorAta,
+          treasuryUsdcAta: ataFor(treasury.publicKey, mint, TOKEN_2022_PROGRAM_ID),
+          creator: t22Creator.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          supportedMint: supportedMintPdaFor(mint),
+        })
+        .signers([t22Creator])
+        .rpc();
+
+      await program.methods
+        .depositUsdc(new anchor.BN(10_000_000))
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(t22Creator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, mint, TOKEN_2022_PROGRAM_ID),
+          mint,
+          creatorUsdcAta: creatorAta,
+          creator: t22Creator.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          supportedMint: supportedMintPdaFor(mint),
+        })
+        .signers([t22Creator])
+        .rpc();
+
+      // 1% transfer fee withheld on the way in
+      const vault = await program.account.vault.fetch(vaultPda);
+      assert.equal(vault.amountLocked.toNumber(), 9_900_000);
+      const vaultAccount = await getAccount(
+        provider.connection,
+        ataFor(vaultPda, mint, TOKEN_2022_PROGRAM_ID),
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      assert.equal(vaultAccount.amount.toString(), "9900000");
+    });
+  });
+
+  describe("SOL vaults", () => {
+    let solCreator: Keypair;
+    let solBeneficiary: Keypair;
+    let nativeListing: PublicKey;
+
+    const solTierFees = (creation: number) => ({
+      creationFee: new anchor.BN(creation),
+      closingFee: new anchor.BN(0),
+      releaseFee: new anchor.BN(0),
+      releaseFeeBps: null,
+    });
+    const solSchedule = {
+      base: solTierFees(1_000_000),
+      plus: solTierFees(1_000_000),
+      premium: solTierFees(1_000_000),
+      lifetime: solTierFees(1_000_000),
+      releaseFeeBps: 0,
+    };
+
+    const solVaultPda = (vaultId: number) => vaultPdaFor(solCreator.publicKey, vaultId);
+
+    const createSolVault = (vaultId: number, supportedMint: PublicKey | null, checkinPeriod = 3600, notification = 60, grace = 60) =>
+      program.methods
+        .createSolVault(
+          solBeneficiary.publicKey,
+          checkinPeriod,
+          Array.from(Buffer.alloc(32, 13)),
+          notification,
+          grace,
+          { base: {} },
+          new anchor.BN(1_000_000)
+        )
+        .accounts({
+          config: configPda,
+          counter: counterPdaFor(solCreator.publicKey),
+          vault: solVaultPda(vaultId),
+          treasury: treasury.publicKey,
+          creator: solCreator.publicKey,
+          systemProgram: SystemProgram.programId,
+          supportedMint,
+        })
+        .signers([solCreator])
+        .rpc();
+
+    const depositSol = (vaultId: number, lamports: number) =>
+      program.methods
+        .depositSol(new anchor.BN(lamports))
+        .accounts({
+          config: configPda,
+          vault: solVaultPda(vaultId),
+          creator: solCreator.publicKey,
+          systemProgram: SystemProgram.programId,
+          supportedMint: nativeListing,
+        })
+        .signers([solCreator])
+        .rpc();
+
+    const lamportsOf = (account: PublicKey) => provider.connection.getBalance(account);
+
+    before(async () => {
+      solCreator = Keypair.generate();
+      solBeneficiary = Keypair.generate();
+      await provider.connection.requestAirdrop(solCreator.publicKey, 20 * anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.requestAirdrop(solBeneficiary.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+      [nativeListing] = PublicKey.findProgramAddressSync(
+        [Buffer.from("supported_mint"), NATIVE_MINT.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("rejects SOL vaults until the native mint is listed", async () => {
+      try {
+        await createSolVault(1, null);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "UnsupportedMint");
+      }
+
+      await program.methods
+        .setSupportedMint(new anchor.BN(5 * anchor.web3.LAMPORTS_PER_SOL), solSchedule, true)
+        .accounts({
+          config: configPda,
+          mint: NATIVE_MINT,
+          supportedMint: nativeListing,
+          treasury: treasury.publicKey,
+          treasuryTokenAccount: ataFor(treasury.publicKey, NATIVE_MINT),
+          admin: admin.publicKey,
+          payer: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("creates a SOL vault and holds deposits in the vault PDA", async () => {
+      const treasuryBefore = await lamportsOf(treasury.publicKey);
+      await createSolVault(1, nativeListing);
+      assert.equal((await lamportsOf(treasury.publicKey)) - treasuryBefore, 1_000_000);
+
+      const rentOnly = await lamportsOf(solVaultPda(1));
+      await depositSol(1, 2 * anchor.web3.LAMPORTS_PER_SOL);
+
+      const vault = await program.account.vault.fetch(solVaultPda(1));
+      assert.deepEqual(vault.kind, { sol: {} });
+      assert.ok(vault.mint.equals(NATIVE_MINT));
+      assert.equal(vault.amountLocked.toNumber(), 2 * anchor.web3.LAMPORTS_PER_SOL);
+      assert.equal((await lamportsOf(solVaultPda(1))) - rentOnly, 2 * anchor.web3.LAMPORTS_PER_SOL);
+    });
+
+    it("enforces the native mint's per-vault cap", async () => {
+      try {
+        await depositSol(1, 4 * anchor.web3.LAMPORTS_PER_SOL);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "AboveVaultCap");
+      }
+    });
+
+    it("blocks SOL deposits while deposits are paused", async () => {
+      await setPauseFlags(PAUSE_DEPOSIT);
+      try {
+        await depositSol(1, anchor.web3.LAMPORTS_PER_SOL);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      } finally {
+        await setPauseFlags(0);
+      }
+    });
+
+    it("rejects token instructions on a SOL vault", async () => {
+      // Wrapped-SOL accounts so deposit_usdc gets past account validation
+      const vaultWsol = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        solCreator,
+        NATIVE_MINT,
+        solVaultPda(1),
+        true
+      );
+      const creatorWsol = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        solCreator,
+        NATIVE_MINT,
+        solCreator.publicKey
+      );
+      try {
+        await program.methods
+          .depositUsdc(new anchor.BN(0))
+          .accounts({
+            config: configPda,
+            vault: solVaultPda(1),
+            counter: counterPdaFor(solCreator.publicKey),
+            vaultTokenAccount: vaultWsol.address,
+            mint: NATIVE_MINT,
+            creatorUsdcAta: creatorWsol.address,
+            creator: solCreator.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            supportedMint: nativeListing,
+          })
+          .signers([solCreator])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "SolVault");
+      }
+    });
+
+    it("refunds lamports on cancel", async () => {
+      const creatorBefore = await lamportsOf(solCreator.publicKey);
+      // No token accounts: the refund and closing fee move lamports
+      await program.methods
+        .cancelVault()
+        .accounts({
+          config: configPda,
+          vault: solVaultPda(1),
+          counter: counterPdaFor(solCreator.publicKey),
+          creator: solCreator.publicKey,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          supportedMint: nativeListing,
+          treasury: treasury.publicKey,
+        })
+        .signers([solCreator])
+        .rpc();
+
+      const vault = await program.account.vault.fetch(solVaultPda(1));
+      assert.equal(vault.cancelled, true);
+      assert.equal(vault.amountLocked.toNumber(), 0);
+      // Refund less the transaction fee
+      assert.isAbove((await lamportsOf(solCreator.publicKey)) - creatorBefore, 2 * anchor.web3.LAMPORTS_PER_SOL - 10_000);
+    });
+
+    it("rejects deposits into a cancelled SOL vault", async () => {
+      try {
+        await depositSol(1, anchor.web3.LAMPORTS_PER_SOL);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "VaultAlreadyCancelled");
+      }
+    });
+
+    const releaseSolVault = (vaultId: number, treasuryWallet: PublicKey | null) =>
+      program.methods
+        .release()
+        .accounts({
+          config: configPda,
+          vault: solVaultPda(vaultId),
+          counter: counterPdaFor(solCreator.publicKey),
+          beneficiary: solBeneficiary.publicKey,
+          payer: provider.wallet.publicKey,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          supportedMint: nativeListing,
+          treasury: treasuryWallet,
+        })
+        .rpc();
+
+    it("pays lamports to the beneficiary on release", async () => {
+      await createSolVault(2, nativeListing, 3, 1, 1);
+      await depositSol(2, anchor.web3.LAMPORTS_PER_SOL);
+
+      // Wait past unlock + grace period
+      await sleep(6000);
+
+      // The release fee is paid in lamports, so the treasury wallet is required
+      try {
+        await releaseSolVault(2, null);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "TreasuryWalletRequired");
+      }
+
+      await setPauseFlags(PAUSE_RELEASE);
+      try {
+        await releaseSolVault(2, treasury.publicKey);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      } finally {
+        await setPauseFlags(0);
+      }
+
+      const beneficiaryBefore = await lamportsOf(solBeneficiary.publicKey);
+      await releaseSolVault(2, treasury.publicKey);
+
+      const vault = await program.account.vault.fetch(solVaultPda(2));
+      assert.equal(vault.released, true);
+      assert.equal((await lamportsOf(solBeneficiary.publicKey)) - beneficiaryBefore, anchor.web3.LAMPORTS_PER_SOL);
+    });
+  });
+
+  describe("estate assets", () => {
+    let estateCreator: Keypair;
+    let heir: Keypair;
+    let estateCreatorUsdcAta: PublicKey;
+    let treasuryUsdcAta: PublicKey;
+    let nftMint: PublicKey;
+    let tokenMint: PublicKey;
+
+    const vaultAssetsPdaFor = (vault: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("vault_assets"), vault.toBuffer()], program.programId)[0];
+
+    const depositToken = (vault: PublicKey, assetMint: PublicKey, amount: number) =>
+      program.methods
+        .depositToken(new anchor.BN(amount))
+        .accounts({
+          config: configPda,
+          vault,
+          vaultAssets: vaultAssetsPdaFor(vault),
+          assetMint,
+          vaultAssetAccount: ataFor(vault, assetMint),
+          creatorAssetAccount: ataFor(estateCreator.publicKey, assetMint),
+          creator: estateCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([estateCreator])
+        .rpc();
+
+    // Four remaining accounts per registered asset, in registry order
+    const assetAccountsFor = (vault: PublicKey, recipient: PublicKey, mints: PublicKey[]) =>
+      mints.flatMap((assetMint) => [
+        { pubkey: assetMint, isSigner: false, isWritable: false },
+        { pubkey: ataFor(vault, assetMint), isSigner: false, isWritable: true },
+        { pubkey: ataFor(recipient, assetMint), isSigner: false, isWritable: true },
+        { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ]);
+
+    before(async () => {
+      estateCreator = Keypair.generate();
+      heir = Keypair.generate();
+      await provider.connection.requestAirdrop(estateCreator.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      estateCreatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        estateCreator,
+        usdcMint,
+        estateCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, estateCreatorUsdcAta, admin, 10_000_000);
+      treasuryUsdcAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, admin, usdcMint, treasury.publicKey)
+      ).address;
+
+      // A 1-of-1 NFT and an unlisted 6-decimal token
+      nftMint = await createMint(provider.connection, admin, admin.publicKey, null, 0);
+      const creatorNftAta = await createAssociatedTokenAccount(provider.connection, estateCreator, nftMint, estateCreator.publicKey);
+      await mintTo(provider.connection, admin, nftMint, creatorNftAta, admin, 1);
+      tokenMint = await createMint(provider.connection, admin, admin.publicKey, null, 6);
+      const creatorTokenAta = await createAssociatedTokenAccount(provider.connection, estateCreator, tokenMint, estateCreator.publicKey);
+      await mintTo(provider.connection, admin, tokenMint, creatorTokenAta, admin, 5_000_000);
+    });
+
+    it("registers tokens and NFTs in the vault's asset registry", async () => {
+      const vaultPda = await createFundedVault(estateCreator, 1, { beneficiary: heir.publicKey });
+      await depositToken(vaultPda, tokenMint, 2_000_000);
+      await depositToken(vaultPda, nftMint, 1);
+      await depositToken(vaultPda, tokenMint, 1_000_000);
+
+      const registry = await program.account.vaultAssets.fetch(vaultAssetsPdaFor(vaultPda));
+      assert.equal(registry.assets.length, 2);
+      assert.ok(registry.assets[0].mint.equals(tokenMint));
+      assert.equal(registry.assets[0].amount.toNumber(), 3_000_000);
+      assert.ok(registry.assets[1].mint.equals(nftMint));
+      assert.equal(registry.assets[1].amount.toNumber(), 1);
+
+      const vault = await program.account.vault.fetch(vaultPda);
+      assert.equal(vault.assetCount, 2);
+      assert.equal(await balanceOf(ataFor(vaultPda, nftMint)), BigInt(1));
+    });
+
+    it("blocks token deposits while deposits are paused", async () => {
+      await setPauseFlags(PAUSE_DEPOSIT);
+      try {
+        await depositToken(vaultPdaFor(estateCreator.publicKey, 1), tokenMint, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "Paused");
+      } finally {
+        await setPauseFlags(0);
+      }
+    });
+
+    it("rejects the vault's own mint", async () => {
+      try {
+        await depositToken(vaultPdaFor(estateCreator.publicKey, 1), usdcMint, 1_000_000);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "PrimaryMintAsset");
+      }
+    });
+
+    it("keeps vaults with registered assets out of claim mode", async () => {
+      try {
+        await program.methods
+          .setPayoutMode({ claim: {} })
+          .accounts({ vault: vaultPdaFor(estateCreator.publicKey, 1), creator: estateCreator.publicKey })
+          .signers([estateCreator])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "VaultAssetsConflict");
+      }
+    });
+
+    it("returns registered assets to the creator on cancel", async () => {
+      const vaultPda = vaultPdaFor(estateCreator.publicKey, 1);
+      await program.methods
+        .cancelVault()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(estateCreator.publicKey),
+          vaultTokenAccount: ataFor(vaultPda, usdcMint),
+          mint: usdcMint,
+          creatorUsdcAta: estateCreatorUsdcAta,
+          treasuryUsdcAta,
+          creator: estateCreator.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          vaultAssets: vaultAssetsPdaFor(vaultPda),
+        })
+        .remainingAccounts(assetAccountsFor(vaultPda, estateCreator.publicKey, [tokenMint, nftMint]))
+        .signers([estateCreator])
+        .rpc();
+
+      assert.equal(await balanceOf(ataFor(estateCreator.publicKey, tokenMint)), BigInt(5_000_000));
+      assert.equal(await balanceOf(ataFor(estateCreator.publicKey, nftMint)), BigInt(1));
+      assert.equal((await program.account.vault.fetch(vaultPda)).assetCount, 0);
+      // The registry and the emptied asset ATAs are closed
+      assert.isNull(await provider.connection.getAccountInfo(vaultAssetsPdaFor(vaultPda)));
+      assert.isNull(await provider.connection.getAccountInfo(ataFor(vaultPda, tokenMint)));
+      assert.isNull(await provider.connection.getAccountInfo(ataFor(vaultPda, nftMint)));
+    });
+
+    it("hands registered assets to the beneficiary at release", async () => {
+      const vaultPda = await createFundedVault(estateCreator, 2, { beneficiary: heir.publicKey, checkinSecs: 3 });
+      await depositToken(vaultPda, nftMint, 1);
+
+      // Wait past unlock + grace period
+      await sleep(6000);
+
+      const releaseAccounts = {
+        config: configPda,
+        vault: vaultPda,
+        counter: counterPdaFor(estateCreator.publicKey),
+        vaultTokenAccount: ataFor(vaultPda, usdcMint),
+        mint: usdcMint,
+        beneficiaryUsdcAta: ataFor(heir.publicKey, usdcMint),
+        beneficiary: heir.publicKey,
+        treasuryUsdcAta,
+        payer: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        creator: estateCreator.publicKey,
+      };
+
+      // Leaving the registry out would strand the NFT
+      try {
+        await program.methods.release().accounts(releaseAccounts).signers([admin]).rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "VaultAssetsRequired");
+      }
+
+      // The creator gets back the registry's and the asset ATA's rent
+      const registryRent = (await provider.connection.getAccountInfo(vaultAssetsPdaFor(vaultPda))).lamports;
+      const ataRent = (await provider.connection.getAccountInfo(ataFor(vaultPda, nftMint))).lamports;
+      const creatorBefore = await provider.connection.getBalance(estateCreator.publicKey);
+
+      await program.methods
+        .release()
+        .accounts({ ...releaseAccounts, vaultAssets: vaultAssetsPdaFor(vaultPda) })
+        .remainingAccounts(assetAccountsFor(vaultPda, heir.publicKey, [nftMint]))
+        .signers([admin])
+        .rpc();
+
+      const vault = await program.account.vault.fetch(vaultPda);
+      assert.equal(vault.released, true);
+      assert.equal(vault.assetCount, 0);
+      assert.equal(await balanceOf(ataFor(heir.publicKey, nftMint)), BigInt(1));
+      assert.isNull(await provider.connection.getAccountInfo(vaultAssetsPdaFor(vaultPda)));
+      assert.isNull(await provider.connection.getAccountInfo(ataFor(vaultPda, nftMint)));
+      assert.isAtLeast(
+        await provider.connection.getBalance(estateCreator.publicKey),
+        creatorBefore + registryRent + ataRent
+      );
+    });
+  });
+
+  describe("check-in delegates", () => {
+    let delegateCreator: Keypair;
+    let phoneKey: Keypair;
+    let stranger: Keypair;
+    let vaultPda: PublicKey;
+
+    const delegatesPdaFor = (vault: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("checkin_delegates"), vault.toBuffer()], program.programId)[0];
+
+    const checkInAs = (signer: Keypair, checkinDelegates: PublicKey | null) =>
+      program.methods
+        .checkIn()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(delegateCreator.publicKey),
+          signer: signer.publicKey,
+          checkinDelegates,
+        })
+        .signers([signer])
+        .rpc();
+
+    const addDelegate = (delegate: PublicKey) =>
+      program.methods
+        .addCheckinDelegate(delegate)
+        .accounts({
+          vault: vaultPda,
+          checkinDelegates: delegatesPdaFor(vaultPda),
+          creator: delegateCreator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([delegateCreator])
+        .rpc();
+
+    before(async () => {
+      delegateCreator = Keypair.generate();
+      phoneKey = Keypair.generate();
+      stranger = Keypair.generate();
+      for (const wallet of [delegateCreator, phoneKey, stranger]) {
+        await provider.connection.requestAirdrop(wallet.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      }
+      await sleep(1000);
+
+      const creatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        delegateCreator,
+        usdcMint,
+        delegateCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, creatorUsdcAta, admin, 5_000_000);
+
+      // Notification window covers the whole period, so check-ins are always open
+      vaultPda = await createFundedVault(delegateCreator, 1, { notificationSecs: 3599, graceSecs: 60 });
+    });
+
+    it("rejects check-ins from keys that aren't delegates", async () => {
+      try {
+        await checkInAs(stranger, null);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotCheckinAuthority");
+      }
+    });
+
+    it("lets a registered delegate check in and records who did", async () => {
+      await addDelegate(phoneKey.publicKey);
+      const list = await program.account.checkinDelegates.fetch(delegatesPdaFor(vaultPda));
+      assert.equal(list.delegates.length, 1);
+
+      let checkedInBy: PublicKey | null = null;
+      const listener = program.addEventListener("vaultCheckedIn", (event) => {
+        checkedInBy = event.checkedInBy;
+      });
+      const before = (await program.account.vault.fetch(vaultPda)).unlockUnix.toNumber();
+      await sleep(1500);
+      await checkInAs(phoneKey, delegatesPdaFor(vaultPda));
+      await sleep(1000);
+      await program.removeEventListener(listener);
+
+      const after = (await program.account.vault.fetch(vaultPda)).unlockUnix.toNumber();
+      assert.isAbove(after, before);
+      assert.ok(checkedInBy && checkedInBy.equals(phoneKey.publicKey));
+    });
+
+    it("keeps delegates to check-ins only", async () => {
+      try {
+        await program.methods
+          .setPayoutMode({ claim: {} })
+          .accounts({ vault: vaultPda, creator: phoneKey.publicKey })
+          .signers([phoneKey])
+          .rpc();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.match(err.message, /ConstraintSeeds|ConstraintHasOne/);
+      }
+    });
+
+    it("rejects a duplicate delegate", async () => {
+      try {
+        await addDelegate(phoneKey.publicKey);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidCheckinDelegate");
+      }
+    });
+
+    it("revokes a delegate", async () => {
+      await program.methods
+        .removeCheckinDelegate(phoneKey.publicKey)
+        .accounts({
+          vault: vaultPda,
+          checkinDelegates: delegatesPdaFor(vaultPda),
+          creator: delegateCreator.publicKey,
+        })
+        .signers([delegateCreator])
+        .rpc();
+
+      try {
+        await checkInAs(phoneKey, delegatesPdaFor(vaultPda));
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotCheckinAuthority");
+      }
+    });
+
+    it("closes the delegate list with the vault", async () => {
+      const releasedVaultPda = await createFundedVault(delegateCreator, 2, { checkinSecs: 3 });
+      const listPda = delegatesPdaFor(releasedVaultPda);
+      await program.methods
+        .addCheckinDelegate(phoneKey.publicKey)
+        .accounts({
+          vault: releasedVaultPda,
+          checkinDelegates: listPda,
+          creator: delegateCreator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([delegateCreator])
+        .rpc();
+
+      // Wait past unlock + grace period
+      await sleep(6000);
+      await program.methods
+        .release()
+        .accounts({
+          config: configPda,
+          vault: releasedVaultPda,
+          counter: counterPdaFor(delegateCreator.publicKey),
+          vaultTokenAccount: ataFor(releasedVaultPda, usdcMint),
+          mint: usdcMint,
+          beneficiaryUsdcAta: ataFor(beneficiary.publicKey, usdcMint),
+          beneficiary: beneficiary.publicKey,
+          treasuryUsdcAta: ataFor(treasury.publicKey, usdcMint),
+          payer: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+      const listRent = (await provider.connection.getAccountInfo(listPda)).lamports;
+      const creatorBefore = await provider.connection.getBalance(delegateCreator.publicKey);
+      await program.methods
+        .closeVault()
+        .accounts({
+          vault: releasedVaultPda,
+          vaultTokenAccount: ataFor(releasedVaultPda, usdcMint),
+          creator: delegateCreator.publicKey,
+          signer: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          checkinDelegates: listPda,
+        })
+        .signers([admin])
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(listPda));
+      assert.isAtLeast(await provider.connection.getBalance(delegateCreator.publicKey), creatorBefore + listRent);
+    });
+  });
+
+  describe("relayed check-ins", () => {
+    let relayCreator: Keypair;
+    let stranger: Keypair;
+    let vaultPda: PublicKey;
+
+    const heartbeat = (nonce: number | bigint, expiry: number) => {
+      const nonceBytes = Buffer.alloc(8);
+      nonceBytes.writeBigUInt64LE(BigInt(nonce));
+      const expiryBytes = Buffer.alloc(8);
+      expiryBytes.writeBigInt64LE(BigInt(expiry));
+      return Buffer.concat([Buffer.from("keepr-vault:check_in:v1"), vaultPda.toBuffer(), nonceBytes, expiryBytes]);
+    };
+
+    // The provider wallet relays and pays; `signer` only signs the heartbeat off-chain
+    const relayCheckIn = (signer: Keypair, nonce: number | bigint, expiry: number, withSignature = true) =>
+      program.methods
+        .relayCheckIn(new anchor.BN(nonce.toString()), new anchor.BN(expiry))
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          checkinDelegates: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions(
+          withSignature
+            ? [Ed25519Program.createInstructionWithPrivateKey({ privateKey: signer.secretKey, message: heartbeat(nonce, expiry) })]
+            : []
+        )
+        .rpc();
+
+    const now = () => Math.floor(Date.now() / 1000);
+
+    before(async () => {
+      relayCreator = Keypair.generate();
+      stranger = Keypair.generate();
+      await provider.connection.requestAirdrop(relayCreator.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      const creatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        relayCreator,
+        usdcMint,
+        relayCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, creatorUsdcAta, admin, 5_000_000);
+
+      // Notification window covers the whole period, so check-ins are always open
+      vaultPda = await createFundedVault(relayCreator, 1, { notificationSecs: 3599, graceSecs: 60 });
+    });
+
+    it("checks in from a creator-signed heartbeat submitted by a relayer", async () => {
+      const before = await program.account.vault.fetch(vaultPda);
+      await sleep(1500);
+      await relayCheckIn(relayCreator, 1, now() + 300);
+
+      const vault = await program.account.vault.fetch(vaultPda);
+      assert.isAbove(vault.unlockUnix.toNumber(), before.unlockUnix.toNumber());
+      assert.equal(vault.relayNonce.toNumber(), 1);
+    });
+
+    it("rejects a replayed heartbeat", async () => {
+      try {
+        await relayCheckIn(relayCreator, 1, now() + 300);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "StaleRelayNonce");
+      }
+    });
+
+    it("rejects a heartbeat that skips the nonce sequence ahead", async () => {
+      // A delegate signing u64::MAX must not lock everyone else out of relaying
+      try {
+        await relayCheckIn(relayCreator, BigInt("18446744073709551615"), now() + 300);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "StaleRelayNonce");
+      }
+
+      const vault = await program.account.vault.fetch(vaultPda);
+      assert.equal(vault.relayNonce.toNumber(), 1);
+    });
+
+    it("rejects a heartbeat that expires too far ahead", async () => {
+      try {
+        await relayCheckIn(relayCreator, 2, now() + 7 * 86_400);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "RelayExpiryTooFar");
+      }
+    });
+
+    it("rejects an expired heartbeat", async () => {
+      try {
+        await relayCheckIn(relayCreator, 2, now() - 60);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "RelayExpired");
+      }
+    });
+
+    it("rejects heartbeats signed by anyone else", async () => {
+      try {
+        await relayCheckIn(stranger, 2, now() + 300);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotCheckinAuthority");
+      }
+    });
+
+    it("requires the ed25519 verification", async () => {
+      try {
+        await relayCheckIn(relayCreator, 2, now() + 300, false);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidRelaySignature");
+      }
+    });
+  });
+
+  describe("early check-ins", () => {
+    let earlyCreator: Keypair;
+    let vaultPda: PublicKey;
+
+    const checkIn = () =>
+      program.methods
+        .checkIn()
+        .accounts({
+          config: configPda,
+          vault: vaultPda,
+          counter: counterPdaFor(earlyCreator.publicKey),
+          signer: earlyCreator.publicKey,
+          checkinDelegates: null,
+        })
+        .signers([earlyCreator])
+        .rpc();
+
+    const setEarlyCheckin = (enabled: boolean, minIntervalSeconds: number) =>
+      program.methods
+        .setEarlyCheckin(enabled, minIntervalSeconds)
+        .accounts({ vault: vaultPda, creator: earlyCreator.publicKey })
+        .signers([earlyCreator])
+        .rpc();
+
+    before(async () => {
+      earlyCreator = Keypair.generate();
+      await provider.connection.requestAirdrop(earlyCreator.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await sleep(1000);
+
+      const creatorUsdcAta = await createAssociatedTokenAccount(
+        provider.connection,
+        earlyCreator,
+        usdcMint,
+        earlyCreator.publicKey
+      );
+      await mintTo(provider.connection, admin, usdcMint, creatorUsdcAta, admin, 5_000_000);
+
+      // One-minute notification window in a one-hour period, so "now" is well before it
+      vaultPda = await createFundedVault(earlyCreator, 1, { notificationSecs: 60, graceSecs: 60 });
+    });
+
+    it("rejects check-ins before the notification window by default", async () => {
+      try {
+        await checkIn();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotInNotificationWindow");
+      }
+    });
+
+    it("rejects a minimum interval as long as the check-in period", async () => {
+      try {
+        await setEarlyCheckin(true, 3600);
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "InvalidCheckinInterval");
+      }
+    });
+
+    it("accepts an early check-in once enabled and restarts the period", async () => {
+      await setEarlyCheckin(true, 2);
+      let vault = await program.account.vault.fetch(vaultPda);
+      assert.isTrue(vault.earlyCheckin);
+      assert.equal(vault.minCheckinIntervalSeconds, 2);
+
+      const before = vault.unlockUnix.toNumber();
+      await sleep(2500);
+      await checkIn();
+
+      vault = await program.account.vault.fetch(vaultPda);
+      assert.isAbove(vault.unlockUnix.toNumber(), before);
+      assert.equal(vault.lastCheckinUnix.toNumber() + 3600, vault.unlockUnix.toNumber());
+    });
+
+    it("enforces the minimum interval between early check-ins", async () => {
+      await setEarlyCheckin(true, 600);
+      try {
+        await checkIn();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "CheckinTooSoon");
+      }
+    });
+
+    it("rejects early check-ins again once disabled", async () => {
+      await setEarlyCheckin(false, 0);
+      try {
+        await checkIn();
+        assert.fail("Should have failed");
+      } catch (err) {
+        assert.include(err.message, "NotInNotificationWindow");
       }
     });
   });
@@ -2,6 +2,7 @@ import {
   Connection,
   Keypair,
   PublicKey,
+  SystemProgram,
   TransactionInstruction,
   Transaction,
   sendAndConfirmTransaction,
@@ -16,6 +17,9 @@ const PROGRAM_ID = new PublicKey("74v7NZh7A6SH9DmKZRC4tFUwaLvq19KfD1NGni62XQJK")
 // Wallet to add as admin tester (change this to your testing wallet)
 const TESTER_WALLET = new PublicKey("9ssrJyXicq9m6FpVeeZuwLbwp3qXoNTgv8u4n31GZphQ");
 
+// WalletFlags bits (match WALLET_FLAG_* in the program)
+const WALLET_FLAG_SELF_BENEFICIARY = 1 << 0;
+
 // Calculate instruction discriminator
 function getInstructionDiscriminator(name: string): Buffer {
   const hash = crypto.createHash("sha256");
@@ -44,26 +48,32 @@ async function main() {
 
   console.log("Config PDA:", configPda.toBase58());
 
-  // Build update_admin_test_wallets instruction
-  // Instruction data: [discriminator(8)] + [vec_length(4)] + [wallet_1(32)] + ...
-  const discriminator = getInstructionDiscriminator("update_admin_test_wallets");
-  const wallets = [TESTER_WALLET];
-
-  // Encode Vec<Pubkey>: length (u32 LE) + pubkeys
-  const vecLength = Buffer.alloc(4);
-  vecLength.writeUInt32LE(wallets.length, 0);
+  const [walletFlagsPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("flags"), TESTER_WALLET.toBuffer()],
+    PROGRAM_ID
+  );
 
-  const walletsData = Buffer.concat(wallets.map(w => w.toBuffer()));
+  console.log("WalletFlags PDA:", walletFlagsPda.toBase58());
 
-  const data = Buffer.concat([discriminator, vecLength, walletsData]);
+  // Build set_wallet_flags instruction
+  // Instruction data: [discriminator(8)] + [wallet(32)] + [flags(1)]
+  const discriminator = getInstructionDiscriminator("set_wallet_flags");
+  const data = Buffer.concat([
+    discriminator,
+    TESTER_WALLET.toBuffer(),
+    Buffer.from([WALLET_FLAG_SELF_BENEFICIARY]),
+  ]);
 
   console.log("\nInstruction discriminator:", Array.from(discriminator));
-  console.log("Number of wallets:", wallets.length);
 
   const instruction = new TransactionInstruction({
     keys: [
-      { pubkey: configPda, isSigner: false, isWritable: true },
+      { pubkey: configPda, isSigner: false, isWritable: false },
+      { pubkey: walletFlagsPda, isSigner: false, isWritable: true },
       { pubkey: adminKeypair.publicKey, isSigner: true, isWritable: false },
+      { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // No role assignment: signing as admin
+      { pubkey: adminKeypair.publicKey, isSigner: true, isWritable: true }, // payer
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ],
     programId: PROGRAM_ID,
     data,
@@ -81,12 +91,12 @@ async function main() {
       adminKeypair,
     ]);
 
-    console.log("\n✓ Admin test wallets updated! Signature:", signature);
+    console.log("\n✓ Wallet flags set! Signature:", signature);
     console.log("  View: https://solscan.io/tx/" + signature + "?cluster=devnet");
 
     console.log("\n✓ Wallet", TESTER_WALLET.toBase58(), "can now test as both creator and beneficiary!");
   } catch (err: any) {
-    console.error("\n✗ Error updating admin test wallets:", err.message);
+    console.error("\n✗ Error setting wallet flags:", err.message);
     if (err.logs) {
       console.error("Program logs:");
       err.logs.forEach((log: string) => console.error("  ", log));
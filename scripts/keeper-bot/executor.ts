@@ -9,13 +9,16 @@ import {
   Transaction,
   sendAndConfirmTransaction,
 } from '@solana/web3.js';
-import { getAssociatedTokenAddress } from '@solana/spl-token';
+import {
+  createAssociatedTokenAccountIdempotentInstruction,
+  getAssociatedTokenAddress,
+  TOKEN_PROGRAM_ID,
+} from '@solana/spl-token';
+import { formatAmount } from './mints';
+import { PROGRAM_ID } from './program';
 import type { ReleaseableVault } from './types';
 import * as crypto from 'crypto';
 
-const PROGRAM_ID = '74v7NZh7A6SH9DmKZRC4tFUwaLvq19KfD1NGni62XQJK';
-const USDC_MINT = process.env.USDC_MINT || '4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU'; // Use env var or default
-
 /**
  * Compute Anchor instruction discriminator
  * Uses first 8 bytes of SHA256("global:<instruction_name>")
@@ -25,24 +28,105 @@ function getDiscriminator(instructionName: string): Buffer {
   return Buffer.from(hash.slice(0, 8));
 }
 
+/**
+ * Read the treasury wallet from the raw config account
+ * (discriminator + admin + version + usdc_mint + max_lock + pause_flags precede it)
+ */
+async function fetchTreasury(connection: Connection, programId: PublicKey): Promise<PublicKey> {
+  const [configPda] = PublicKey.findProgramAddressSync([Buffer.from('config')], programId);
+  const info = await connection.getAccountInfo(configPda);
+  if (!info) {
+    throw new Error('Config account not found');
+  }
+  return new PublicKey(info.data.subarray(82, 114));
+}
+
+/**
+ * The token program that owns the mint (classic SPL Token or Token-2022)
+ */
+async function fetchTokenProgram(connection: Connection, mint: PublicKey): Promise<PublicKey> {
+  const info = await connection.getAccountInfo(mint);
+  return info ? info.owner : TOKEN_PROGRAM_ID;
+}
+
+/**
+ * The mint's SupportedMint PDA if it is listed, else the program ID
+ * (the "none" placeholder for optional accounts; the config mint is never listed)
+ */
+async function findSupportedMint(connection: Connection, mint: PublicKey, programId: PublicKey): Promise<PublicKey> {
+  const [supportedMint] = PublicKey.findProgramAddressSync([Buffer.from('supported_mint'), mint.toBuffer()], programId);
+  const info = await connection.getAccountInfo(supportedMint);
+  return info ? supportedMint : programId;
+}
+
+/**
+ * A vault's registered assets (deposit_token) as release's trailing remaining accounts:
+ * per asset its mint, the vault's ATA, the beneficiary's ATA and the mint's token program.
+ * The registry is the program ID placeholder for vaults that never registered an asset.
+ */
+async function findVaultAssets(
+  connection: Connection,
+  vault: PublicKey,
+  beneficiary: PublicKey,
+  programId: PublicKey
+): Promise<{ vaultAssets: PublicKey; assetAccounts: { pubkey: PublicKey; isSigner: boolean; isWritable: boolean }[] }> {
+  const [vaultAssets] = PublicKey.findProgramAddressSync([Buffer.from('vault_assets'), vault.toBuffer()], programId);
+  const info = await connection.getAccountInfo(vaultAssets);
+  if (!info) {
+    return { vaultAssets: programId, assetAccounts: [] };
+  }
+  // discriminator(8) + vault(32) + vec length(4), then mint(32) + amount(8) per asset
+  const count = info.data.readUInt32LE(40);
+  const assetAccounts = [];
+  for (let i = 0; i < count; i++) {
+    const assetMint = new PublicKey(info.data.subarray(44 + i * 40, 76 + i * 40));
+    const tokenProgram = await fetchTokenProgram(connection, assetMint);
+    assetAccounts.push(
+      { pubkey: assetMint, isSigner: false, isWritable: false },
+      { pubkey: await getAssociatedTokenAddress(assetMint, vault, true, tokenProgram), isSigner: false, isWritable: true },
+      { pubkey: await getAssociatedTokenAddress(assetMint, beneficiary, false, tokenProgram), isSigner: false, isWritable: true },
+      { pubkey: tokenProgram, isSigner: false, isWritable: false }
+    );
+  }
+  return { vaultAssets, assetAccounts };
+}
+
 /**
  * Build release instruction manually (same logic as web/app/_lib/instructions.ts)
+ * SOL vaults (tokenProgram null) skip the token accounts: lamports go straight to the
+ * payees' wallets and the release fee to the treasury wallet
  */
 async function buildReleaseInstruction(
   vault: PublicKey,
   creator: PublicKey,
   beneficiary: PublicKey,
   payer: PublicKey,
+  treasury: PublicKey,
+  mint: PublicKey,
+  tokenProgram: PublicKey | null,
+  supportedMint: PublicKey,
+  additionalBeneficiaries: PublicKey[],
+  scheduled: boolean,
+  vaultAssets: PublicKey,
+  assetAccounts: { pubkey: PublicKey; isSigner: boolean; isWritable: boolean }[],
   programId: PublicKey
-): Promise<{ instruction: any; accounts: any }> {
+): Promise<{ instruction: any; createAtaInstructions: any[] }> {
+  const [configPda] = PublicKey.findProgramAddressSync([Buffer.from('config')], programId);
   const [counterPda] = PublicKey.findProgramAddressSync(
     [Buffer.from('vault_counter'), creator.toBuffer()],
     programId
   );
 
-  const usdcMint = new PublicKey(USDC_MINT);
-  const vaultTokenAccount = await getAssociatedTokenAddress(usdcMint, vault, true);
-  const beneficiaryUsdcAta = await getAssociatedTokenAddress(usdcMint, beneficiary);
+  // Optional accounts: the program ID stands in for "none"
+  const ata = async (owner: PublicKey, allowOwnerOffCurve = false) =>
+    tokenProgram ? getAssociatedTokenAddress(mint, owner, allowOwnerOffCurve, tokenProgram) : programId;
+  const vaultTokenAccount = await ata(vault, true);
+  const beneficiaryUsdcAta = await ata(beneficiary);
+  const treasuryUsdcAta = await ata(treasury);
+  // Additional payees are paid into their token accounts, or their wallets for SOL vaults
+  const additionalPayeeAccounts = tokenProgram
+    ? await Promise.all(additionalBeneficiaries.map((payee) => ata(payee)))
+    : additionalBeneficiaries;
 
   console.log(`[Executor] 🔍 DEBUG: Vault addresses`);
   console.log(`  - Vault PDA: ${vault.toBase58()}`);
@@ -50,26 +134,27 @@ async function buildReleaseInstruction(
   console.log(`  - Creator: ${creator.toBase58()}`);
   console.log(`  - Beneficiary: ${beneficiary.toBase58()}`);
 
-  // Discriminator for release instruction (8 bytes)
-  const discriminator = getDiscriminator('release');
+  // Discriminator for release instruction (8 bytes); vesting vaults take the same accounts
+  const discriminator = getDiscriminator(scheduled ? 'release_tranche' : 'release');
 
   // Instruction data (just discriminator, no args)
   const data = discriminator;
 
+  const token = tokenProgram !== null;
+  const hasAssets = !vaultAssets.equals(programId);
+
   // Account keys
   const keys = [
+    { pubkey: configPda, isSigner: false, isWritable: false },
     { pubkey: vault, isSigner: false, isWritable: true },
     { pubkey: counterPda, isSigner: false, isWritable: false },
-    { pubkey: vaultTokenAccount, isSigner: false, isWritable: true },
-    { pubkey: usdcMint, isSigner: false, isWritable: false },
-    { pubkey: beneficiaryUsdcAta, isSigner: false, isWritable: true },
-    { pubkey: beneficiary, isSigner: false, isWritable: false },
+    { pubkey: vaultTokenAccount, isSigner: false, isWritable: token }, // Token vaults only
+    { pubkey: token ? mint : programId, isSigner: false, isWritable: false }, // Token vaults only
+    { pubkey: beneficiaryUsdcAta, isSigner: false, isWritable: token }, // Token vaults only
+    { pubkey: beneficiary, isSigner: false, isWritable: true },
+    { pubkey: treasuryUsdcAta, isSigner: false, isWritable: token }, // Token vaults only
     { pubkey: payer, isSigner: true, isWritable: true },
-    {
-      pubkey: new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'),
-      isSigner: false,
-      isWritable: false,
-    }, // Token Program
+    { pubkey: tokenProgram ?? programId, isSigner: false, isWritable: false }, // Token Program (the mint's owner)
     {
       pubkey: new PublicKey('ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL'),
       isSigner: false,
@@ -80,6 +165,14 @@ async function buildReleaseInstruction(
       isSigner: false,
       isWritable: false,
     }, // System Program
+    { pubkey: supportedMint, isSigner: false, isWritable: false }, // Optional: program ID = none
+    { pubkey: vaultAssets, isSigner: false, isWritable: hasAssets }, // Optional: program ID = none
+    { pubkey: hasAssets ? creator : programId, isSigner: false, isWritable: hasAssets }, // Refunded the asset accounts' rent
+    { pubkey: token ? programId : treasury, isSigner: false, isWritable: !token }, // SOL vaults: fee in lamports
+    // Remaining accounts: split vaults pay each additional beneficiary, in share order,
+    // then registered assets go to the primary beneficiary
+    ...additionalPayeeAccounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })),
+    ...assetAccounts,
   ];
 
   return {
@@ -88,15 +181,12 @@ async function buildReleaseInstruction(
       programId,
       data,
     },
-    accounts: {
-      vault,
-      counter: counterPda,
-      vaultTokenAccount,
-      usdcMint,
-      beneficiaryUsdcAta,
-      beneficiary,
-      payer,
-    },
+    // release only creates the primary beneficiary's ATA
+    createAtaInstructions: tokenProgram
+      ? additionalBeneficiaries.map((payee, i) =>
+          createAssociatedTokenAccountIdempotentInstruction(payer, additionalPayeeAccounts[i], payee, mint, tokenProgram)
+        )
+      : [],
   };
 }
 
@@ -106,11 +196,15 @@ async function buildReleaseInstruction(
 async function buildCloseInstruction(
   vault: PublicKey,
   creator: PublicKey,
+  mint: PublicKey,
+  tokenProgram: PublicKey | null, // null for SOL vaults, which have no token account
   signer: PublicKey,
   programId: PublicKey
 ): Promise<{ instruction: any }> {
-  const usdcMint = new PublicKey(USDC_MINT);
-  const vaultTokenAccount = await getAssociatedTokenAddress(usdcMint, vault, true);
+  // Optional accounts: the program ID stands in for "none"
+  const vaultTokenAccount = tokenProgram
+    ? await getAssociatedTokenAddress(mint, vault, true, tokenProgram)
+    : programId;
 
   // Discriminator for close_vault instruction (8 bytes)
   const discriminator = getDiscriminator('close_vault');
@@ -121,14 +215,10 @@ async function buildCloseInstruction(
   // Account keys
   const keys = [
     { pubkey: vault, isSigner: false, isWritable: true },
-    { pubkey: vaultTokenAccount, isSigner: false, isWritable: true },
+    { pubkey: vaultTokenAccount, isSigner: false, isWritable: tokenProgram !== null },
     { pubkey: creator, isSigner: false, isWritable: true }, // Rent goes here
     { pubkey: signer, isSigner: true, isWritable: false }, // Anyone can sign
-    {
-      pubkey: new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA'),
-      isSigner: false,
-      isWritable: false,
-    }, // Token Program
+    { pubkey: tokenProgram ?? programId, isSigner: false, isWritable: false }, // Token Program (the mint's owner)
   ];
 
   return {
@@ -147,16 +237,20 @@ async function executeClose(
   connection: Connection,
   keeper: Keypair,
   vault: PublicKey,
-  creator: PublicKey
+  creator: PublicKey,
+  mint: PublicKey,
+  tokenProgram: PublicKey | null
 ): Promise<{ success: boolean; signature?: string; error?: string }> {
   try {
-    const programId = new PublicKey(PROGRAM_ID);
+    const programId = PROGRAM_ID;
 
     console.log(`[Executor] Building close transaction for vault ${vault.toBase58().slice(0, 8)}...`);
 
     const { instruction } = await buildCloseInstruction(
       vault,
       creator,
+      mint,
+      tokenProgram,
       keeper.publicKey,
       programId
     );
@@ -190,19 +284,33 @@ export async function executeRelease(
     const vault = new PublicKey(vaultData.vaultPda);
     const creator = new PublicKey(vaultData.creator);
     const beneficiary = new PublicKey(vaultData.beneficiary);
-    const programId = new PublicKey(PROGRAM_ID);
+    const mint = new PublicKey(vaultData.mint);
+    // SOL vaults hold lamports in the vault PDA, so there is no token program
+    const tokenProgram = vaultData.sol ? null : await fetchTokenProgram(connection, mint);
+    const programId = PROGRAM_ID;
+    const treasury = await fetchTreasury(connection, programId);
+    const supportedMint = await findSupportedMint(connection, mint, programId);
+    const additionalBeneficiaries = vaultData.additionalBeneficiaries.map((payee) => new PublicKey(payee));
 
     console.log(`[Executor] Building release transaction for vault ${vaultData.vaultPda.slice(0, 8)}...`);
 
-    const { instruction } = await buildReleaseInstruction(
+    const { vaultAssets, assetAccounts } = await findVaultAssets(connection, vault, beneficiary, programId);
+    const { instruction, createAtaInstructions } = await buildReleaseInstruction(
       vault,
       creator,
       beneficiary,
       keeper.publicKey,
+      treasury,
+      mint,
+      tokenProgram,
+      supportedMint,
+      additionalBeneficiaries,
+      vaultData.scheduled,
+      vaultAssets,
+      assetAccounts,
       programId
     );
-
-    const transaction = new Transaction().add(instruction);
+    const transaction = new Transaction().add(...createAtaInstructions, instruction);
 
     console.log(`[Executor] Sending release transaction...`);
     const releaseSignature = await sendAndConfirmTransaction(connection, transaction, [keeper], {
@@ -210,13 +318,19 @@ export async function executeRelease(
     });
 
     console.log(`[Executor] ✅ Release successful! Signature: ${releaseSignature}`);
-    console.log(
-      `[Executor] Released ${vaultData.amountLocked / 1_000_000} USDC to ${beneficiary.toBase58().slice(0, 8)}...`
-    );
+    const released = await formatAmount(connection, vaultData.mint, vaultData.releaseDue);
+    console.log(`[Executor] Released ${released} to ${beneficiary.toBase58().slice(0, 8)}...`);
+
+    // A vesting vault stays open until its last tranche is paid
+    if (vaultData.releaseDue < vaultData.amountLocked) {
+      const stillVesting = await formatAmount(connection, vaultData.mint, vaultData.amountLocked - vaultData.releaseDue);
+      console.log(`[Executor] Tranche paid; ${stillVesting} still vesting`);
+      return { success: true, signature: releaseSignature };
+    }
 
     // Now close the vault to reclaim rent for creator
     console.log(`[Executor] Closing vault to reclaim rent...`);
-    const closeResult = await executeClose(connection, keeper, vault, creator);
+    const closeResult = await executeClose(connection, keeper, vault, creator, mint, tokenProgram);
 
     if (closeResult.success) {
       console.log(`[Executor] 🎉 Vault fully processed: released + closed`);
@@ -232,6 +346,42 @@ export async function executeRelease(
   }
 }
 
+/**
+ * Migrate v1 vaults to the current layout (the keeper pays the extra rent) so the next scan can read them
+ */
+export async function executeMigrations(
+  connection: Connection,
+  keeper: Keypair,
+  legacyVaults: string[]
+): Promise<{ successful: number; failed: number }> {
+  let successful = 0;
+  let failed = 0;
+
+  for (const vaultPda of legacyVaults) {
+    try {
+      const transaction = new Transaction().add({
+        keys: [
+          { pubkey: new PublicKey(vaultPda), isSigner: false, isWritable: true },
+          { pubkey: keeper.publicKey, isSigner: true, isWritable: true },
+          { pubkey: new PublicKey('11111111111111111111111111111111'), isSigner: false, isWritable: false },
+        ],
+        programId: PROGRAM_ID,
+        data: getDiscriminator('migrate_vault'),
+      });
+      const signature = await sendAndConfirmTransaction(connection, transaction, [keeper], {
+        commitment: 'confirmed',
+      });
+      console.log(`[Executor] ✅ Migrated vault ${vaultPda.slice(0, 8)}... Signature: ${signature}`);
+      successful++;
+    } catch (error: any) {
+      console.error(`[Executor] ❌ Migration of ${vaultPda.slice(0, 8)}... failed:`, error.message || error);
+      failed++;
+    }
+  }
+
+  return { successful, failed };
+}
+
 /**
  * Execute releases for multiple vaults (with error handling)
  */
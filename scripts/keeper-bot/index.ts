@@ -14,7 +14,7 @@
 import { Connection, Keypair } from '@solana/web3.js';
 import bs58 from 'bs58';
 import { scanForReleaseableVaults } from './scanner';
-import { executeReleases } from './executor';
+import { executeMigrations, executeReleases } from './executor';
 
 // Configuration
 const KEEPER_PRIVATE_KEY = process.env.KEEPER_PRIVATE_KEY;
@@ -67,7 +67,13 @@ async function pollAndRelease() {
 
   try {
     // Scan for releaseable vaults
-    const releaseableVaults = await scanForReleaseableVaults(connection);
+    const { releaseableVaults, legacyVaults } = await scanForReleaseableVaults(connection);
+
+    // v1 vaults are unreadable until migrated; they are picked up by the next scan
+    if (legacyVaults.length > 0) {
+      console.log(`[Keeper] Migrating ${legacyVaults.length} v1 vault(s)...`);
+      await executeMigrations(connection, keeper, legacyVaults);
+    }
 
     if (releaseableVaults.length === 0) {
       console.log('[Keeper] No vaults ready for release');
@@ -0,0 +1,35 @@
+/**
+ * Mint helpers - vault amounts are raw units of the vault's mint
+ */
+
+import { Connection, PublicKey } from '@solana/web3.js';
+
+const decimalsByMint = new Map<string, number>();
+
+/**
+ * Read a mint's decimals (SOL vaults hold the native mint, 9 decimals)
+ */
+export async function fetchMintDecimals(connection: Connection, mint: PublicKey): Promise<number> {
+  const key = mint.toBase58();
+  const cached = decimalsByMint.get(key);
+  if (cached !== undefined) {
+    return cached;
+  }
+
+  const info = await connection.getAccountInfo(mint);
+  if (!info) {
+    throw new Error(`Mint ${key} not found`);
+  }
+  // SPL Token and Token-2022 mints: mint_authority(36) + supply(8), then decimals
+  const decimals = info.data[44];
+  decimalsByMint.set(key, decimals);
+  return decimals;
+}
+
+/**
+ * A raw amount in the mint's decimals, followed by the mint address
+ */
+export async function formatAmount(connection: Connection, mint: string, amount: number): Promise<string> {
+  const decimals = await fetchMintDecimals(connection, new PublicKey(mint));
+  return `${amount / 10 ** decimals} of mint ${mint}`;
+}
@@ -0,0 +1,24 @@
+/**
+ * Program client - the keepr_vault IDL written by `anchor build`
+ */
+
+import { Program, type Idl, type Provider } from '@coral-xyz/anchor';
+import { Connection, PublicKey } from '@solana/web3.js';
+import * as fs from 'fs';
+import * as path from 'path';
+
+const IDL_PATH = path.join(__dirname, '../../target/idl/keepr_vault.json');
+
+const idl: Idl = JSON.parse(fs.readFileSync(IDL_PATH, 'utf-8'));
+
+/**
+ * The IDL address is the program's declare_id
+ */
+export const PROGRAM_ID = new PublicKey(idl.address);
+
+/**
+ * Read-only program client (the keeper signs its transactions itself)
+ */
+export function keeprProgram(connection: Connection): Program {
+  return new Program(idl, { connection } as Provider);
+}
@@ -3,71 +3,105 @@
  */
 
 import { Connection, PublicKey } from '@solana/web3.js';
+import { formatAmount } from './mints';
+import { keeprProgram } from './program';
 import type { VaultData, ReleaseableVault } from './types';
 
-const PROGRAM_ID = '74v7NZh7A6SH9DmKZRC4tFUwaLvq19KfD1NGni62XQJK';
-const VAULT_SIZE = 237; // 8 discriminator + 229 struct data
+const LEGACY_VAULT_SIZE = 237; // v1: 8 discriminator + 229, before schema_version
 
 /**
- * Scan all vaults from the program
+ * Scan all vaults from the program; v1 accounts that still need migrate_vault are returned separately
  */
-export async function scanAllVaults(connection: Connection): Promise<VaultData[]> {
-  const programId = new PublicKey(PROGRAM_ID);
+export async function scanAllVaults(
+  connection: Connection
+): Promise<{ vaults: VaultData[]; legacyVaults: string[] }> {
+  const program = keeprProgram(connection);
 
   console.log('[Scanner] Fetching all vault accounts...');
-  const accounts = await connection.getProgramAccounts(programId, {
-    filters: [
-      {
-        dataSize: VAULT_SIZE,
-      },
-    ],
+  const accounts = await connection.getProgramAccounts(program.programId, {
+    filters: [{ memcmp: program.coder.accounts.memcmp('vault') }],
   });
 
   console.log(`[Scanner] Found ${accounts.length} vault accounts on-chain`);
 
   const vaults: VaultData[] = [];
+  const legacyVaults: string[] = [];
 
   for (const account of accounts) {
+    if (account.account.data.length === LEGACY_VAULT_SIZE) {
+      legacyVaults.push(account.pubkey.toBase58());
+      continue;
+    }
+
     try {
-      const data = account.account.data;
-
-      // Parse vault data
-      const creator = new PublicKey(data.slice(8, 40));
-      const beneficiary = new PublicKey(data.slice(40, 72));
-      const amountLockedBuf = data.slice(136, 144);
-      const unlockUnixBuf = data.slice(144, 152);
-      const released = data[152] === 1;
-      const cancelled = data[153] === 1;
-      const gracePeriodBuf = data.slice(204, 208);
-
-      const amountLocked = Number(
-        new DataView(amountLockedBuf.buffer, amountLockedBuf.byteOffset, 8).getBigUint64(0, true)
-      );
-      const unlockUnix = Number(
-        new DataView(unlockUnixBuf.buffer, unlockUnixBuf.byteOffset, 8).getBigInt64(0, true)
-      );
-      const gracePeriodSeconds = new DataView(
-        gracePeriodBuf.buffer,
-        gracePeriodBuf.byteOffset,
-        4
-      ).getUint32(0, true);
+      const vault = program.coder.accounts.decode('vault', account.account.data);
+      const now = Math.floor(Date.now() / 1000);
+
+      // Split vaults list every payee in shares; the first is always `beneficiary`
+      const additionalBeneficiaries: string[] = vault.beneficiaryShares
+        .slice(1, vault.beneficiaryCount)
+        .map((share: { beneficiary: PublicKey }) => share.beneficiary.toBase58());
+
+      // A pending update_beneficiary whose cooldown has elapsed is applied by release
+      const effectiveAt = vault.beneficiaryEffectiveAt.toNumber();
+      const beneficiary: PublicKey =
+        effectiveAt !== 0 && now >= effectiveAt ? vault.pendingBeneficiary : vault.beneficiary;
+
+      // Claim-mode vaults without a backup are never released; with a backup, release opens the claim window
+      const claimOnly = 'claim' in vault.payoutMode && vault.backupBeneficiary.equals(PublicKey.default);
+
+      const amountLocked = vault.amountLocked.toNumber();
+      const unlockUnix = vault.unlockUnix.toNumber();
+      const gracePeriodSeconds: number = vault.gracePeriodSeconds;
+      const { cliffSeconds, intervalSeconds, tranches, upfrontBps } = vault.releaseSchedule;
+
+      // Vesting vaults pay out tranche by tranche (same formula as Vault::vested_amount)
+      let releaseDue = amountLocked;
+      if (tranches > 0) {
+        const amountReleased = BigInt(vault.amountReleased.toString());
+        const total = BigInt(amountLocked) + amountReleased;
+        const start = unlockUnix + gracePeriodSeconds + cliffSeconds;
+
+        let vested = BigInt(0);
+        if (now >= start) {
+          const elapsed = Math.min(tranches, Math.floor((now - start) / intervalSeconds));
+          const upfront = (total * BigInt(upfrontBps)) / BigInt(10_000);
+          vested =
+            elapsed === tranches
+              ? total
+              : upfront + ((total - upfront) * BigInt(elapsed)) / BigInt(tranches);
+        }
+        releaseDue = vested > amountReleased ? Number(vested - amountReleased) : 0;
+      }
 
       vaults.push({
         vaultPda: account.pubkey.toBase58(),
-        creator: creator.toBase58(),
+        creator: vault.creator.toBase58(),
         beneficiary: beneficiary.toBase58(),
+        mint: vault.mint.toBase58(),
+        additionalBeneficiaries,
         amountLocked,
         unlockUnix,
         gracePeriodSeconds,
-        released,
-        cancelled,
+        released: vault.released,
+        cancelled: vault.cancelled,
+        claimPending: !vault.claimDeadline.isZero(),
+        claimOnly,
+        scheduled: tranches > 0,
+        sol: 'sol' in vault.kind,
+        assetCount: vault.assetCount,
+        releaseDue,
       });
     } catch (error) {
       console.error(`[Scanner] Error parsing vault ${account.pubkey.toBase58()}:`, error);
     }
   }
 
-  return vaults;
+  if (legacyVaults.length > 0) {
+    console.log(`[Scanner] ${legacyVaults.length} v1 vault(s) need migrate_vault before they can be read`);
+  }
+
+  return { vaults, legacyVaults };
 }
 
 /**
@@ -81,8 +115,18 @@ export function filterReleaseableVaults(vaults: VaultData[], currentTime: number
         return false;
       }
 
-      // Must have funds
-      if (vault.amountLocked === 0) {
+      // Release already ran on a backup vault; only the beneficiary (or backup) can claim now
+      if (vault.claimPending) {
+        return false;
+      }
+
+      // Claim payout mode: the beneficiary pulls the funds with claim
+      if (vault.claimOnly) {
+        return false;
+      }
+
+      // Must have funds (for vesting vaults: a tranche that has vested) or registered assets
+      if (vault.releaseDue === 0 && vault.assetCount === 0) {
         return false;
       }
 
@@ -101,25 +145,24 @@ export function filterReleaseableVaults(vaults: VaultData[], currentTime: number
 }
 
 /**
- * Main scanning function - returns vaults ready for release
+ * Main scanning function - returns vaults ready for release and v1 vaults to migrate first
  */
 export async function scanForReleaseableVaults(
   connection: Connection
-): Promise<ReleaseableVault[]> {
-  const allVaults = await scanAllVaults(connection);
+): Promise<{ releaseableVaults: ReleaseableVault[]; legacyVaults: string[] }> {
+  const { vaults: allVaults, legacyVaults } = await scanAllVaults(connection);
   const currentTime = Math.floor(Date.now() / 1000);
   const releaseableVaults = filterReleaseableVaults(allVaults, currentTime);
 
   console.log(`[Scanner] Found ${releaseableVaults.length} vaults eligible for release`);
 
   if (releaseableVaults.length > 0) {
-    releaseableVaults.forEach((vault) => {
+    for (const vault of releaseableVaults) {
       const overdue = currentTime - vault.gracePeriodEnd;
-      console.log(
-        `  - ${vault.vaultPda.slice(0, 8)}... | Amount: ${vault.amountLocked / 1_000_000} USDC | Overdue: ${overdue}s`
-      );
-    });
+      const amount = await formatAmount(connection, vault.mint, vault.amountLocked);
+      console.log(`  - ${vault.vaultPda.slice(0, 8)}... | Amount: ${amount} | Overdue: ${overdue}s`);
+    }
   }
 
-  return releaseableVaults;
+  return { releaseableVaults, legacyVaults };
 }
@@ -6,11 +6,19 @@ export interface VaultData {
   vaultPda: string;
   creator: string;
   beneficiary: string;
+  mint: string; // Token the vault holds (the config mint or a supported mint)
+  additionalBeneficiaries: string[]; // Split vaults: payees 2..n in share order
   amountLocked: number;
   unlockUnix: number;
   gracePeriodSeconds: number;
   released: boolean;
   cancelled: boolean;
+  claimPending: boolean; // Backup vaults: release opened the claim window, beneficiary must claim
+  claimOnly: boolean; // Claim payout mode without a backup: release is rejected
+  scheduled: boolean; // Vesting vault: paid out by release_tranche
+  sol: boolean; // SOL vault: lamports in the vault PDA, released to the payees' wallets
+  assetCount: number; // Registered assets (deposit_token) that release hands to the beneficiary
+  releaseDue: number; // Gross the next release pays (vested-but-unpaid for vesting vaults)
 }
 
 export interface ReleaseableVault extends VaultData {
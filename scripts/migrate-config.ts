@@ -2,6 +2,7 @@ import {
   Connection,
   Keypair,
   PublicKey,
+  SystemProgram,
   TransactionInstruction,
   Transaction,
   sendAndConfirmTransaction,
@@ -13,8 +14,7 @@ import * as crypto from "crypto";
 // Program ID
 const PROGRAM_ID = new PublicKey("74v7NZh7A6SH9DmKZRC4tFUwaLvq19KfD1NGni62XQJK");
 
-// Calculate instruction discriminator for close_config
-// Anchor uses: sha256("global:close_config").slice(0, 8)
+// Anchor uses: sha256("global:<name>").slice(0, 8)
 function getInstructionDiscriminator(name: string): Buffer {
   const hash = crypto.createHash("sha256");
   hash.update(`global:${name}`);
@@ -41,28 +41,35 @@ async function main() {
 
   console.log("Config PDA:", configPda.toBase58());
 
-  // Check if config exists
   const configAccount = await connection.getAccountInfo(configPda);
-
   if (!configAccount) {
-    console.log("✓ Config account does not exist");
-    return;
+    console.log("✗ Config account does not exist - run init-config instead");
+    process.exit(1);
   }
 
   console.log("✓ Config account exists (", configAccount.data.length, "bytes)");
-  console.log("\nClosing config account...");
 
-  // Build close_config instruction
-  const discriminator = getInstructionDiscriminator("close_config");
-  console.log("Instruction discriminator:", Array.from(discriminator));
+  // v0 layout: discriminator(8) + admin(32) + usdc_mint(32) + max_lock(8) + paused(1) + admin_test_wallets(4 + 32*n)
+  // The test wallets don't carry over; re-add them with set_wallet_flags after migrating
+  if (configAccount.data.length === 437) {
+    const walletCount = configAccount.data.readUInt32LE(81);
+    for (let i = 0; i < walletCount; i++) {
+      const wallet = new PublicKey(configAccount.data.subarray(85 + 32 * i, 117 + 32 * i));
+      console.log("  Test wallet to re-add with set_wallet_flags:", wallet.toBase58());
+    }
+  }
+  console.log("\nMigrating config account in place...");
 
+  // Build migrate_config instruction (no args)
   const instruction = new TransactionInstruction({
     keys: [
       { pubkey: configPda, isSigner: false, isWritable: true },
-      { pubkey: adminKeypair.publicKey, isSigner: true, isWritable: true },
+      { pubkey: adminKeypair.publicKey, isSigner: true, isWritable: true }, // admin, gets the freed rent
+      { pubkey: adminKeypair.publicKey, isSigner: true, isWritable: true }, // payer
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ],
     programId: PROGRAM_ID,
-    data: discriminator,
+    data: getInstructionDiscriminator("migrate_config"),
   });
 
   const transaction = new Transaction().add(instruction);
@@ -72,18 +79,13 @@ async function main() {
       adminKeypair,
     ]);
 
-    console.log("✓ Config closed! Signature:", signature);
+    console.log("✓ Config migrated! Signature:", signature);
     console.log("  View: https://solscan.io/tx/" + signature + "?cluster=devnet");
 
-    // Verify it's closed
     const verifyAccount = await connection.getAccountInfo(configPda);
-    if (!verifyAccount) {
-      console.log("✓ Config account successfully closed");
-    } else {
-      console.log("⚠ Config account still exists with", verifyAccount.data.length, "bytes");
-    }
+    console.log("✓ Config account now", verifyAccount?.data.length, "bytes, version", verifyAccount?.data[40]);
   } catch (err: any) {
-    console.error("✗ Error closing config:", err.message);
+    console.error("✗ Error migrating config:", err.message);
     if (err.logs) {
       console.error("Program logs:");
       err.logs.forEach((log: string) => console.error("  ", log));
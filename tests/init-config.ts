@@ -27,6 +27,7 @@ describe("Initialize Config", () => {
 
   const USDC_MINT = new PublicKey("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
   const MAX_LOCK_PER_VAULT = new BN(500_000_000); // 500 USDC
+  const CONFIG_TIMELOCK_SECONDS = 48 * 60 * 60; // 48 hours
 
   it("Initialize config", async () => {
     // Derive config PDA
@@ -40,7 +41,7 @@ describe("Initialize Config", () => {
 
     try {
       const tx = await program.methods
-        .initConfig(USDC_MINT, MAX_LOCK_PER_VAULT, false)
+        .initConfig(USDC_MINT, MAX_LOCK_PER_VAULT, 0, provider.wallet.publicKey, CONFIG_TIMELOCK_SECONDS)
         .accounts({
           config: configPda,
           admin: provider.wallet.publicKey,
@@ -22,11 +22,15 @@ describe("Update Config", () => {
 
   const NEW_USDC_MINT = new PublicKey("BTYDiUpZuxzswKhbg8C8sJcYNjua4D7186rU2fzxUjjt");
 
-  it("Update config with new USDC mint", async () => {
+  it("Queue config change with new USDC mint", async () => {
     const [configPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("config")],
       programId
     );
+    const [configChangePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config_change")],
+      programId
+    );
 
     console.log("Config PDA:", configPda.toBase58());
     console.log("Admin:", provider.wallet.publicKey.toBase58());
@@ -34,14 +38,20 @@ describe("Update Config", () => {
 
     try {
       const tx = await program.methods
-        .updateConfig(NEW_USDC_MINT, null, null)
+        .queueConfigChange(NEW_USDC_MINT, null, null, null, null, null)
         .accounts({
           config: configPda,
+          configChange: configChangePda,
           admin: provider.wallet.publicKey,
+          payer: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
 
-      console.log("✅ Config updated!");
+      const change = await (program.account as any).pendingConfigChange.fetch(configChangePda);
+      console.log("✅ Config change queued!");
+      console.log("Executable after:", new Date(change.eta.toNumber() * 1000).toISOString());
+      console.log("Run execute_config_change once the timelock has elapsed.");
       console.log("Transaction signature:", tx);
       console.log("Explorer:", `https://explorer.solana.com/tx/${tx}?cluster=devnet`);
     } catch (error) {
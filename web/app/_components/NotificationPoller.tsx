@@ -6,7 +6,8 @@ import { PublicKey } from '@solana/web3.js';
 import { getVaultCache } from '../_lib/storage';
 import { getVaultStatus, VaultData } from '../_lib/vault-status';
 import { useNotifications } from '../_contexts/NotificationContext';
-import { connection, PROGRAM_ID } from '../_lib/solana';
+import { connection } from '../_lib/solana';
+import { decodeVault, vaultProgram, VAULT_FILTER } from '../_lib/vault-account';
 
 interface NotificationSettings {
   enabled: boolean;
@@ -74,7 +75,7 @@ export function NotificationPoller() {
       try {
         const userAddress = publicKey.toBase58();
         const now = Math.floor(Date.now() / 1000);
-        const programId = new PublicKey(PROGRAM_ID);
+        const programId = vaultProgram.programId;
 
         console.log('[NotificationPoller] Fetching user vaults from blockchain...');
 
@@ -83,14 +84,14 @@ export function NotificationPoller() {
           // Vaults where user is creator (offset 8 = creator pubkey, 32 bytes)
           connection.getProgramAccounts(programId, {
             filters: [
-              { dataSize: 237 }, // Current vault schema size
+              VAULT_FILTER,
               { memcmp: { offset: 8, bytes: publicKey.toBase58() } },
             ],
           }),
           // Vaults where user is beneficiary (offset 40 = beneficiary pubkey, 32 bytes)
           connection.getProgramAccounts(programId, {
             filters: [
-              { dataSize: 237 },
+              VAULT_FILTER,
               { memcmp: { offset: 40, bytes: publicKey.toBase58() } },
             ],
           }),
@@ -114,20 +115,11 @@ export function NotificationPoller() {
 
         const deserializeVault = (pubkey: PublicKey, data: Buffer): BlockchainVault | null => {
           try {
-            if (data.length !== 237) return null;
+            const vault = decodeVault(data);
+            if (!vault) return null;
 
-            const creator = new PublicKey(data.slice(8, 40));
-            const beneficiary = new PublicKey(data.slice(40, 72));
-            const unlockUnixBuf = data.slice(144, 152);
-            const released = data[152] === 1;
-            const cancelled = data[153] === 1;
-            const notificationWindowBuf = data.slice(200, 204);
-            const gracePeriodBuf = data.slice(204, 208);
-            const lastCheckinBuf = data.slice(208, 216);
-            const checkinPeriodBuf = data.slice(233, 237);
-
-            const unlockUnix = Number(new DataView(unlockUnixBuf.buffer, unlockUnixBuf.byteOffset, 8).getBigInt64(0, true));
-            const lastCheckinUnix = Number(new DataView(lastCheckinBuf.buffer, lastCheckinBuf.byteOffset, 8).getBigInt64(0, true));
+            const unlockUnix = vault.unlockUnix.toNumber();
+            const lastCheckinUnix = vault.lastCheckinUnix.toNumber();
 
             // DEBUG: Log deserialized values for newly created vaults
             const now = Math.floor(Date.now() / 1000);
@@ -139,24 +131,22 @@ export function NotificationPoller() {
                 unlockDate: new Date(unlockUnix * 1000).toISOString(),
                 lastCheckinUnix,
                 lastCheckinDate: lastCheckinUnix > 0 ? new Date(lastCheckinUnix * 1000).toISOString() : 'never (0)',
-                released,
-                cancelled,
-                rawLastCheckinBytes: Array.from(lastCheckinBuf),
-                rawUnlockBytes: Array.from(unlockUnixBuf),
+                released: vault.released,
+                cancelled: vault.cancelled,
               });
             }
 
             return {
               vaultPda: pubkey.toBase58(),
-              creator: creator.toBase58(),
-              beneficiary: beneficiary.toBase58(),
+              creator: vault.creator.toBase58(),
+              beneficiary: vault.beneficiary.toBase58(),
               unlockUnix,
-              released,
-              cancelled,
-              notificationWindowSeconds: new DataView(notificationWindowBuf.buffer, notificationWindowBuf.byteOffset, 4).getUint32(0, true),
-              gracePeriodSeconds: new DataView(gracePeriodBuf.buffer, gracePeriodBuf.byteOffset, 4).getUint32(0, true),
+              released: vault.released,
+              cancelled: vault.cancelled,
+              notificationWindowSeconds: vault.notificationWindowSeconds,
+              gracePeriodSeconds: vault.gracePeriodSeconds,
               lastCheckinUnix,
-              checkinPeriodSeconds: new DataView(checkinPeriodBuf.buffer, checkinPeriodBuf.byteOffset, 4).getUint32(0, true),
+              checkinPeriodSeconds: vault.checkinPeriodSeconds,
             };
           } catch (error) {
             console.error('Error deserializing vault:', error);
@@ -4,8 +4,8 @@ import { useState, useEffect } from 'react';
 import { useWallet } from '@solana/wallet-adapter-react';
 import { formatUSDC } from '../_lib/format';
 import { useNotifications } from '../_contexts/NotificationContext';
-import { Connection, PublicKey } from '@solana/web3.js';
-import { connection, PROGRAM_ID } from '../_lib/solana';
+import { connection } from '../_lib/solana';
+import { decodeVault, vaultProgram, VAULT_FILTER } from '../_lib/vault-account';
 
 interface TopBarProps {
   onMenuClick: () => void;
@@ -30,27 +30,21 @@ export function TopBar({ onMenuClick, onNotificationClick }: TopBarProps) {
     if (!publicKey) return;
 
     try {
-      const programId = new PublicKey(PROGRAM_ID);
-      
       // Fetch all vaults where user is creator
-      const accounts = await connection.getProgramAccounts(programId, {
+      const accounts = await connection.getProgramAccounts(vaultProgram.programId, {
         filters: [
-          { dataSize: 237 },
+          VAULT_FILTER,
           { memcmp: { offset: 8, bytes: publicKey.toBase58() } },
         ],
       });
 
       let total = 0;
       for (const { account } of accounts) {
-        const data = account.data;
-        const released = data[152] === 1;
-        const cancelled = data[153] === 1;
+        const vault = decodeVault(account.data);
 
         // Only count active vaults
-        if (!released && !cancelled) {
-          const amountLockedBuf = data.slice(136, 144);
-          const amountLocked = Number(new DataView(amountLockedBuf.buffer, amountLockedBuf.byteOffset, 8).getBigUint64(0, true));
-          total += amountLocked;
+        if (vault && !vault.released && !vault.cancelled) {
+          total += vault.amountLocked.toNumber();
         }
       }
 
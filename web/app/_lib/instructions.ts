@@ -1,10 +1,20 @@
 import {
+  AccountMeta,
+  Connection,
+  Ed25519Program,
   PublicKey,
   SystemProgram,
   TransactionInstruction,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   SYSVAR_RENT_PUBKEY,
 } from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  getAssociatedTokenAddress,
+} from '@solana/spl-token';
+import { decodeVault } from './vault-account';
 
 /**
  * Compute Anchor instruction discriminator
@@ -54,6 +64,16 @@ function encodeU32(value: number): Buffer {
   return Buffer.from(buffer);
 }
 
+/**
+ * Encode u16 to Buffer (2 bytes little-endian)
+ */
+function encodeU16(value: number): Buffer {
+  const buffer = new ArrayBuffer(2);
+  const view = new DataView(buffer);
+  view.setUint16(0, value, true); // true = little-endian
+  return Buffer.from(buffer);
+}
+
 /**
  * Encode u8 to Buffer (1 byte)
  */
@@ -61,6 +81,33 @@ function encodeU8(value: number): Buffer {
   return Buffer.from([value]);
 }
 
+/**
+ * Vesting payout chosen at creation (matches Rust ReleaseSchedule)
+ * upfrontBps vests at trigger + cliff, then `tranches` equal tranches every interval
+ */
+export interface ReleaseSchedule {
+  cliffSeconds: number;
+  intervalSeconds: number;
+  tranches: number;
+  upfrontBps: number;
+}
+
+/**
+ * Encode Option<ReleaseSchedule>: 0 = None, 1 + cliff(u32) + interval(u32) + tranches(u16) + upfront_bps(u16) = Some
+ */
+function encodeReleaseSchedule(schedule?: ReleaseSchedule): Buffer {
+  if (!schedule) {
+    return encodeU8(0);
+  }
+  return Buffer.concat([
+    encodeU8(1),
+    encodeU32(schedule.cliffSeconds),
+    encodeU32(schedule.intervalSeconds),
+    encodeU16(schedule.tranches),
+    encodeU16(schedule.upfrontBps),
+  ]);
+}
+
 /**
  * VaultTier enum (matches Rust program)
  */
@@ -113,7 +160,9 @@ export async function createVaultInstruction(params: {
   counter: PublicKey;
   vault: PublicKey;
   vaultTokenAccount: PublicKey;
-  usdcMint: PublicKey;
+  mint: PublicKey;
+  creatorUsdcAta: PublicKey;  // Pays the creation fee
+  treasuryUsdcAta: PublicKey;  // Receives the creation fee
   creator: PublicKey;
   beneficiary: PublicKey;
   checkinPeriodSeconds: number; // NEW: replaces unlockUnix - rolling deadline period
@@ -121,7 +170,11 @@ export async function createVaultInstruction(params: {
   notificationWindowSeconds: number;
   gracePeriodSeconds: number;
   tier: VaultTier; // NEW: pricing tier
-  creationFeePaid: number | bigint; // NEW: fee paid for auditing
+  creationFeePaid: number | bigint; // Must match the on-chain fee schedule for the tier
+  releaseSchedule?: ReleaseSchedule; // Vesting payout via release_tranche; omit for a single release
+  walletFlags?: PublicKey; // Creator's WalletFlags PDA, if one exists (see findWalletFlags)
+  supportedMint?: PublicKey; // Required when `mint` isn't the config mint (see findSupportedMint)
+  tokenProgram?: PublicKey; // The mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
   programId: PublicKey;
 }): Promise<TransactionInstruction> {
   const discriminator = await getCachedDiscriminator('create_vault');
@@ -136,9 +189,10 @@ export async function createVaultInstruction(params: {
   console.log('  - grace_period_seconds (u32):', params.gracePeriodSeconds);
   console.log('  - tier (u8):', VaultTier[params.tier], '=', params.tier);
   console.log('  - creation_fee_paid (u64):', params.creationFeePaid);
+  console.log('  - release_schedule (Option):', params.releaseSchedule ?? 'none');
 
   // Encode instruction data matching Rust signature:
-  // pub fn create_vault(beneficiary, checkin_period_seconds, name_hash, notification_window_seconds, grace_period_seconds, tier, creation_fee_paid)
+  // pub fn create_vault(beneficiary, checkin_period_seconds, name_hash, notification_window_seconds, grace_period_seconds, tier, creation_fee_paid, release_schedule)
   // NOTE: name_hash is a fixed-size [u8; 32] array, NOT Vec<u8>, so use encodeFixedBytes (no length prefix)
   const data = Buffer.concat([
     discriminator,                                    // 8 bytes
@@ -149,21 +203,27 @@ export async function createVaultInstruction(params: {
     encodeU32(params.gracePeriodSeconds),             // 4 bytes
     encodeVaultTier(params.tier),                     // 1 byte (NEW)
     encodeU64(params.creationFeePaid),                // 8 bytes (NEW)
+    encodeReleaseSchedule(params.releaseSchedule),    // 1 or 13 bytes
   ]);
 
   console.log('📦 Total instruction data size:', data.length, 'bytes');
-  console.log('   Expected: 8 (disc) + 32 (beneficiary) + 4 (checkin) + 32 (hash) + 4 (notif) + 4 (grace) + 1 (tier) + 8 (fee) = 93 bytes');
+  console.log('   Expected: 8 (disc) + 32 (beneficiary) + 4 (checkin) + 32 (hash) + 4 (notif) + 4 (grace) + 1 (tier) + 8 (fee) + 1 (no schedule) = 94 bytes');
 
   const keys = [
     { pubkey: params.config, isSigner: false, isWritable: false },
     { pubkey: params.counter, isSigner: false, isWritable: true },
     { pubkey: params.vault, isSigner: false, isWritable: true },
     { pubkey: params.vaultTokenAccount, isSigner: false, isWritable: true },
-    { pubkey: params.usdcMint, isSigner: false, isWritable: false },
+    { pubkey: params.mint, isSigner: false, isWritable: false },
+    { pubkey: params.creatorUsdcAta, isSigner: false, isWritable: true },
+    { pubkey: params.treasuryUsdcAta, isSigner: false, isWritable: true },
     { pubkey: params.creator, isSigner: true, isWritable: true },
-    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: params.tokenProgram ?? TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
     { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    // Optional accounts: the program ID stands in for "none"
+    { pubkey: params.walletFlags ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: params.supportedMint ?? params.programId, isSigner: false, isWritable: false },
   ];
 
   return new TransactionInstruction({
@@ -181,10 +241,13 @@ export async function depositUsdcInstruction(params: {
   vault: PublicKey;
   counter: PublicKey;
   vaultTokenAccount: PublicKey;
-  usdcMint: PublicKey;
+  mint: PublicKey;
   creatorUsdcAta: PublicKey;
   creator: PublicKey;
   amount: number | bigint;
+  walletFlags?: PublicKey; // Creator's WalletFlags PDA, if one exists (see findWalletFlags)
+  supportedMint?: PublicKey; // Required when `mint` isn't the config mint (see findSupportedMint)
+  tokenProgram?: PublicKey; // The mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
   programId: PublicKey;
 }): Promise<TransactionInstruction> {
   const discriminator = await getCachedDiscriminator('deposit_usdc');
@@ -197,10 +260,437 @@ export async function depositUsdcInstruction(params: {
     { pubkey: params.vault, isSigner: false, isWritable: true },
     { pubkey: params.counter, isSigner: false, isWritable: false },
     { pubkey: params.vaultTokenAccount, isSigner: false, isWritable: true },
-    { pubkey: params.usdcMint, isSigner: false, isWritable: false },
+    { pubkey: params.mint, isSigner: false, isWritable: false },
     { pubkey: params.creatorUsdcAta, isSigner: false, isWritable: true },
     { pubkey: params.creator, isSigner: true, isWritable: false },
-    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: params.tokenProgram ?? TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    // Optional accounts: the program ID stands in for "none"
+    { pubkey: params.walletFlags ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: params.supportedMint ?? params.programId, isSigner: false, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Build withdraw_usdc instruction (creator only, before the watchdog period)
+ * What stays locked must meet the config's minimum vault balance
+ */
+export async function withdrawUsdcInstruction(params: {
+  config: PublicKey;
+  vault: PublicKey;
+  vaultTokenAccount: PublicKey;
+  mint: PublicKey;
+  creatorUsdcAta: PublicKey;
+  creator: PublicKey;
+  amount: number | bigint;
+  tokenProgram?: PublicKey; // The mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('withdraw_usdc');
+
+  // Encode instruction data: discriminator + amount
+  const data = Buffer.concat([discriminator, encodeU64(params.amount)]);
+
+  const keys = [
+    { pubkey: params.config, isSigner: false, isWritable: false },
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.vaultTokenAccount, isSigner: false, isWritable: true },
+    { pubkey: params.mint, isSigner: false, isWritable: false },
+    { pubkey: params.creatorUsdcAta, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: false },
+    { pubkey: params.tokenProgram ?? TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Build deposit_token instruction: escrow another token or NFT in the vault and register it
+ * (creator only, push-mode vaults without a backup beneficiary or release schedule)
+ */
+export async function depositTokenInstruction(params: {
+  config: PublicKey;
+  vault: PublicKey;
+  assetMint: PublicKey;
+  creator: PublicKey;
+  amount: number | bigint; // 1 for an NFT
+  tokenProgram?: PublicKey; // The asset mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('deposit_token');
+  const tokenProgram = params.tokenProgram ?? TOKEN_PROGRAM_ID;
+
+  // Encode instruction data: discriminator + amount
+  const data = Buffer.concat([discriminator, encodeU64(params.amount)]);
+
+  const [vaultAssets] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_assets'), params.vault.toBuffer()],
+    params.programId
+  );
+
+  const keys = [
+    { pubkey: params.config, isSigner: false, isWritable: false },
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: vaultAssets, isSigner: false, isWritable: true },
+    { pubkey: params.assetMint, isSigner: false, isWritable: false },
+    {
+      pubkey: await getAssociatedTokenAddress(params.assetMint, params.vault, true, tokenProgram),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await getAssociatedTokenAddress(params.assetMint, params.creator, false, tokenProgram),
+      isSigner: false,
+      isWritable: true,
+    },
+    { pubkey: params.creator, isSigner: true, isWritable: true },
+    { pubkey: tokenProgram, isSigner: false, isWritable: false },
+    { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Read the treasury wallet from the on-chain config account
+ * Layout: discriminator(8) + admin(32) + version(1) + usdc_mint(32) + max_lock(8) + pause_flags(1) + treasury(32)
+ */
+export async function fetchTreasury(connection: Connection, programId: PublicKey): Promise<PublicKey> {
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], programId);
+  const info = await connection.getAccountInfo(config);
+  if (!info) {
+    throw new Error('Config account not found');
+  }
+  return new PublicKey(info.data.subarray(82, 114));
+}
+
+/**
+ * WalletFlags capability bits (match WALLET_FLAG_* in the program)
+ */
+export const WALLET_FLAG_SELF_BENEFICIARY = 1 << 0;
+export const WALLET_FLAG_FEE_EXEMPT = 1 << 1;
+export const WALLET_FLAG_CAP_EXEMPT = 1 << 2;
+
+/**
+ * Find a wallet's WalletFlags PDA (QA / partner capabilities), if it exists
+ * Layout: discriminator(8) + wallet(32) + flags(1) + ...
+ */
+export async function findWalletFlags(
+  connection: Connection,
+  programId: PublicKey,
+  wallet: PublicKey
+): Promise<{ address: PublicKey; flags: number } | undefined> {
+  const [address] = PublicKey.findProgramAddressSync([Buffer.from('flags'), wallet.toBuffer()], programId);
+  const info = await connection.getAccountInfo(address);
+  return info ? { address, flags: info.data[40] } : undefined;
+}
+
+/**
+ * Find a mint's SupportedMint PDA, if the mint is listed (the config mint never is)
+ * Layout: discriminator(8) + mint(32) + max_lock_per_vault(8) + fee_schedule + enabled + ...
+ */
+export async function findSupportedMint(
+  connection: Connection,
+  programId: PublicKey,
+  mint: PublicKey
+): Promise<PublicKey | undefined> {
+  const [address] = PublicKey.findProgramAddressSync([Buffer.from('supported_mint'), mint.toBuffer()], programId);
+  const info = await connection.getAccountInfo(address);
+  return info ? address : undefined;
+}
+
+/**
+ * List every WalletFlags account (matched on the Anchor account discriminator,
+ * since RoleAssignment has the same size)
+ */
+export async function listWalletFlags(
+  connection: Connection,
+  programId: PublicKey
+): Promise<{ address: PublicKey; wallet: PublicKey; flags: number }[]> {
+  const hash = await crypto.subtle.digest('SHA-256', new TextEncoder().encode('account:WalletFlags'));
+  const accountDiscriminator = Buffer.from(new Uint8Array(hash).slice(0, 8));
+  const accounts = await connection.getProgramAccounts(programId, {
+    filters: [{ memcmp: { offset: 0, bytes: accountDiscriminator.toString('base64'), encoding: 'base64' } }],
+  });
+  return accounts.map(({ pubkey, account }) => ({
+    address: pubkey,
+    wallet: new PublicKey(account.data.subarray(8, 40)),
+    flags: account.data[40],
+  }));
+}
+
+/**
+ * A decoded vault's payees and bps shares, as of `nowUnix`
+ * Legacy single-beneficiary vaults (beneficiary_count = 0) pay 100% to `beneficiary`;
+ * a pending update_beneficiary whose cooldown has elapsed replaces the first payee
+ */
+export function vaultBeneficiaryShares(
+  vault: NonNullable<ReturnType<typeof decodeVault>>,
+  nowUnix: number = Math.floor(Date.now() / 1000)
+): { beneficiary: PublicKey; bps: number }[] {
+  const shares: { beneficiary: PublicKey; bps: number }[] =
+    vault.beneficiaryCount === 0
+      ? [{ beneficiary: vault.beneficiary, bps: 10_000 }]
+      : vault.beneficiaryShares
+          .slice(0, vault.beneficiaryCount)
+          .map((share: { beneficiary: PublicKey; bps: number }) => ({ ...share }));
+
+  const effectiveAt = vault.beneficiaryEffectiveAt.toNumber();
+  if (effectiveAt !== 0 && nowUnix >= effectiveAt) {
+    shares[0].beneficiary = vault.pendingBeneficiary;
+  }
+  return shares;
+}
+
+/**
+ * A vault's registered assets (deposit_token), as the remaining accounts release and
+ * cancel_vault expect: per asset its mint, the vault's ATA, `recipient`'s ATA and the
+ * mint's token program. `vaultAssets` is unset for vaults that never registered an asset.
+ * Registry layout: discriminator(8) + vault(32) + assets vec (u32 length, then mint(32) + amount(8) each) + bump
+ */
+export async function findVaultAssets(
+  connection: Connection,
+  programId: PublicKey,
+  vault: PublicKey,
+  recipient: PublicKey
+): Promise<{ vaultAssets?: PublicKey; assetAccounts: AccountMeta[] }> {
+  const [vaultAssets] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_assets'), vault.toBuffer()],
+    programId
+  );
+  const info = await connection.getAccountInfo(vaultAssets);
+  if (!info) {
+    return { assetAccounts: [] };
+  }
+  const count = info.data.readUInt32LE(40);
+  const assetAccounts: AccountMeta[] = [];
+  for (let i = 0; i < count; i++) {
+    const assetMint = new PublicKey(info.data.subarray(44 + i * 40, 76 + i * 40));
+    const tokenProgram = (await connection.getAccountInfo(assetMint))?.owner ?? TOKEN_PROGRAM_ID;
+    assetAccounts.push(
+      { pubkey: assetMint, isSigner: false, isWritable: false },
+      { pubkey: await getAssociatedTokenAddress(assetMint, vault, true, tokenProgram), isSigner: false, isWritable: true },
+      { pubkey: await getAssociatedTokenAddress(assetMint, recipient, false, tokenProgram), isSigner: false, isWritable: true },
+      { pubkey: tokenProgram, isSigner: false, isWritable: false },
+    );
+  }
+  return { vaultAssets, assetAccounts };
+}
+
+/**
+ * Payees for a release: the primary beneficiary currently in effect, plus the ATAs
+ * of a split vault's additional payees (shares 2..n, in share order) and idempotent
+ * instructions creating them (release only creates the primary's ATA).
+ * `mint` is the vault's mint, `tokenProgram` its owner and `supportedMint` its listing, if it has one.
+ * `vesting` is set for vaults with a release schedule, which pay out with release_tranche.
+ * `vaultAssets` and `assetAccounts` hand registered assets to the primary beneficiary (see findVaultAssets).
+ */
+export async function releasePayees(
+  connection: Connection,
+  vault: PublicKey,
+  payer: PublicKey
+): Promise<{
+  beneficiary: PublicKey;
+  atas: PublicKey[];
+  createAtaInstructions: TransactionInstruction[];
+  mint: PublicKey;
+  tokenProgram: PublicKey;
+  supportedMint?: PublicKey;
+  vesting: boolean;
+  vaultAssets?: PublicKey;
+  assetAccounts: AccountMeta[];
+}> {
+  const info = await connection.getAccountInfo(vault);
+  if (!info) {
+    throw new Error('Vault account not found');
+  }
+  const decoded = decodeVault(info.data);
+  if (!decoded) {
+    throw new Error('Vault uses the v1 layout; run migrate_vault first');
+  }
+  const mint: PublicKey = decoded.mint;
+  const tokenProgram = (await connection.getAccountInfo(mint))?.owner ?? TOKEN_PROGRAM_ID;
+  const [primary, ...others] = vaultBeneficiaryShares(decoded);
+  const payees = others.map((share) => share.beneficiary);
+  const atas = await Promise.all(
+    payees.map((payee) => getAssociatedTokenAddress(mint, payee, false, tokenProgram))
+  );
+  return {
+    beneficiary: primary.beneficiary,
+    atas,
+    createAtaInstructions: payees.map((payee, i) =>
+      createAssociatedTokenAccountIdempotentInstruction(payer, atas[i], payee, mint, tokenProgram)
+    ),
+    mint,
+    tokenProgram,
+    supportedMint: await findSupportedMint(connection, info.owner, mint),
+    vesting: decoded.releaseSchedule.tranches > 0,
+    ...(await findVaultAssets(connection, info.owner, vault, primary.beneficiary)),
+  };
+}
+
+/**
+ * Build update_beneficiary instruction (creator only, before the watchdog period)
+ * The change takes effect after the config's beneficiary cooldown; passing the
+ * current beneficiary withdraws a pending change
+ */
+export async function updateBeneficiaryInstruction(params: {
+  vault: PublicKey;
+  creator: PublicKey;
+  newBeneficiary: PublicKey;
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('update_beneficiary');
+
+  const data = Buffer.concat([discriminator, encodePublicKey(params.newBeneficiary)]);
+
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], params.programId);
+
+  const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Build set_beneficiaries instruction (creator only, before the watchdog period)
+ * Shares must be 1-5 unique payees with bps summing to 10_000; the first must be the
+ * current beneficiary (change it with updateBeneficiaryInstruction)
+ */
+export async function setBeneficiariesInstruction(params: {
+  vault: PublicKey;
+  creator: PublicKey;
+  shares: { beneficiary: PublicKey; bps: number }[];
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('set_beneficiaries');
+
+  // Vec<BeneficiaryShare>: length (u32 LE) + (pubkey + bps u16 LE) per share
+  const length = Buffer.alloc(4);
+  length.writeUInt32LE(params.shares.length, 0);
+  const shares = params.shares.map((share) => {
+    const bps = Buffer.alloc(2);
+    bps.writeUInt16LE(share.bps, 0);
+    return Buffer.concat([encodePublicKey(share.beneficiary), bps]);
+  });
+
+  const data = Buffer.concat([discriminator, length, ...shares]);
+
+  const keys = [
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Build set_backup_beneficiary instruction (creator only, before the watchdog period)
+ * Pass `backupBeneficiary: null` to clear the backup
+ */
+export async function setBackupBeneficiaryInstruction(params: {
+  vault: PublicKey;
+  creator: PublicKey;
+  backupBeneficiary: PublicKey | null;
+  claimWindowSeconds: number; // How long the beneficiary has to claim before the backup may
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('set_backup_beneficiary');
+
+  // Option<Pubkey>: 0 = None, 1 + pubkey = Some
+  const backup = params.backupBeneficiary
+    ? Buffer.concat([encodeU8(1), encodePublicKey(params.backupBeneficiary)])
+    : encodeU8(0);
+  const data = Buffer.concat([discriminator, backup, encodeU32(params.claimWindowSeconds)]);
+
+  const keys = [
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+export type PayoutMode = 'push' | 'claim';
+
+/**
+ * Build set_payout_mode instruction (creator only, before the watchdog period)
+ * 'claim' vaults are paid out by the beneficiary with claim instead of by release
+ */
+export async function setPayoutModeInstruction(params: {
+  vault: PublicKey;
+  creator: PublicKey;
+  payoutMode: PayoutMode;
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('set_payout_mode');
+
+  // PayoutMode enum: 0 = Push, 1 = Claim
+  const data = Buffer.concat([discriminator, encodeU8(params.payoutMode === 'claim' ? 1 : 0)]);
+
+  const keys = [
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Build set_early_checkin instruction (creator only)
+ * Lets check-ins land before the notification window, at most once per minIntervalSeconds (0 = no limit)
+ */
+export async function setEarlyCheckinInstruction(params: {
+  vault: PublicKey;
+  creator: PublicKey;
+  enabled: boolean;
+  minIntervalSeconds: number; // Must be shorter than the vault's check-in period
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('set_early_checkin');
+
+  const data = Buffer.concat([
+    discriminator,
+    encodeU8(params.enabled ? 1 : 0),
+    encodeU32(params.minIntervalSeconds),
+  ]);
+
+  const keys = [
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: false },
   ];
 
   return new TransactionInstruction({
@@ -210,35 +700,108 @@ export async function depositUsdcInstruction(params: {
   });
 }
 
+/**
+ * Build claim instruction (claim-mode vaults after the grace period, or backup vaults
+ * after release opened the claim window)
+ * The beneficiary can claim any time; the backup once the claim deadline has passed
+ */
+export async function claimInstruction(params: {
+  vault: PublicKey;
+  vaultTokenAccount: PublicKey;
+  mint: PublicKey;
+  claimant: PublicKey;
+  destination: PublicKey; // Any token account in the vault's mint owned by the claimant
+  treasuryUsdcAta: PublicKey; // Receives the tier release fee (if any)
+  supportedMint?: PublicKey; // Required when `mint` isn't the config mint (see findSupportedMint)
+  tokenProgram?: PublicKey; // The mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('claim');
+
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], params.programId);
+
+  const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.vaultTokenAccount, isSigner: false, isWritable: true },
+    { pubkey: params.mint, isSigner: false, isWritable: false },
+    { pubkey: params.claimant, isSigner: true, isWritable: false },
+    { pubkey: params.destination, isSigner: false, isWritable: true },
+    { pubkey: params.treasuryUsdcAta, isSigner: false, isWritable: true },
+    { pubkey: params.tokenProgram ?? TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    // Optional account: the program ID stands in for "none"
+    { pubkey: params.supportedMint ?? params.programId, isSigner: false, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data: discriminator,
+  });
+}
+
 /**
  * Build release instruction
+ * SOL vaults leave out the token accounts and pay their payees' wallets plus the treasury wallet
  */
 export async function releaseInstruction(params: {
   vault: PublicKey;
   counter: PublicKey;
-  vaultTokenAccount: PublicKey;
-  usdcMint: PublicKey;
-  beneficiaryUsdcAta: PublicKey;
+  vaultTokenAccount?: PublicKey; // Token vaults only
+  mint?: PublicKey; // Token vaults only
+  beneficiaryUsdcAta?: PublicKey; // Token vaults only
   beneficiary: PublicKey;
+  treasuryUsdcAta?: PublicKey;  // Token vaults: receives the tier release fee (if any)
   payer: PublicKey;  // Added: pays for beneficiary ATA creation if needed
+  additionalBeneficiaryAtas?: PublicKey[]; // Split vaults: payees 2..n in share order (their wallets for SOL vaults; see releasePayees)
+  tranche?: boolean; // Vesting vaults: build release_tranche instead (same accounts)
+  supportedMint?: PublicKey; // Required when `mint` isn't the config mint (see releasePayees)
+  tokenProgram?: PublicKey; // The mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
+  vaultAssets?: PublicKey; // Registered assets, if any (see releasePayees)
+  creator?: PublicKey; // Vault creator; required with vaultAssets (refunded the asset accounts' rent)
+  treasury?: PublicKey; // SOL vaults: treasury wallet, receives the tier release fee (see fetchTreasury)
+  assetAccounts?: AccountMeta[]; // Their accounts, after the additional payees' ATAs
   programId: PublicKey;
 }): Promise<TransactionInstruction> {
-  const discriminator = await getCachedDiscriminator('release');
+  const discriminator = await getCachedDiscriminator(params.tranche ? 'release_tranche' : 'release');
 
   // Release has no arguments, just the discriminator
   const data = discriminator;
 
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], params.programId);
+  // Token accounts are optional: SOL vaults pass the program ID ("none") in their place
+  const tokenVault = !!params.vaultTokenAccount;
+  const tokenAccount = (pubkey?: PublicKey) => ({
+    pubkey: pubkey ?? params.programId,
+    isSigner: false,
+    isWritable: !!pubkey,
+  });
+
   const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
     { pubkey: params.vault, isSigner: false, isWritable: true },
     { pubkey: params.counter, isSigner: false, isWritable: false },
-    { pubkey: params.vaultTokenAccount, isSigner: false, isWritable: true },
-    { pubkey: params.usdcMint, isSigner: false, isWritable: false },
-    { pubkey: params.beneficiaryUsdcAta, isSigner: false, isWritable: true },
-    { pubkey: params.beneficiary, isSigner: false, isWritable: false }, // Fixed: not a signer
+    tokenAccount(params.vaultTokenAccount),
+    { pubkey: params.mint ?? params.programId, isSigner: false, isWritable: false },
+    tokenAccount(params.beneficiaryUsdcAta),
+    { pubkey: params.beneficiary, isSigner: false, isWritable: true }, // Fixed: not a signer; SOL vaults pay it lamports
+    tokenAccount(params.treasuryUsdcAta),
     { pubkey: params.payer, isSigner: true, isWritable: true }, // Added: must sign
-    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: tokenVault ? params.tokenProgram ?? TOKEN_PROGRAM_ID : params.programId,
+      isSigner: false,
+      isWritable: false,
+    },
     { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    // Optional accounts: the program ID stands in for "none"
+    { pubkey: params.supportedMint ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: params.vaultAssets ?? params.programId, isSigner: false, isWritable: !!params.vaultAssets },
+    { pubkey: params.creator ?? params.programId, isSigner: false, isWritable: !!params.creator },
+    { pubkey: params.treasury ?? params.programId, isSigner: false, isWritable: !!params.treasury },
+    // Remaining accounts: one token account (wallet for SOL vaults) per additional payee, then the registered assets
+    ...(params.additionalBeneficiaryAtas ?? []).map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })),
+    ...(params.assetAccounts ?? []),
   ];
 
   return new TransactionInstruction({
@@ -248,13 +811,22 @@ export async function releaseInstruction(params: {
   });
 }
 
+/**
+ * A vault's check-in delegate list PDA (see addCheckinDelegateInstruction)
+ */
+export function findCheckinDelegates(programId: PublicKey, vault: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('checkin_delegates'), vault.toBuffer()], programId)[0];
+}
+
 /**
  * Build check_in instruction
+ * The signer is the creator or one of the vault's check-in delegates
  */
 export async function checkInInstruction(params: {
   vault: PublicKey;
-  counter: PublicKey;
-  creator: PublicKey;
+  counter: PublicKey; // Derived from the vault's creator, not the signer
+  signer: PublicKey;
+  checkinDelegates?: PublicKey; // Required when a delegate signs (see findCheckinDelegates)
   programId: PublicKey;
 }): Promise<TransactionInstruction> {
   const discriminator = await getCachedDiscriminator('check_in');
@@ -262,10 +834,104 @@ export async function checkInInstruction(params: {
   // Check-in has no arguments
   const data = discriminator;
 
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], params.programId);
+
   const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
     { pubkey: params.vault, isSigner: false, isWritable: true },
     { pubkey: params.counter, isSigner: false, isWritable: false },
-    { pubkey: params.creator, isSigner: true, isWritable: false },
+    { pubkey: params.signer, isSigner: true, isWritable: false },
+    // Optional account: the program ID stands in for "none"
+    { pubkey: params.checkinDelegates ?? params.programId, isSigner: false, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Heartbeat message the creator (or a check-in delegate) signs off-chain for relay_check_in,
+ * e.g. with the wallet's signMessage: domain || vault || nonce (u64 LE) || expiry (i64 LE).
+ * `nonce` must be the vault's relay_nonce + 1; `expiry` is a unix timestamp at most 15 minutes ahead.
+ */
+export function relayCheckInMessage(vault: PublicKey, nonce: number | bigint, expiry: number | bigint): Uint8Array {
+  return Buffer.concat([
+    Buffer.from('keepr-vault:check_in:v1'),
+    encodePublicKey(vault),
+    encodeU64(nonce),
+    encodeI64(expiry),
+  ]);
+}
+
+/**
+ * Build the two instructions a relayer submits for a signed heartbeat (permissionless):
+ * the ed25519 signature verification, then relay_check_in, in that order
+ */
+export async function relayCheckInInstructions(params: {
+  vault: PublicKey;
+  signer: PublicKey; // Creator or check-in delegate who signed the heartbeat
+  signature: Uint8Array; // Ed25519 signature over relayCheckInMessage(vault, nonce, expiry)
+  nonce: number | bigint;
+  expiry: number | bigint;
+  checkinDelegates?: PublicKey; // Required when a delegate signed (see findCheckinDelegates)
+  programId: PublicKey;
+}): Promise<TransactionInstruction[]> {
+  const discriminator = await getCachedDiscriminator('relay_check_in');
+
+  // Encode instruction data: discriminator + nonce + expiry
+  const data = Buffer.concat([discriminator, encodeU64(params.nonce), encodeI64(params.expiry)]);
+
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], params.programId);
+
+  const keys = [
+    { pubkey: config, isSigner: false, isWritable: false },
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    // Optional account: the program ID stands in for "none"
+    { pubkey: params.checkinDelegates ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+  ];
+
+  return [
+    Ed25519Program.createInstructionWithPublicKey({
+      publicKey: params.signer.toBytes(),
+      message: relayCheckInMessage(params.vault, params.nonce, params.expiry),
+      signature: params.signature,
+    }),
+    new TransactionInstruction({
+      keys,
+      programId: params.programId,
+      data,
+    }),
+  ];
+}
+
+/**
+ * Build add_checkin_delegate / remove_checkin_delegate instruction (creator only)
+ * Delegates may call check_in for the vault and nothing else
+ */
+export async function checkinDelegateInstruction(params: {
+  vault: PublicKey;
+  creator: PublicKey;
+  delegate: PublicKey;
+  remove?: boolean;
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator(
+    params.remove ? 'remove_checkin_delegate' : 'add_checkin_delegate'
+  );
+
+  // Encode instruction data: discriminator + delegate
+  const data = Buffer.concat([discriminator, encodePublicKey(params.delegate)]);
+
+  const keys = [
+    { pubkey: params.vault, isSigner: false, isWritable: false },
+    { pubkey: findCheckinDelegates(params.programId, params.vault), isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: !params.remove },
+    // add_checkin_delegate creates the list on first use
+    ...(params.remove ? [] : [{ pubkey: SystemProgram.programId, isSigner: false, isWritable: false }]),
   ];
 
   return new TransactionInstruction({
@@ -277,16 +943,23 @@ export async function checkInInstruction(params: {
 
 /**
  * Build cancel_vault instruction
+ * SOL vaults leave out the token accounts; the refund and closing fee are paid in lamports
  */
 export async function cancelVaultInstruction(params: {
   config: PublicKey;
   vault: PublicKey;
   counter: PublicKey;
-  vaultTokenAccount: PublicKey;
-  usdcMint: PublicKey;
-  creatorUsdcAta: PublicKey;
-  treasuryUsdcAta: PublicKey;
+  vaultTokenAccount?: PublicKey; // Token vaults only
+  mint?: PublicKey; // Token vaults only
+  creatorUsdcAta?: PublicKey; // Token vaults only
+  treasuryUsdcAta?: PublicKey; // Token vaults: receives the closing fee (if any)
   creator: PublicKey;
+  walletFlags?: PublicKey; // Creator's WalletFlags PDA, if one exists (see findWalletFlags)
+  supportedMint?: PublicKey; // Required when `mint` isn't the config mint (see findSupportedMint)
+  tokenProgram?: PublicKey; // The mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
+  vaultAssets?: PublicKey; // Registered assets, if any (see findVaultAssets with the creator as recipient)
+  treasury?: PublicKey; // SOL vaults: treasury wallet, receives the closing fee (see fetchTreasury)
+  assetAccounts?: AccountMeta[];
   programId: PublicKey;
 }): Promise<TransactionInstruction> {
   const discriminator = await getCachedDiscriminator('cancel_vault');
@@ -294,18 +967,125 @@ export async function cancelVaultInstruction(params: {
   // Cancel vault has no arguments, just the discriminator
   const data = discriminator;
 
+  // Token accounts are optional: SOL vaults pass the program ID ("none") in their place
+  const tokenVault = !!params.vaultTokenAccount;
+  const tokenAccount = (pubkey?: PublicKey) => ({
+    pubkey: pubkey ?? params.programId,
+    isSigner: false,
+    isWritable: !!pubkey,
+  });
+
   const keys = [
     { pubkey: params.config, isSigner: false, isWritable: false },
     { pubkey: params.vault, isSigner: false, isWritable: true },
     { pubkey: params.counter, isSigner: false, isWritable: false },
-    { pubkey: params.vaultTokenAccount, isSigner: false, isWritable: true },
-    { pubkey: params.usdcMint, isSigner: false, isWritable: false },
-    { pubkey: params.creatorUsdcAta, isSigner: false, isWritable: true },
-    { pubkey: params.treasuryUsdcAta, isSigner: false, isWritable: true },
+    tokenAccount(params.vaultTokenAccount),
+    { pubkey: params.mint ?? params.programId, isSigner: false, isWritable: false },
+    tokenAccount(params.creatorUsdcAta),
+    tokenAccount(params.treasuryUsdcAta),
     { pubkey: params.creator, isSigner: true, isWritable: true },
-    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: tokenVault ? params.tokenProgram ?? TOKEN_PROGRAM_ID : params.programId,
+      isSigner: false,
+      isWritable: false,
+    },
     { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
     { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    // Optional accounts: the program ID stands in for "none"
+    { pubkey: params.walletFlags ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: params.supportedMint ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: params.vaultAssets ?? params.programId, isSigner: false, isWritable: !!params.vaultAssets },
+    { pubkey: params.treasury ?? params.programId, isSigner: false, isWritable: !!params.treasury },
+    // Remaining accounts: registered assets go back to the creator
+    ...(params.assetAccounts ?? []),
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Build create_sol_vault instruction
+ * SOL vaults hold lamports in the vault PDA; the creation fee is paid in lamports to the treasury wallet
+ */
+export async function createSolVaultInstruction(params: {
+  config: PublicKey;
+  counter: PublicKey;
+  vault: PublicKey;
+  treasury: PublicKey; // Treasury wallet (see fetchTreasury)
+  creator: PublicKey;
+  beneficiary: PublicKey;
+  checkinPeriodSeconds: number;
+  nameHash: number[] | Uint8Array;
+  notificationWindowSeconds: number;
+  gracePeriodSeconds: number;
+  tier: VaultTier;
+  creationFeePaid: number | bigint; // Lamports; must match the native mint's listed fee schedule
+  walletFlags?: PublicKey; // Creator's WalletFlags PDA, if one exists (see findWalletFlags)
+  supportedMint: PublicKey; // The native mint's listing (see findSupportedMint)
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('create_sol_vault');
+
+  // Same arguments as create_vault, minus the release schedule
+  const data = Buffer.concat([
+    discriminator,                                    // 8 bytes
+    encodePublicKey(params.beneficiary),              // 32 bytes
+    encodeU32(params.checkinPeriodSeconds),           // 4 bytes
+    encodeFixedBytes(params.nameHash),                // 32 bytes (no length prefix)
+    encodeU32(params.notificationWindowSeconds),      // 4 bytes
+    encodeU32(params.gracePeriodSeconds),             // 4 bytes
+    encodeVaultTier(params.tier),                     // 1 byte
+    encodeU64(params.creationFeePaid),                // 8 bytes
+  ]);
+
+  const keys = [
+    { pubkey: params.config, isSigner: false, isWritable: false },
+    { pubkey: params.counter, isSigner: false, isWritable: true },
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.treasury, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    // Optional accounts: the program ID stands in for "none"
+    { pubkey: params.walletFlags ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: params.supportedMint, isSigner: false, isWritable: false },
+  ];
+
+  return new TransactionInstruction({
+    keys,
+    programId: params.programId,
+    data,
+  });
+}
+
+/**
+ * Build deposit_sol instruction (amount in lamports)
+ */
+export async function depositSolInstruction(params: {
+  config: PublicKey;
+  vault: PublicKey;
+  creator: PublicKey;
+  amount: number | bigint;
+  walletFlags?: PublicKey; // Creator's WalletFlags PDA, if one exists (see findWalletFlags)
+  supportedMint: PublicKey; // The native mint's listing (see findSupportedMint)
+  programId: PublicKey;
+}): Promise<TransactionInstruction> {
+  const discriminator = await getCachedDiscriminator('deposit_sol');
+
+  // Encode instruction data: discriminator + amount
+  const data = Buffer.concat([discriminator, encodeU64(params.amount)]);
+
+  const keys = [
+    { pubkey: params.config, isSigner: false, isWritable: false },
+    { pubkey: params.vault, isSigner: false, isWritable: true },
+    { pubkey: params.creator, isSigner: true, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    // Optional accounts: the program ID stands in for "none"
+    { pubkey: params.walletFlags ?? params.programId, isSigner: false, isWritable: false },
+    { pubkey: params.supportedMint, isSigner: false, isWritable: false },
   ];
 
   return new TransactionInstruction({
@@ -320,9 +1100,10 @@ export async function cancelVaultInstruction(params: {
  */
 export async function closeVaultInstruction(params: {
   vault: PublicKey;
-  vaultTokenAccount: PublicKey;
+  vaultTokenAccount?: PublicKey; // Omit for SOL vaults, which have no token account
   creator: PublicKey;
   signer: PublicKey;
+  tokenProgram?: PublicKey; // The mint's owner; pass TOKEN_2022_PROGRAM_ID for Token-2022 mints
   programId: PublicKey;
 }): Promise<TransactionInstruction> {
   const discriminator = await getCachedDiscriminator('close_vault');
@@ -332,10 +1113,15 @@ export async function closeVaultInstruction(params: {
 
   const keys = [
     { pubkey: params.vault, isSigner: false, isWritable: true },
-    { pubkey: params.vaultTokenAccount, isSigner: false, isWritable: true },
+    // Optional accounts: the program ID stands in for "none" (SOL vaults)
+    { pubkey: params.vaultTokenAccount ?? params.programId, isSigner: false, isWritable: !!params.vaultTokenAccount },
     { pubkey: params.creator, isSigner: false, isWritable: true },
     { pubkey: params.signer, isSigner: true, isWritable: false },
-    { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    {
+      pubkey: params.vaultTokenAccount ? params.tokenProgram ?? TOKEN_PROGRAM_ID : params.programId,
+      isSigner: false,
+      isWritable: false,
+    },
   ];
 
   return new TransactionInstruction({
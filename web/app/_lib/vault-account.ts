@@ -0,0 +1,36 @@
+import { Program, type Idl, type Provider } from '@coral-xyz/anchor';
+import type { GetProgramAccountsFilter } from '@solana/web3.js';
+import idl from './keepr_vault.json';
+import { connection } from './solana';
+
+/**
+ * Read-only keepr_vault client; its program ID is the IDL address (declare_id)
+ */
+export const vaultProgram = new Program(idl as Idl, { connection } as Provider);
+
+/**
+ * Matches every Vault account, whatever its layout version
+ */
+export const VAULT_FILTER: GetProgramAccountsFilter = {
+  memcmp: vaultProgram.coder.accounts.memcmp('vault'),
+};
+
+const VAULT_TIERS = ['base', 'plus', 'premium', 'lifetime'];
+
+/**
+ * Decode a Vault account; null for a v1 account that still needs migrate_vault
+ */
+export function decodeVault(data: Buffer) {
+  try {
+    return vaultProgram.coder.accounts.decode('vault', data);
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Index of a decoded VaultTier (the order VaultCard's names and colours use)
+ */
+export function tierIndex(tier: object): number {
+  return VAULT_TIERS.indexOf(Object.keys(tier)[0]);
+}
@@ -8,8 +8,20 @@ import { PublicKey } from '@solana/web3.js';
 import { AnchorProvider, Program } from '@coral-xyz/anchor';
 import { isAdminWallet } from '../../_lib/validation';
 import { connection, PROGRAM_ID } from '../../_lib/solana';
+import {
+  listWalletFlags,
+  WALLET_FLAG_SELF_BENEFICIARY,
+  WALLET_FLAG_FEE_EXEMPT,
+  WALLET_FLAG_CAP_EXEMPT,
+} from '../../_lib/instructions';
 import idl from '../../_lib/keepr_vault.json';
 
+const WALLET_FLAG_LABELS: [number, string][] = [
+  [WALLET_FLAG_SELF_BENEFICIARY, 'Self-beneficiary'],
+  [WALLET_FLAG_FEE_EXEMPT, 'Fee exempt'],
+  [WALLET_FLAG_CAP_EXEMPT, 'Cap exempt'],
+];
+
 export default function AdminSettingsPage() {
   const { connected, publicKey, signTransaction } = useWallet();
   const router = useRouter();
@@ -53,6 +65,7 @@ export default function AdminSettingsPage() {
 
       // Fetch config account
       const configAccount = await program.account.config.fetch(configPda);
+      const flaggedWallets = await listWalletFlags(connection, new PublicKey(PROGRAM_ID));
 
       setConfig({
         pda: configPda.toBase58(),
@@ -60,7 +73,10 @@ export default function AdminSettingsPage() {
         usdcMint: (configAccount as any).usdcMint.toBase58(),
         maxLockPerVault: (configAccount as any).maxLockPerVault.toString(),
         paused: (configAccount as any).paused,
-        adminTestWallets: ((configAccount as any).adminTestWallets || []).map((w: PublicKey) => w.toBase58()),
+        flaggedWallets: flaggedWallets.map(({ wallet, flags }) => ({
+          wallet: wallet.toBase58(),
+          labels: WALLET_FLAG_LABELS.filter(([bit]) => flags & bit).map(([, label]) => label),
+        })),
       });
     } catch (err: any) {
       console.error('Error loading config:', err);
@@ -192,19 +208,20 @@ export default function AdminSettingsPage() {
 
                 <div className="py-3">
                   <div className="mb-2">
-                    <p className="text-sm font-medium text-warm-900">Admin Test Wallets</p>
-                    <p className="text-xs text-warm-600">Wallets with testing privileges (max 10)</p>
+                    <p className="text-sm font-medium text-warm-900">Flagged Wallets</p>
+                    <p className="text-xs text-warm-600">QA and partner wallets with per-wallet capabilities</p>
                   </div>
-                  {config.adminTestWallets.length === 0 ? (
-                    <p className="text-xs text-warm-500 italic">No test wallets configured</p>
+                  {config.flaggedWallets.length === 0 ? (
+                    <p className="text-xs text-warm-500 italic">No wallet flags configured</p>
                   ) : (
                     <div className="space-y-2">
-                      {config.adminTestWallets.map((wallet: string, index: number) => (
+                      {config.flaggedWallets.map(({ wallet, labels }: { wallet: string; labels: string[] }, index: number) => (
                         <div key={index} className="flex items-center gap-2 p-2 bg-warm-50 rounded-lg">
                           <svg className="w-4 h-4 text-purple-600" fill="none" stroke="currentColor" viewBox="0 0 24 24">
                             <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M9 12l2 2 4-4m5.618-4.016A11.955 11.955 0 0112 2.944a11.955 11.955 0 01-8.618 3.04A12.02 12.02 0 003 9c0 5.591 3.824 10.29 9 11.622 5.176-1.332 9-6.03 9-11.622 0-1.042-.133-2.052-.382-3.016z" />
                           </svg>
                           <span className="text-xs font-mono text-warm-700">{wallet}</span>
+                          <span className="text-xs text-warm-500">{labels.join(', ')}</span>
                         </div>
                       ))}
                     </div>
@@ -222,10 +239,10 @@ export default function AdminSettingsPage() {
                 <div>
                   <h3 className="font-semibold text-amber-900 mb-1">Configuration Management</h3>
                   <p className="text-sm text-amber-800">
-                    System configuration is stored on-chain and can only be modified by the admin wallet using the
+                    System configuration is stored on-chain and can only be modified by the admin wallet (or a role holder) using the
                     <code className="px-2 py-0.5 bg-amber-100 rounded mx-1 font-mono text-xs">update_config</code>
                     and
-                    <code className="px-2 py-0.5 bg-amber-100 rounded mx-1 font-mono text-xs">update_admin_test_wallets</code>
+                    <code className="px-2 py-0.5 bg-amber-100 rounded mx-1 font-mono text-xs">set_wallet_flags</code>
                     instructions. UI-based updates are not yet implemented - use CLI tools for modifications.
                   </p>
                 </div>
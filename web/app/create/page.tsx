@@ -14,7 +14,7 @@ import { PublicKey, Transaction } from '@solana/web3.js';
 import { saveVaultMeta, addActivityLog, updateLastSeen } from '../_lib/storage';
 import { connection, PROGRAM_ID, USDC_MINT } from '../_lib/solana';
 import { TOKEN_PROGRAM_ID, getAssociatedTokenAddress, ASSOCIATED_TOKEN_PROGRAM_ID } from '@solana/spl-token';
-import { createVaultInstruction, depositUsdcInstruction, VaultTier } from '../_lib/instructions';
+import { createVaultInstruction, depositUsdcInstruction, fetchTreasury, findWalletFlags, VaultTier, WALLET_FLAG_FEE_EXEMPT } from '../_lib/instructions';
 import { useNotifications } from '../_contexts/NotificationContext';
 
 type Step = 'form' | 'review' | 'processing' | 'success';
@@ -305,6 +305,15 @@ export default function CreateVaultPage() {
         publicKey
       );
 
+      // Treasury USDC ATA receives the creation fee
+      const treasuryUsdcAta = await getAssociatedTokenAddress(
+        new PublicKey(USDC_MINT),
+        await fetchTreasury(connection, programId)
+      );
+
+      // QA / partner capabilities (self-beneficiary, fee or cap exemptions)
+      const walletFlags = await findWalletFlags(connection, programId, publicKey);
+
       console.log('Building create vault instruction...');
       console.log('Program ID:', programId.toString());
       console.log('Vault PDA:', vaultPda.toString());
@@ -319,7 +328,9 @@ export default function CreateVaultPage() {
         counter: counterPda,
         vault: vaultPda,
         vaultTokenAccount,
-        usdcMint: new PublicKey(USDC_MINT),
+        mint: new PublicKey(USDC_MINT),
+        creatorUsdcAta,
+        treasuryUsdcAta,
         creator: publicKey,
         beneficiary: new PublicKey(formData.beneficiary),
         checkinPeriodSeconds,
@@ -327,7 +338,9 @@ export default function CreateVaultPage() {
         notificationWindowSeconds,
         gracePeriodSeconds,
         tier,
-        creationFeePaid,
+        // Fee-exempt wallets must quote a zero creation fee
+        creationFeePaid: walletFlags && walletFlags.flags & WALLET_FLAG_FEE_EXEMPT ? 0 : creationFeePaid,
+        walletFlags: walletFlags?.address,
         programId,
       });
 
@@ -339,10 +352,11 @@ export default function CreateVaultPage() {
         vault: vaultPda,
         counter: counterPda,
         vaultTokenAccount,
-        usdcMint: new PublicKey(USDC_MINT),
+        mint: new PublicKey(USDC_MINT),
         creatorUsdcAta,
         creator: publicKey,
         amount: amountLamports,
+        walletFlags: walletFlags?.address,
         programId,
       });
 
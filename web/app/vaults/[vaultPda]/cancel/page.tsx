@@ -193,7 +193,7 @@ export default function CancelVaultPage() {
           vault: vaultPdaKey,
           counter: counterPda,
           vaultTokenAccount,
-          usdcMint: new PublicKey(USDC_MINT),
+          mint: new PublicKey(USDC_MINT),
           creatorUsdcAta,
           treasuryUsdcAta,
           creator: creatorKey,
@@ -7,7 +7,7 @@ import { formatDateTime } from '../../../_lib/format';
 import { getVaultMeta } from '../../../_lib/storage';
 import { connection, PROGRAM_ID } from '../../../_lib/solana';
 import { PublicKey, Transaction } from '@solana/web3.js';
-import { checkInInstruction } from '../../../_lib/instructions';
+import { checkInInstruction, findCheckinDelegates } from '../../../_lib/instructions';
 import { useNotifications } from '../../../_contexts/NotificationContext';
 import Link from 'next/link';
 
@@ -23,6 +23,7 @@ export default function CheckInPage() {
   const [step, setStep] = useState<Step>('confirm');
   const [vaultName, setVaultName] = useState<string>('Vault');
   const [unlockUnix, setUnlockUnix] = useState<number>(0);
+  const [creator, setCreator] = useState<PublicKey | null>(null);
   const [txSignature, setTxSignature] = useState<string>('');
   const [errorMessage, setErrorMessage] = useState<string>('');
 
@@ -56,6 +57,7 @@ export default function CheckInPage() {
       const offset = 8 + 32 + 32 + 32 + 32 + 8; // discriminator + 4 pubkeys + amount_locked
       const unlockTime = Number(accountInfo.data.readBigInt64LE(offset));
       setUnlockUnix(unlockTime);
+      setCreator(new PublicKey(accountInfo.data.subarray(8, 40)));
     } catch (error) {
       console.error('Failed to load vault:', error);
       setErrorMessage('Failed to load vault');
@@ -70,18 +72,23 @@ export default function CheckInPage() {
     setErrorMessage('');
 
     try {
-      // Derive counter PDA
+      // Derive counter PDA (the creator's; a delegate may be signing)
+      const creatorKey = creator ?? publicKey;
+      const programId = new PublicKey(PROGRAM_ID);
       const [counterPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from('vault_counter'), publicKey.toBuffer()],
-        new PublicKey(PROGRAM_ID)
+        [Buffer.from('vault_counter'), creatorKey.toBuffer()],
+        programId
       );
 
       // Build check-in instruction
       const checkInIx = await checkInInstruction({
         vault: new PublicKey(vaultPda),
         counter: counterPda,
-        creator: publicKey,
-        programId: new PublicKey(PROGRAM_ID),
+        signer: publicKey,
+        checkinDelegates: publicKey.equals(creatorKey)
+          ? undefined
+          : findCheckinDelegates(programId, new PublicKey(vaultPda)),
+        programId,
       });
 
       const transaction = new Transaction().add(checkInIx);
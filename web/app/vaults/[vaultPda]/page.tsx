@@ -11,7 +11,14 @@ import Link from 'next/link';
 import { Connection, PublicKey, Transaction } from '@solana/web3.js';
 import { getAssociatedTokenAddress } from '@solana/spl-token';
 import { connection, PROGRAM_ID, USDC_MINT } from '../../_lib/solana';
-import { releaseInstruction, closeVaultInstruction, checkInInstruction } from '../../_lib/instructions';
+import {
+  releasePayees,
+  fetchTreasury,
+  releaseInstruction,
+  closeVaultInstruction,
+  checkInInstruction,
+  findCheckinDelegates,
+} from '../../_lib/instructions';
 import { useNotifications } from '../../_contexts/NotificationContext';
 
 interface VaultDetail {
@@ -235,7 +242,20 @@ export default function VaultDetailPage() {
     try {
       const programId = new PublicKey(PROGRAM_ID);
       const vaultPdaKey = new PublicKey(vaultPda);
-      const beneficiaryKey = new PublicKey(vault.beneficiary);
+
+      // Current payees: a matured beneficiary change applies at release, and split vaults
+      // also pay the other beneficiaries (their ATAs are created if missing)
+      const {
+        beneficiary: beneficiaryKey,
+        atas: additionalBeneficiaryAtas,
+        createAtaInstructions,
+        vesting,
+        mint,
+        tokenProgram,
+        supportedMint,
+        vaultAssets,
+        assetAccounts,
+      } = await releasePayees(connection, vaultPdaKey, publicKey);
       const creatorKey = new PublicKey(vault.creator);
 
       // Derive vault counter PDA
@@ -246,15 +266,26 @@ export default function VaultDetailPage() {
 
       // Derive vault token account
       const vaultTokenAccount = await getAssociatedTokenAddress(
-        new PublicKey(USDC_MINT),
+        mint,
         vaultPdaKey,
-        true // allowOwnerOffCurve = true for PDA
+        true, // allowOwnerOffCurve = true for PDA
+        tokenProgram
       );
 
-      // Get beneficiary's USDC token account
+      // Get beneficiary's token account in the vault's mint
       const beneficiaryUsdcAta = await getAssociatedTokenAddress(
-        new PublicKey(USDC_MINT),
-        beneficiaryKey
+        mint,
+        beneficiaryKey,
+        false,
+        tokenProgram
+      );
+
+      // Treasury ATA receives the release fee (if the tier charges one)
+      const treasuryUsdcAta = await getAssociatedTokenAddress(
+        mint,
+        await fetchTreasury(connection, programId),
+        false,
+        tokenProgram
       );
 
       // Build release instruction
@@ -262,15 +293,23 @@ export default function VaultDetailPage() {
         vault: vaultPdaKey,
         counter: counterPda,
         vaultTokenAccount,
-        usdcMint: new PublicKey(USDC_MINT),
+        mint,
         beneficiaryUsdcAta,
         beneficiary: beneficiaryKey,
+        treasuryUsdcAta,
+        additionalBeneficiaryAtas,
+        tranche: vesting,
+        supportedMint,
+        tokenProgram,
+        vaultAssets,
+        creator: vaultAssets ? creatorKey : undefined,
+        assetAccounts,
         payer: publicKey,
         programId,
       });
 
       // Create and send transaction
-      const transaction = new Transaction().add(instruction);
+      const transaction = new Transaction().add(...createAtaInstructions, instruction);
 
       // Simulate first to get better error messages
       console.log('Simulating release transaction...');
@@ -397,7 +436,9 @@ export default function VaultDetailPage() {
       const instruction = await checkInInstruction({
         vault: vaultPdaKey,
         counter: counterPda,
-        creator: publicKey,
+        signer: publicKey,
+        // Anyone but the creator checks in as a delegate
+        checkinDelegates: publicKey.equals(creatorKey) ? undefined : findCheckinDelegates(programId, vaultPdaKey),
         programId,
       });
 
@@ -5,10 +5,10 @@ import { useRouter, useParams } from 'next/navigation';
 import { useEffect, useState } from 'react';
 import { formatUSDC, formatAddress, formatDateTime } from '../../../_lib/format';
 import { getVaultMeta, addActivityLog } from '../../../_lib/storage';
-import { connection, PROGRAM_ID, USDC_MINT } from '../../../_lib/solana';
+import { connection, PROGRAM_ID } from '../../../_lib/solana';
 import { PublicKey, Transaction } from '@solana/web3.js';
 import { getAssociatedTokenAddress, TOKEN_PROGRAM_ID } from '@solana/spl-token';
-import { releaseInstruction } from '../../../_lib/instructions';
+import { fetchTreasury, releasePayees, releaseInstruction } from '../../../_lib/instructions';
 import { useNotifications } from '../../../_contexts/NotificationContext';
 import Link from 'next/link';
 
@@ -113,7 +113,20 @@ export default function ReleasePage() {
     try {
       const programId = new PublicKey(PROGRAM_ID);
       const vaultPdaKey = new PublicKey(vaultPda);
-      const beneficiaryKey = new PublicKey(vault.beneficiary);
+
+      // Current payees: a matured beneficiary change applies at release, and split vaults
+      // also pay the other beneficiaries (their ATAs are created if missing)
+      const {
+        beneficiary: beneficiaryKey,
+        atas: additionalBeneficiaryAtas,
+        createAtaInstructions,
+        vesting,
+        mint,
+        tokenProgram,
+        supportedMint,
+        vaultAssets,
+        assetAccounts,
+      } = await releasePayees(connection, vaultPdaKey, publicKey);
       const creatorKey = new PublicKey(vault.creator);
 
       // Derive vault counter PDA
@@ -124,15 +137,26 @@ export default function ReleasePage() {
 
       // Derive vault token account
       const vaultTokenAccount = await getAssociatedTokenAddress(
-        new PublicKey(USDC_MINT),
+        mint,
         vaultPdaKey,
-        true // allowOwnerOffCurve = true for PDA
+        true, // allowOwnerOffCurve = true for PDA
+        tokenProgram
       );
 
-      // Get beneficiary's USDC token account
+      // Get beneficiary's token account in the vault's mint
       const beneficiaryUsdcAta = await getAssociatedTokenAddress(
-        new PublicKey(USDC_MINT),
-        beneficiaryKey
+        mint,
+        beneficiaryKey,
+        false,
+        tokenProgram
+      );
+
+      // Treasury ATA receives the release fee (if the tier charges one)
+      const treasuryUsdcAta = await getAssociatedTokenAddress(
+        mint,
+        await fetchTreasury(connection, programId),
+        false,
+        tokenProgram
       );
 
       // Build release instruction
@@ -140,15 +164,23 @@ export default function ReleasePage() {
         vault: vaultPdaKey,
         counter: counterPda,
         vaultTokenAccount,
-        usdcMint: new PublicKey(USDC_MINT),
+        mint,
         beneficiaryUsdcAta,
         beneficiary: beneficiaryKey,  // Actual beneficiary from vault
+        treasuryUsdcAta,
+        additionalBeneficiaryAtas,
+        tranche: vesting,
+        supportedMint,
+        tokenProgram,
+        vaultAssets,
+        creator: vaultAssets ? creatorKey : undefined,
+        assetAccounts,
         payer: publicKey,  // Pays for beneficiary ATA creation if needed (connected wallet signs)
         programId,
       });
 
       // Create and send transaction
-      const transaction = new Transaction().add(instruction);
+      const transaction = new Transaction().add(...createAtaInstructions, instruction);
 
       // Simulate first to get better error messages
       console.log('Simulating release transaction...');
@@ -8,7 +8,8 @@ import { getVaultCache, updateLastSeen, saveVaultMeta } from '../_lib/storage';
 import { checkAllVaultNotifications, filterDismissedNotifications, type VaultForNotification } from '../_lib/notifications';
 import { NotificationBanner } from '../_components/NotificationBanner';
 import Link from 'next/link';
-import { Connection, PublicKey } from '@solana/web3.js';
+import { Connection } from '@solana/web3.js';
+import { decodeVault, tierIndex, vaultProgram, VAULT_FILTER } from '../_lib/vault-account';
 
 interface Vault {
   vaultPda: string;
@@ -61,12 +62,13 @@ export default function VaultsPage() {
       const userAddress = publicKey.toBase58();
       const cache = getVaultCache();
       const connection = new Connection('https://api.devnet.solana.com', 'confirmed');
-      const programId = new PublicKey('74v7NZh7A6SH9DmKZRC4tFUwaLvq19KfD1NGni62XQJK');
+      const programId = vaultProgram.programId;
 
       // Scan blockchain for all vaults created by this user
       console.log('Scanning blockchain for vaults created by', userAddress);
       const accounts = await connection.getProgramAccounts(programId, {
         filters: [
+          VAULT_FILTER,
           {
             memcmp: {
               offset: 8, // After discriminator
@@ -81,52 +83,30 @@ export default function VaultsPage() {
       // Process all on-chain vaults
       const onChainVaults = accounts.map((account) => {
         try {
-          const data = account.account.data;
-
-          // Filter out old schema vaults
-          // Old vaults are undeserializable and should be hidden
-          const CURRENT_VAULT_SIZE = 237; // 8 discriminator + 229 struct data (with dead man's switch fields)
-          if (data.length !== CURRENT_VAULT_SIZE) {
-            console.log('Skipping vault with old schema:', account.pubkey.toBase58().slice(0, 8) + '... (size:', data.length, 'bytes, expected:', CURRENT_VAULT_SIZE, ')');
+          // v1 vaults can't be decoded until migrate_vault runs; hide them
+          const vault = decodeVault(account.account.data);
+          if (!vault) {
+            console.log('Skipping v1 vault awaiting migration:', account.pubkey.toBase58().slice(0, 8) + '...');
             return null;
           }
 
-          const creator = new PublicKey(data.slice(8, 40));
-          const beneficiary = new PublicKey(data.slice(40, 72));
-          const amountLockedBuf = data.slice(136, 144);
-          const unlockUnixBuf = data.slice(144, 152);
-          const released = data[152] === 1;
-          const cancelled = data[153] === 1;
-          const notificationWindowBuf = data.slice(200, 204);
-          const gracePeriodBuf = data.slice(204, 208);
-          const lastCheckinBuf = data.slice(208, 216);
-          const tier = data[216];
-          const checkinPeriodBuf = data.slice(233, 237);
-
-          const amountLocked = Number(new DataView(amountLockedBuf.buffer, amountLockedBuf.byteOffset, 8).getBigUint64(0, true));
-          const unlockUnix = Number(new DataView(unlockUnixBuf.buffer, unlockUnixBuf.byteOffset, 8).getBigInt64(0, true));
-          const notificationWindowSeconds = new DataView(notificationWindowBuf.buffer, notificationWindowBuf.byteOffset, 4).getUint32(0, true);
-          const gracePeriodSeconds = new DataView(gracePeriodBuf.buffer, gracePeriodBuf.byteOffset, 4).getUint32(0, true);
-          const lastCheckinUnix = Number(new DataView(lastCheckinBuf.buffer, lastCheckinBuf.byteOffset, 8).getBigInt64(0, true));
-          const checkinPeriodSeconds = new DataView(checkinPeriodBuf.buffer, checkinPeriodBuf.byteOffset, 4).getUint32(0, true);
-
           // Find cached metadata or use defaults
           const cachedMeta = cache.find((m) => m.vaultPda === account.pubkey.toBase58());
 
           return {
             vaultPda: account.pubkey.toBase58(),
             name: cachedMeta?.name || 'Unnamed Vault',
-            creator: creator.toBase58(),
-            beneficiary: beneficiary.toBase58(),
-            amountLocked,
-            unlockUnix,
-            released,
-            cancelled,
-            tier,
-            checkinPeriodSeconds,
-            notificationWindowSeconds,
-            gracePeriodSeconds,
-            lastCheckinUnix,
+            creator: vault.creator.toBase58(),
+            beneficiary: vault.beneficiary.toBase58(),
+            amountLocked: vault.amountLocked.toNumber(),
+            unlockUnix: vault.unlockUnix.toNumber(),
+            released: vault.released,
+            cancelled: vault.cancelled,
+            tier: tierIndex(vault.tier),
+            checkinPeriodSeconds: vault.checkinPeriodSeconds,
+            notificationWindowSeconds: vault.notificationWindowSeconds,
+            gracePeriodSeconds: vault.gracePeriodSeconds,
+            lastCheckinUnix: vault.lastCheckinUnix.toNumber(),
           };
         } catch (error) {
           console.error('Error parsing vault', account.pubkey.toBase58(), error);
@@ -134,6 +134,7 @@ async function main() {
         { pubkey: vaultPda, isSigner: false, isWritable: true },
         { pubkey: vaultTokenAccountKey, isSigner: false, isWritable: false },
         { pubkey: admin.publicKey, isSigner: true, isWritable: false },
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // No role assignment: signing as admin
       ];
 
       const instruction = new TransactionInstruction({ keys, programId: PROGRAM_ID, data: discriminator });
@@ -56,6 +56,7 @@ async function main() {
     { pubkey: VAULT_PDA, isSigner: false, isWritable: true },
     { pubkey: vaultTokenAccount, isSigner: false, isWritable: false },
     { pubkey: admin.publicKey, isSigner: true, isWritable: false },
+    { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // No role assignment: signing as admin
   ];
 
   const instruction = new TransactionInstruction({